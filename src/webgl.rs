@@ -1,11 +1,24 @@
 use super::*;
 
-mod webglprogrambuilder; pub use webglprogrambuilder::WebGlProgramBuilder;
+mod webglprogrambuilder; pub use webglprogrambuilder::{WebGlProgramBuilder, ProgramCache};
+mod shaderlibrary;
 
 pub mod renderer; use renderer::Renderer;
+mod color; pub use color::Color;
+pub mod material; pub use material::Material;
+mod linestyle; pub use linestyle::{LineStyle, LineCap, LineJoin, DashUnits};
+pub mod texture; pub use texture::{Texture, TextureFilter, TextureWrap};
+mod scalarstyle; pub use scalarstyle::{ScalarStyle, Colormap};
+mod pointstyle; pub use pointstyle::{PointStyle, PointShape, PointSizeUnits};
 pub mod camera; pub use camera::Camera;
 pub mod grid; pub use grid::Grid;
 pub mod hexahedron; pub use hexahedron::Hexahedron;
+pub mod instancedmesh; pub use instancedmesh::InstancedMesh;
+pub mod batch; pub use batch::Batch;
+pub mod label; pub use label::{Label, LabelStyle};
+pub mod mesh; pub use mesh::Mesh;
+pub mod pointcloud; pub use pointcloud::PointCloud;
+pub mod customelement; pub use customelement::CustomElement;
 mod transform;
 mod traits; pub use traits::{Drawable, Identifiable, Transformable};
 
@@ -16,6 +29,12 @@ mod traits; pub use traits::{Drawable, Identifiable, Transformable};
 pub enum DrawableElement {
   Grid(grid::Grid),
   Hexahedron(hexahedron::Hexahedron),
+  InstancedMesh(instancedmesh::InstancedMesh),
+  Batch(batch::Batch),
+  Label(label::Label),
+  Mesh(mesh::Mesh),
+  PointCloud(pointcloud::PointCloud),
+  CustomElement(customelement::CustomElement),
 }
 
 #[wasm_bindgen::prelude::wasm_bindgen(inline_js = "export function try_as_grid(v) { return v; }")]
@@ -30,6 +49,42 @@ extern "C" {
   fn try_as_hexahedron(v: wasm_bindgen::JsValue) -> Result<hexahedron::Hexahedron, wasm_bindgen::JsValue>;
 }
 
+#[wasm_bindgen::prelude::wasm_bindgen(inline_js = "export function try_as_instanced_mesh(v) { return v; }")]
+extern "C" {
+  #[wasm_bindgen::prelude::wasm_bindgen(catch)]
+  fn try_as_instanced_mesh(v: wasm_bindgen::JsValue) -> Result<instancedmesh::InstancedMesh, wasm_bindgen::JsValue>;
+}
+
+#[wasm_bindgen::prelude::wasm_bindgen(inline_js = "export function try_as_batch(v) { return v; }")]
+extern "C" {
+  #[wasm_bindgen::prelude::wasm_bindgen(catch)]
+  fn try_as_batch(v: wasm_bindgen::JsValue) -> Result<batch::Batch, wasm_bindgen::JsValue>;
+}
+
+#[wasm_bindgen::prelude::wasm_bindgen(inline_js = "export function try_as_label(v) { return v; }")]
+extern "C" {
+  #[wasm_bindgen::prelude::wasm_bindgen(catch)]
+  fn try_as_label(v: wasm_bindgen::JsValue) -> Result<label::Label, wasm_bindgen::JsValue>;
+}
+
+#[wasm_bindgen::prelude::wasm_bindgen(inline_js = "export function try_as_mesh(v) { return v; }")]
+extern "C" {
+  #[wasm_bindgen::prelude::wasm_bindgen(catch)]
+  fn try_as_mesh(v: wasm_bindgen::JsValue) -> Result<mesh::Mesh, wasm_bindgen::JsValue>;
+}
+
+#[wasm_bindgen::prelude::wasm_bindgen(inline_js = "export function try_as_point_cloud(v) { return v; }")]
+extern "C" {
+  #[wasm_bindgen::prelude::wasm_bindgen(catch)]
+  fn try_as_point_cloud(v: wasm_bindgen::JsValue) -> Result<pointcloud::PointCloud, wasm_bindgen::JsValue>;
+}
+
+#[wasm_bindgen::prelude::wasm_bindgen(inline_js = "export function try_as_custom_element(v) { return v; }")]
+extern "C" {
+  #[wasm_bindgen::prelude::wasm_bindgen(catch)]
+  fn try_as_custom_element(v: wasm_bindgen::JsValue) -> Result<customelement::CustomElement, wasm_bindgen::JsValue>;
+}
+
 #[wasm_bindgen::prelude::wasm_bindgen(inline_js = "export function get_type_name(v) { return v.type_name(); }")]
 extern "C" {
   #[wasm_bindgen::prelude::wasm_bindgen(catch)]
@@ -51,6 +106,12 @@ impl std::convert::TryFrom<wasm_bindgen::JsValue> for DrawableElement {
     match name.as_str() {
       grid::Grid::TYPE_NAME => try_as_grid(v).map(|r| r.into()).map_err(|e| { web_sys::console::error_1(&e); WebglError::DrawableError }),
       hexahedron::Hexahedron::TYPE_NAME => try_as_hexahedron(v).map(|r| r.into()).map_err(|e| { web_sys::console::error_1(&e); WebglError::DrawableError }),
+      instancedmesh::InstancedMesh::TYPE_NAME => try_as_instanced_mesh(v).map(|r| r.into()).map_err(|e| { web_sys::console::error_1(&e); WebglError::DrawableError }),
+      batch::Batch::TYPE_NAME => try_as_batch(v).map(|r| r.into()).map_err(|e| { web_sys::console::error_1(&e); WebglError::DrawableError }),
+      label::Label::TYPE_NAME => try_as_label(v).map(|r| r.into()).map_err(|e| { web_sys::console::error_1(&e); WebglError::DrawableError }),
+      mesh::Mesh::TYPE_NAME => try_as_mesh(v).map(|r| r.into()).map_err(|e| { web_sys::console::error_1(&e); WebglError::DrawableError }),
+      pointcloud::PointCloud::TYPE_NAME => try_as_point_cloud(v).map(|r| r.into()).map_err(|e| { web_sys::console::error_1(&e); WebglError::DrawableError }),
+      customelement::CustomElement::TYPE_NAME => try_as_custom_element(v).map(|r| r.into()).map_err(|e| { web_sys::console::error_1(&e); WebglError::DrawableError }),
       _ => Err(WebglError::DrawableUnsupported(name)),
     }
   }
@@ -61,6 +122,12 @@ impl std::convert::Into<wasm_bindgen::JsValue> for DrawableElement {
     match self {
       DrawableElement::Grid(v)       => v.into(),
       DrawableElement::Hexahedron(v) => v.into(),
+      DrawableElement::InstancedMesh(v) => v.into(),
+      DrawableElement::Batch(v) => v.into(),
+      DrawableElement::Label(v) => v.into(),
+      DrawableElement::Mesh(v) => v.into(),
+      DrawableElement::PointCloud(v) => v.into(),
+      DrawableElement::CustomElement(v) => v.into(),
     }
   }
 }
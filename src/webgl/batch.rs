@@ -0,0 +1,244 @@
+use super::*;
+
+/// UID initialisation function
+fn nano_id() -> String { nanoid::nanoid!(6) }
+
+/// Collection of drawable elements whose geometry is merged into one draw per material and primitive type.
+/// The merged geometry and its GPU buffers are cached and rebuilt only when elements are added, replaced or
+/// removed. Instanced geometry is drawn as is. Picking resolves the uid of the original elements.
+#[cfg(feature = "wasm")]
+#[wasm_bindgen::prelude::wasm_bindgen]
+#[derive(Clone)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Batch {
+  #[serde(default = "nano_id")]
+  uid:      String,
+  #[serde(default)]
+  elements: Vec<DrawableElement>,
+  #[serde(skip)]
+  merged:   std::rc::Rc<std::cell::RefCell<Option<Vec<renderer::OwnedInfo>>>>,
+}
+
+impl Batch {
+  pub const TYPE_NAME: &str = "Batch";
+
+  /// Add an element, replacing any element with the same uid
+  pub fn with_drawable_element(mut self, element: DrawableElement) -> Result<Self, Error> {
+    let uid = element.uuid()?;
+    self.elements.retain(|e| e.uuid().map(|u| u != uid).unwrap_or(true));
+    self.elements.push(element);
+    Ok(self.invalidate())
+  }
+
+  /// Discard the merged geometry
+  fn invalidate(mut self) -> Self {
+    self.merged = std::rc::Rc::new(std::cell::RefCell::new(None));
+    self
+  }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen::prelude::wasm_bindgen]
+impl Batch {
+  /// Create an empty `Batch`
+  pub fn new() -> Batch {
+    Batch { uid: nano_id(), elements: Vec::new(), merged: std::rc::Rc::new(std::cell::RefCell::new(None)) }
+  }
+
+  /// Retrieve the object id
+  /// Exposed to JavaScript
+  pub fn uuid(&self) -> Result<String, JsError> {
+    Ok(self.uid.clone())
+  }
+
+  /// Retrieve type name
+  /// Exposed to JavaScript
+  pub fn type_name(&self) -> Result<String, JsError> {
+    Ok(Self::TYPE_NAME.to_string())
+  }
+
+  /// Add a drawable element, replacing any element with the same uid - for example after its geometry changed.
+  /// Exposed to JavaScript
+  pub fn with_element(self, element: wasm_bindgen::JsValue) -> Result<Batch, JsError> {
+    let element = DrawableElement::try_from(element).map_err(|e| Error::from(e.to_string()))?;
+    Ok(self.with_drawable_element(element)?)
+  }
+
+  /// Remove the element with the given uid. Exposed to JavaScript
+  pub fn without_element(mut self, uid: &str) -> Self {
+    self.elements.retain(|e| e.uuid().map(|u| u != uid).unwrap_or(true));
+    self.invalidate()
+  }
+
+  /// Number of elements. Exposed to JavaScript
+  pub fn n_elements(&self) -> usize {
+    self.elements.len()
+  }
+
+  /// Draw the elements on the context
+  /// Exposed to JavaScript
+  pub fn draw(&self, context: &web_sys::WebGl2RenderingContext, renderer: &renderer::Renderer) -> Result<(), JsError> {
+    Drawable::draw(self, context, renderer)
+  }
+
+  pub fn clone(&self) -> Self {
+    Clone::clone(self)
+  }
+}
+
+impl Default for Batch {
+  fn default() -> Batch { Batch::new() }
+}
+
+impl Identifiable for Batch {
+  /// Retrieve the object uuid
+  fn uuid(&self) -> Result<String, Error> {
+    Ok(self.uid.clone())
+  }
+}
+
+impl Drawable for Batch {
+  /// Draw the merged geometry, merging it first if required
+  fn draw<T>(&self, context: &web_sys::WebGl2RenderingContext, renderer: &T) -> Result<(), JsError> 
+  where T: renderer::RendererTrait {
+    if self.merged.borrow().is_none() {
+      let recorder = renderer::Recorder::new();
+      for element in self.elements.iter() { element.draw(context, &recorder)?; }
+      *self.merged.borrow_mut() = Some(self.merge(recorder.into_infos()));
+    }
+    let merged = self.merged.borrow();
+    for info in merged.as_ref().ok_or("Unable to retrieve merged geometry")?.iter() {
+      renderer.draw(context, info.as_info())?;
+    }
+    Ok(())
+  }
+}
+
+/// Primitive type of merged geometry
+#[derive(PartialEq)]
+enum Primitive { Lines, Triangles }
+
+/// Geometry of the elements sharing a primitive type and material
+struct Group {
+  primitive: Primitive,
+  material:  Material,
+  uids:      Vec<String>,
+  elements:  Vec<u32>,
+  positions: Vec<nalgebra::Point3<f32>>,
+  normals:   Vec<nalgebra::Vector3<f32>>,
+  indices:   Vec<u32>,
+}
+
+impl Batch {
+  /// Merge the information recorded from the elements
+  fn merge(&self, infos: Vec<renderer::OwnedInfo>) -> Vec<renderer::OwnedInfo> {
+    use renderer::OwnedInfo;
+
+    let mut groups: Vec<Group> = Vec::new();
+    let mut merged = Vec::new();
+    for info in infos.into_iter() {
+      let (primitive, uid, positions, normals, indices, material) = match info {
+        OwnedInfo::Lines { uid, vertices, material } => {
+          let n = 2*vertices.len() as u32;
+          (Primitive::Lines, uid, vertices.into_iter().flat_map(|(s, e)| [s, e]).collect(), Vec::new(), (0..n).collect(), material)
+        },
+        OwnedInfo::IndexedLines { uid, positions, indices, material } => {
+          let indices = indices.iter().map(|i| i as u32).collect::<Vec<u32>>();
+          (Primitive::Lines, uid, positions, Vec::new(), indices, material)
+        },
+        OwnedInfo::TrianglesWithNormals { uid, vertices, normals, material } => {
+          let n = 3*vertices.len() as u32;
+          (Primitive::Triangles, uid, 
+            vertices.into_iter().flat_map(|(p1, p2, p3)| [p1, p2, p3]).collect(), 
+            normals.into_iter().flat_map(|(n1, n2, n3)| [n1, n2, n3]).collect(), 
+            (0..n).collect(), material)
+        },
+        OwnedInfo::IndexedTrianglesWithNormals { uid, positions, normals, indices, material } => {
+          let indices = indices.iter().map(|i| i as u32).collect::<Vec<u32>>();
+          (Primitive::Triangles, uid, positions, normals, indices, material)
+        },
+        info => { merged.push(info); continue; },
+      };
+
+      let group = match groups.iter().position(|g| g.primitive == primitive && g.material == material) {
+        Some(i) => &mut groups[i],
+        None => {
+          groups.push(Group { primitive, material, uids: Vec::new(), elements: Vec::new(), 
+            positions: Vec::new(), normals: Vec::new(), indices: Vec::new() });
+          groups.last_mut().unwrap()
+        },
+      };
+      if group.uids.last() != Some(&uid) { group.uids.push(uid); }
+      let element = (group.uids.len() - 1) as u32;
+      let offset = group.positions.len() as u32;
+      group.elements.extend(std::iter::repeat_n(element, positions.len()));
+      group.positions.extend(positions);
+      group.normals.extend(normals);
+      group.indices.extend(indices.into_iter().map(|i| i + offset));
+    }
+
+    groups.into_iter()
+    .map(|g| {
+      let indices = renderer::Indices::new(g.indices);
+      let info = match g.primitive {
+        Primitive::Lines => OwnedInfo::IndexedLines { uid: self.uid.clone(), positions: g.positions, indices, material: g.material },
+        Primitive::Triangles => OwnedInfo::IndexedTrianglesWithNormals { uid: self.uid.clone(), positions: g.positions, normals: g.normals, indices, material: g.material },
+      };
+      OwnedInfo::Batched { uids: g.uids, elements: g.elements, key: renderer::BatchKey::new(), info: Box::new(info) }
+    })
+    .chain(merged)
+    .collect()
+  }
+}
+
+#[cfg(all(test, feature = "wasm"))]
+mod tests {
+  use super::*;
+  use renderer::OwnedInfo;
+  use crate::webgl::material::materialbuilder::MaterialBuilder;
+
+  fn p(x: f32, y: f32, z: f32) -> nalgebra::Point3<f32> { nalgebra::Point3::new(x, y, z) }
+
+  /// Triangle of the element `uid` with the `material`
+  fn triangle(uid: &str, material: &Material) -> OwnedInfo {
+    let n = nalgebra::Vector3::z();
+    OwnedInfo::TrianglesWithNormals { uid: uid.into(), vertices: vec![(p(0.0, 0.0, 0.0), p(1.0, 0.0, 0.0), p(0.0, 1.0, 0.0))],
+      normals: vec![(n, n, n)], material: material.clone() }
+  }
+
+  /// Indexed segment of the element `uid`
+  fn segment(uid: &str) -> OwnedInfo {
+    OwnedInfo::IndexedLines { uid: uid.into(), positions: vec![p(0.0, 0.0, 0.0), p(1.0, 1.0, 1.0)],
+      indices: renderer::Indices::new(vec![1, 0]), material: Material::default() }
+  }
+
+  #[test]
+  fn elements_are_grouped_by_primitive_and_material() {
+    let red = MaterialBuilder::new().color(Color::rgb(1.0, 0.0, 0.0)).build_material().unwrap();
+    let infos = vec![triangle("a", &Material::default()), segment("b"), triangle("c", &red), triangle("d", &Material::default())];
+    let merged = Batch::new().merge(infos);
+    let uids = merged.iter().map(|info| match info {
+      OwnedInfo::Batched { uids, .. } => uids.clone(),
+      _ => panic!("Merged geometry is expected to be batched"),
+    }).collect::<Vec<_>>();
+    assert_eq!(uids, vec![vec!["a", "d"], vec!["b"], vec!["c"]]);
+  }
+
+  #[test]
+  fn indices_are_offset_by_the_previous_positions() {
+    let merged = Batch::new().merge(vec![segment("a"), segment("b")]);
+    let [OwnedInfo::Batched { elements, info, .. }] = merged.as_slice() else { panic!("One batch is expected") };
+    let OwnedInfo::IndexedLines { positions, indices, .. } = info.as_ref() else { panic!("Lines are expected") };
+    assert_eq!(positions.len(), 4);
+    assert_eq!(indices.iter().collect::<Vec<_>>(), vec![1, 0, 3, 2]);
+    assert_eq!(elements, &vec![0, 0, 1, 1]);
+  }
+
+  #[test]
+  fn instanced_geometry_is_drawn_as_is() {
+    let instanced = OwnedInfo::InstancedTrianglesWithNormals { uid: "a".into(), positions: Vec::new(), normals: Vec::new(),
+      indices: renderer::Indices::new(Vec::new()), instances: renderer::Instances::default(), material: Material::default() };
+    let merged = Batch::new().merge(vec![instanced, triangle("b", &Material::default())]);
+    assert!(matches!(merged.as_slice(), [OwnedInfo::Batched { .. }, OwnedInfo::InstancedTrianglesWithNormals { .. }]));
+  }
+}
@@ -17,6 +17,8 @@ pub struct Camera {
   eye:    nalgebra::Point3<f32>,
   target: nalgebra::Point3<f32>,
   up:     nalgebra::Vector3<f32>,
+  #[serde(default)]
+  clipping: renderer::Clipping,
   #[serde(skip, default = "make_false")]
   updated: bool,
   #[serde(skip)]
@@ -35,6 +37,7 @@ impl Camera {
     let up = si.cross(&fw).normalize();
     Ok(Camera {
       width, height, fov, eye, target, up,
+      clipping: renderer::Clipping::default(),
       updated: true,
       mouse_move: None,
       mouse_select: None,
@@ -242,11 +245,37 @@ impl Camera {
   /// Retrieve the update status
   pub fn updated(&self) -> bool { self.updated }
 
+  /// Update the section view `Clipping` and return updated object
+  pub fn with_clipping(mut self, clipping: renderer::Clipping) -> Camera { self.clipping = clipping; self.updated = true; self }
+
+  /// Retrieve the section view `Clipping`
+  pub fn clipping(&self) -> renderer::Clipping { self.clipping.clone() }
+
+  /// Move the clipping plane at `index` along its normal following a mouse drag of `(dx, dy)` pixels,
+  /// by the length of the drag projected on the screen direction of the normal
+  pub fn with_dragged_clip_plane(mut self, index: usize, dx: f32, dy: f32) -> Result<Camera, JsError> {
+    let plane = self.clipping.plane(index).ok_or(format!("No clipping plane at index {index}"))?;
+    // World units per pixel at the target distance
+    let scale = 2.0 * self.distance() * (0.5 * self.fov).tan() / self.height;
+    let displacement = (self.side() * dx - self.up() * dy) * scale;
+    let distance = displacement.dot(&plane.normal_vector());
+    self.clipping = self.clipping.with_translated_plane(index, distance)?;
+    self.updated = true;
+    Ok(self)
+  }
+
   /// Convert camera to json
   pub fn to_json(&self) -> Result<String, JsError> {
     Ok(serde_json::to_string(&self).map_err(|e| format!("{e}"))?)
   }
 
+  /// Create camera from json
+  pub fn from_json(json: &str) -> Result<Camera, JsError> {
+    let mut camera: Camera = serde_json::from_str(json).map_err(|e| format!("{e}"))?;
+    camera.updated = true;
+    Ok(camera)
+  }
+
   /// Trigger a pick_hover
   pub fn pick_hover(&self) -> Result<wasm_bindgen::JsValue, JsError> {
     let r = self.mouse_move
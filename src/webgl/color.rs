@@ -0,0 +1,60 @@
+
+/// RGBA colour with components in the range `[0, 1]`
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Color {
+  r: f32,
+  g: f32,
+  b: f32,
+  #[serde(default = "Color::opaque")]
+  a: f32,
+}
+
+impl Color {
+  fn opaque() -> f32 { 1.0 }
+
+  /// Create a colour from a slice `[r, g, b]` or `[r, g, b, a]`. Missing components default to 0 (alpha to 1)
+  pub fn from_slice(v: &[f32]) -> Color {
+    Color {
+      r: v.first().copied().unwrap_or(0.0),
+      g: v.get(1).copied().unwrap_or(0.0),
+      b: v.get(2).copied().unwrap_or(0.0),
+      a: v.get(3).copied().unwrap_or(1.0),
+    }
+  }
+
+  /// Red component
+  pub fn r(&self) -> f32 { self.r }
+  /// Green component
+  pub fn g(&self) -> f32 { self.g }
+  /// Blue component
+  pub fn b(&self) -> f32 { self.b }
+  /// Alpha component
+  pub fn a(&self) -> f32 { self.a }
+
+  /// Return the colour as an array `[r, g, b, a]`
+  pub fn as_array(&self) -> [f32; 4] { [self.r, self.g, self.b, self.a] }
+
+  /// Blend the red, green and blue components towards `other` by `t` in `[0, 1]`, keeping the alpha component
+  pub fn mix(&self, other: &Color, t: f32) -> Color {
+    Color { r: self.r + (other.r - self.r)*t, g: self.g + (other.g - self.g)*t, b: self.b + (other.b - self.b)*t, a: self.a }
+  }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+impl Color {
+  /// Create a colour from its red, green, blue and alpha components in the range `[0, 1]`
+  pub fn new(r: f32, g: f32, b: f32, a: f32) -> Color { Color { r, g, b, a } }
+
+  /// Create an opaque colour from its red, green and blue components in the range `[0, 1]`
+  pub fn rgb(r: f32, g: f32, b: f32) -> Color { Color { r, g, b, a: 1.0 } }
+
+  /// Create a colour from 8-bit red, green, blue and alpha components
+  pub fn from_u8(r: u8, g: u8, b: u8, a: u8) -> Color {
+    Color { r: r as f32/255.0, g: g as f32/255.0, b: b as f32/255.0, a: a as f32/255.0 }
+  }
+
+  /// Return a copy of the colour with the alpha component replaced
+  pub fn with_alpha(mut self, a: f32) -> Color { self.a = a; self }
+}
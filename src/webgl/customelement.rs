@@ -0,0 +1,174 @@
+use super::*;
+
+/// UID initialisation function
+fn nano_id() -> String { nanoid::nanoid!(6) }
+
+/// Geometry drawn by a user supplied `CustomProgram`: positions, the values of the attributes declared by the program,
+/// optional indices and the values of the uniforms declared by the program. Drawn and picked like the other elements.
+///
+/// Downstream crates may instead implement `Drawable` for their own types, emitting `renderer::Info::Custom`
+#[cfg(feature = "wasm")]
+#[wasm_bindgen::prelude::wasm_bindgen]
+#[derive(Clone)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct CustomElement {
+  #[serde(default = "nano_id")]
+  uid:        String,
+  program:    renderer::CustomProgram,
+  positions:  Vec<nalgebra::Point3<f32>>,
+  #[serde(default)]
+  attributes: Vec<(String, Vec<f32>)>,
+  #[serde(default)]
+  indices:    Option<Vec<u32>>,
+  #[serde(default)]
+  uniforms:   Vec<(String, renderer::Uniform)>,
+  #[serde(default)]
+  material:   Material,
+}
+
+impl CustomElement {
+  pub const TYPE_NAME: &str = "CustomElement";
+
+  /// Set the value of a uniform, replacing any previous value
+  pub fn with_uniform(mut self, name: &str, value: renderer::Uniform) -> Result<CustomElement, Error> {
+    if !self.program.uniforms().iter().any(|n| n == name) {
+      return Err(format!("Uniform {} is not declared by program {}", name, self.program.name()).into());
+    }
+    self.uniforms.retain(|(n, _)| n != name);
+    self.uniforms.push((name.to_string(), value));
+    Ok(self)
+  }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen::prelude::wasm_bindgen]
+impl CustomElement {
+  /// Create the element drawn by `program` from the flattened `positions` (3 values per vertex).
+  /// Exposed to JavaScript
+  pub fn new(program: renderer::CustomProgram, positions: &[f32]) -> Result<CustomElement, JsError> {
+    if !positions.len().is_multiple_of(3) { return Err(Error::from("Number of position values is not a multiple of 3").into()); }
+    Ok( CustomElement { uid: nanoid::nanoid!(6), program, positions: positions.chunks_exact(3).map(nalgebra::Point3::from_slice).collect(),
+      attributes: Vec::new(), indices: None, uniforms: Vec::new(), material: Material::default(), } )
+  }
+
+  /// Retrieve the object id
+  /// Exposed to JavaScript
+  pub fn uuid(&self) -> Result<String, JsError> {
+    Ok(self.uid.clone())
+  }
+
+  /// Retrieve type name
+  /// Exposed to JavaScript
+  pub fn type_name(&self) -> Result<String, JsError> {
+    Ok(Self::TYPE_NAME.to_string())
+  }
+
+  /// Number of vertices. Exposed to JavaScript
+  pub fn n_vertices(&self) -> usize {
+    self.positions.len()
+  }
+
+  /// Set the flattened `values` of the attribute `name` declared by the program, of its number of components per
+  /// vertex. Exposed to JavaScript
+  pub fn with_attribute(mut self, name: &str, values: &[f32]) -> Result<CustomElement, JsError> {
+    let (_, size) = self.program.attributes().iter().find(|(n, _)| n == name)
+      .ok_or(format!("Attribute {} is not declared by program {}", name, self.program.name()))?;
+    if values.len() != *size as usize * self.positions.len() {
+      return Err(Error::from(format!("Attribute {} requires {} values per vertex", name, size)).into());
+    }
+    self.attributes.retain(|(n, _)| n != name);
+    self.attributes.push((name.to_string(), values.to_vec()));
+    Ok(self)
+  }
+
+  /// Draw the primitives between the vertices referenced by `indices`, instead of consecutive vertices.
+  /// Exposed to JavaScript
+  pub fn with_indices(mut self, indices: Vec<u32>) -> Result<CustomElement, JsError> {
+    if indices.iter().any(|i| *i as usize >= self.positions.len()) { return Err(Error::from("Index exceeds the number of positions").into()); }
+    self.indices = Some(indices);
+    Ok(self)
+  }
+
+  /// Set the `int` uniform `name` declared by the program. Exposed to JavaScript
+  pub fn with_uniform_int(self, name: &str, value: i32) -> Result<CustomElement, JsError> {
+    Ok(self.with_uniform(name, renderer::Uniform::Int(value))?)
+  }
+
+  /// Set the `float` uniform `name` declared by the program. Exposed to JavaScript
+  pub fn with_uniform_float(self, name: &str, value: f32) -> Result<CustomElement, JsError> {
+    Ok(self.with_uniform(name, renderer::Uniform::Float(value))?)
+  }
+
+  /// Set the `vec2`, `vec3` or `vec4` uniform `name` declared by the program, from its 2, 3 or 4 `values`.
+  /// Exposed to JavaScript
+  pub fn with_uniform_vector(self, name: &str, values: &[f32]) -> Result<CustomElement, JsError> {
+    let value = match *values {
+      [x, y] => renderer::Uniform::Vec2([x, y]),
+      [x, y, z] => renderer::Uniform::Vec3([x, y, z]),
+      [x, y, z, w] => renderer::Uniform::Vec4([x, y, z, w]),
+      _ => return Err(Error::from("Vector uniforms require 2 to 4 values").into()),
+    };
+    Ok(self.with_uniform(name, value)?)
+  }
+
+  /// Retrieve the program. Exposed to JavaScript
+  pub fn program(&self) -> renderer::CustomProgram {
+    self.program.clone()
+  }
+
+  /// Replace the material. Exposed to JavaScript
+  pub fn with_material(mut self, material: Material) -> Self {
+    self.material = material; self
+  }
+
+  /// Retrieve the material. Exposed to JavaScript
+  pub fn material(&self) -> Material {
+    self.material.clone()
+  }
+
+  /// Draw the element on the context
+  /// Exposed to JavaScript
+  pub fn draw(&self, context: &web_sys::WebGl2RenderingContext, renderer: &renderer::Renderer) -> Result<(), JsError> {
+    Drawable::draw(self, context, renderer)
+  }
+
+  pub fn clone(&self) -> Self {
+    Clone::clone(self)
+  }
+
+  /// Convert the element to json
+  pub fn to_json(&self) -> Result<String, JsError> {
+    Ok(serde_json::to_string(&self).map_err(|e| format!("{e}"))?)
+  }
+
+  /// Create an element from json
+  pub fn from_json(json: &str) -> Result<CustomElement, JsError> {
+    Ok(serde_json::from_str(json).map_err(|e| format!("{e}"))?)
+  }
+}
+
+impl Identifiable for CustomElement {
+  /// Retrieve the object uuid
+  fn uuid(&self) -> Result<String, Error> {
+    Ok(self.uid.clone())
+  }
+}
+
+impl Drawable for CustomElement {
+  /// Draw the element with its program, once its attributes and uniforms match the program declarations
+  fn draw<T>(&self, context: &web_sys::WebGl2RenderingContext, renderer: &T) -> Result<(), JsError>
+  where T: renderer::RendererTrait {
+    self.program.validate(self.positions.len(), &self.attributes, &self.uniforms)?;
+    let indices = self.indices.clone().map(renderer::Indices::new);
+    let info = renderer::Info::Custom {
+      uid: &self.uid,
+      program: &self.program,
+      positions: &self.positions,
+      attributes: &self.attributes,
+      indices: indices.as_ref(),
+      uniforms: &self.uniforms,
+      material: &self.material,
+    };
+    Ok( renderer.draw(context, info)? )
+  }
+}
@@ -19,6 +19,8 @@ pub struct Grid {
   n:       u32,
   #[serde(default)]
   transform: Vec<transform::Transform>,
+  #[serde(default)]
+  material: Material,
 }
 
 impl Transformable for Grid {
@@ -42,9 +44,10 @@ impl Grid {
          tangent:  nalgebra::Vector3<f32>,
          center:   nalgebra::Point3<f32>,
          delta:    f32,
-         n:        u32) -> Result<Grid, Error>
+         n:        u32,
+         material: Material) -> Result<Grid, Error>
   {
-    Ok( Grid { uid: nanoid::nanoid!(6), normal, tangent, center, delta, n, transform: Vec::new(), } )
+    Ok( Grid { uid: nanoid::nanoid!(6), normal, tangent, center, delta, n, transform: Vec::new(), material, } )
   }
 
   /// Retrieve the object id
@@ -74,6 +77,16 @@ impl Grid {
     self.with_transform(transform.into())
   }
 
+  /// Replace the material. Exposed to JavaScript
+  pub fn with_material(mut self, material: Material) -> Self {
+    self.material = material; self
+  }
+
+  /// Retrieve the material. Exposed to JavaScript
+  pub fn material(&self) -> Material {
+    self.material.clone()
+  }
+
   /// Draw the grid on the context
   /// Exposed to JavaScript
   pub fn draw(&self, context: &web_sys::WebGl2RenderingContext, renderer: &renderer::Renderer) -> Result<(), JsError> {
@@ -106,13 +119,15 @@ impl Drawable for Grid {
   /// Draw the grid on the context
   fn draw<T>(&self, context: &web_sys::WebGl2RenderingContext, renderer: &T) -> Result<(), JsError> 
   where T: renderer::RendererTrait {
-    let vertices = self.vertices()?;
-    let vertices = vertices.into_iter()
-      .map(|(p1, p2)| Ok((self.transform_point(&p1)?, self.transform_point(&p2)?)) )
-      .collect::<Result<Vec<(nalgebra::Point3<f32>, nalgebra::Point3<f32>)>, Error>>()?;
-    let info = renderer::Info::Lines {
+    let (positions, indices) = self.vertices()?;
+    let positions = positions.iter()
+      .map(|p| self.transform_point(p))
+      .collect::<Result<Vec<nalgebra::Point3<f32>>, Error>>()?;
+    let info = renderer::Info::IndexedLines {
       uid: &self.uid,
-      vertices: &vertices 
+      positions: &positions,
+      indices: &indices,
+      material: &self.material,
     };
     Ok( renderer.draw(context, info)? )
   }
@@ -120,23 +135,31 @@ impl Drawable for Grid {
 
 #[cfg(feature = "wasm")]
 impl Grid {
-  fn vertices(&self) -> Result<Vec<(nalgebra::Point3<f32>, nalgebra::Point3<f32>)>, Error> {
+  /// Return the positions of the grid line end points, shared between lines where they coincide, 
+  /// and the indices of the end points of each line
+  fn vertices(&self) -> Result<(Vec<nalgebra::Point3<f32>>, renderer::Indices), Error> {
     let n = self.normal.normalize();
     let t = self.tangent.normalize();
     let c: nalgebra::Vector3<f32> = n.cross(&t);
     let l = (self.n as f32) * self.delta;
-    let vertices = (0..=self.n).map(|i| { -0.5*l + (i as f32) * self.delta })
+    let x = |i: u32| { -0.5*l + (i as f32) * self.delta };
+
+    // End points lie on the boundary of the (n+1) x (n+1) lattice of grid nodes
+    let mut positions = Vec::new();
+    let mut index = std::collections::HashMap::new();
+    let mut node = |i: u32, j: u32| -> u32 {
+      *index.entry((i, j)).or_insert_with(|| { 
+        positions.push(self.center - x(i)*t - x(j)*c); 
+        (positions.len() - 1) as u32 
+      })
+    };
+    let indices = (0..=self.n)
     .fold(Vec::new(),
-      |mut r, x| {
-        let s = self.center - x*t - 0.5*l*c;
-        let e = self.center - x*t + 0.5*l*c;
-        r.push((s,e));
-        let s = self.center - x*c - 0.5*l*t;
-        let e = self.center - x*c + 0.5*l*t;
-        r.push((s,e));
+      |mut r, i| {
+        r.push(node(i, 0)); r.push(node(i, self.n));
+        r.push(node(0, i)); r.push(node(self.n, i));
         r
       });
-    Ok(vertices)
+    Ok((positions, renderer::Indices::new(indices)))
   }
 }
-
@@ -8,6 +8,7 @@ pub struct GridBuilder {
   center: Option<nalgebra::Point3<f32>>,
   delta: Option<f32>,
   n: Option<u32>,
+  material: Option<Material>,
 }
 
 #[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
@@ -20,6 +21,7 @@ impl GridBuilder {
       center: None,
       delta: None,
       n: None,
+      material: None,
     }
   }
 
@@ -38,6 +40,9 @@ impl GridBuilder {
   /// Specify number of grid cells
   pub fn n(mut self, n: u32) -> GridBuilder { self.n = Some(n); self }
 
+  /// Specify material. Optional
+  pub fn material(mut self, material: Material) -> GridBuilder { self.material = Some(material); self }
+
   /// Build a `Grid` object
   pub fn build(self) -> Result<Grid, JsError> {
     Ok(
@@ -47,6 +52,7 @@ impl GridBuilder {
         self.center.ok_or("Center not specified")?,
         self.delta.ok_or("Grid spacing (delta) not specified")?,
         self.n.ok_or("Number of grid cell (n) not specified")?,
+        self.material.unwrap_or_default(),
       )?
     )
   }
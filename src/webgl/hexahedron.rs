@@ -2,6 +2,9 @@ use super::*;
 
 pub mod hexahedronbuilder;
 
+/// Positions, normals and triangle indices
+type Geometry = (Vec<nalgebra::Point3<f32>>, Vec<nalgebra::Vector3<f32>>, Vec<u32>);
+
 /// UID initialisation function
 fn nano_id() -> String { nanoid::nanoid!(6) }
 
@@ -15,6 +18,13 @@ pub struct Hexahedron {
   start: nalgebra::Point3<f32>,
   end:   nalgebra::Point3<f32>,
   transform: Vec<transform::Transform>,
+  #[serde(default)]
+  material: Material,
+  /// Scalar value per corner, indexed by `x + 2y + 4z` with `x`, `y` and `z` 0 at start and 1 at end
+  #[serde(default)]
+  scalars: Vec<f32>,
+  #[serde(default)]
+  scalar_style: ScalarStyle,
 }
 
 impl Transformable for Hexahedron {
@@ -45,10 +55,14 @@ impl Hexahedron {
 impl Hexahedron {
   fn new(
     start: nalgebra::Point3<f32>,
-    end: nalgebra::Point3<f32>) -> Result<Hexahedron, Error> {
+    end: nalgebra::Point3<f32>,
+    material: Material) -> Result<Hexahedron, Error> {
     Ok( Hexahedron { uid: nanoid::nanoid!(6),
           start, end, 
           transform: Vec::new(),
+          material,
+          scalars: Vec::new(),
+          scalar_style: ScalarStyle::default(),
           } )
   }
 
@@ -79,6 +93,40 @@ impl Hexahedron {
     self.with_transform(transform.into())
   }
 
+  /// Replace the material. Exposed to JavaScript
+  pub fn with_material(mut self, material: Material) -> Self {
+    self.material = material; self
+  }
+
+  /// Retrieve the material. Exposed to JavaScript
+  pub fn material(&self) -> Material {
+    self.material.clone()
+  }
+
+  /// Colour the faces by the `scalars` at the 8 corners, indexed by `x + 2y + 4z` with `x`, `y` and `z` 0 at start and 1 at end,
+  /// mapped through the colormap of `style`. Exposed to JavaScript
+  pub fn with_scalars(mut self, scalars: &[f32], style: ScalarStyle) -> Result<Hexahedron, JsError> {
+    if scalars.len() != 8 { return Err(Error::from("Hexahedron scalars require 1 value per corner").into()); }
+    self.scalars = scalars.to_vec();
+    self.scalar_style = style;
+    Ok(self)
+  }
+
+  /// Replace the mapping of the scalars to colours. Exposed to JavaScript
+  pub fn with_scalar_style(mut self, style: ScalarStyle) -> Self {
+    self.scalar_style = style; self
+  }
+
+  /// Remove the scalars. Exposed to JavaScript
+  pub fn without_scalars(mut self) -> Self {
+    self.scalars.clear(); self
+  }
+
+  /// Retrieve the mapping of the scalars to colours. Exposed to JavaScript
+  pub fn scalar_style(&self) -> ScalarStyle {
+    self.scalar_style.clone()
+  }
+
   /// Apply a translation. Exposed to JavaScript
   pub fn translate(self, translate: transform::translation::Translation) -> Self {
     self.with_transform(translate.into())
@@ -114,25 +162,30 @@ impl Identifiable for Hexahedron {
 }
 
 impl Drawable for Hexahedron {
-  /// Draw the hex on the context
+  /// Draw the hex on the context, coloured by the corner scalars if set
   fn draw<T>(&self, context: &web_sys::WebGl2RenderingContext, renderer: &T) -> Result<(), JsError> 
   where T: renderer::RendererTrait {
-    let (vertices, normals) = self.vertices()?;
-    let (vertices, normals): (Vec<(_, _, _)>, Vec<(_, _, _)>) = std::iter::zip(vertices.into_iter(), normals.into_iter())
-      .map(|((p1, p2, p3), n)| 
-        Ok(
-          ((self.transform_point(&p1)?, self.transform_point(&p2)?, self.transform_point(&p3)?),
-           (self.transform_vector(&p1, &n)?, self.transform_vector(&p2, &n)?, self.transform_vector(&p3, &n)?))
-        )
-      )
-      .collect::<Result<Vec<((nalgebra::Point3<f32>, nalgebra::Point3<f32>, nalgebra::Point3<f32>), 
-                             (nalgebra::Vector3<f32>, nalgebra::Vector3<f32>, nalgebra::Vector3<f32>))>, Error>>()?
-      .into_iter()
-      .unzip();
-    let info = renderer::Info::TrianglesWithNormals {
+    let (positions, normals, indices) = self.geometry()?;
+    let indices = renderer::Indices::new(indices);
+    if !self.scalars.is_empty() {
+      let scalars = self.vertex_scalars()?;
+      let info = renderer::Info::ScalarTriangles {
+        uid: &self.uid,
+        positions: &positions,
+        normals: &normals,
+        scalars: &scalars,
+        indices: &indices,
+        style: &self.scalar_style,
+        material: &self.material,
+      };
+      return Ok( renderer.draw(context, info)? );
+    }
+    let info = renderer::Info::IndexedTrianglesWithNormals {
       uid: &self.uid,
-      vertices: &vertices,
+      positions: &positions,
       normals: &normals,
+      indices: &indices,
+      material: &self.material,
     };
     Ok( renderer.draw(context, info)? )
   }
@@ -140,58 +193,58 @@ impl Drawable for Hexahedron {
 
 #[cfg(feature = "wasm")]
 impl Hexahedron {
-  fn vertices(&self) -> Result<(Vec<(nalgebra::Point3<f32>, nalgebra::Point3<f32>, nalgebra::Point3<f32>)>, Vec<nalgebra::Vector3<f32>>), Error> {
+  /// Return the transformed positions and normals, and the triangle indices. Used as base geometry of `InstancedMesh`
+  pub(crate) fn geometry(&self) -> Result<Geometry, Error> {
+    let (positions, normals, indices) = self.vertices()?;
+    let (positions, normals) = std::iter::zip(positions.iter(), normals.iter())
+      .map(|(p, n)| Ok((self.transform_point(p)?, self.transform_vector(p, n)?)))
+      .collect::<Result<Vec<(nalgebra::Point3<f32>, nalgebra::Vector3<f32>)>, Error>>()?
+      .into_iter()
+      .unzip();
+    Ok((positions, normals, indices))
+  }
+
+  /// Return the corner scalar of each vertex of the faces
+  fn vertex_scalars(&self) -> Result<Vec<f32>, Error> {
+    let (positions, _, _) = self.vertices()?;
+    let delta = self.end - self.start;
+    let bit = |offset: f32, delta: f32| (delta != 0.0 && offset / delta > 0.5) as usize;
+    Ok(positions.iter()
+      .map(|p| {
+        let o = p - self.start;
+        self.scalars[bit(o.x, delta.x) + 2*bit(o.y, delta.y) + 4*bit(o.z, delta.z)]
+      })
+      .collect())
+  }
+
+  /// Return the positions and normals of the 4 corners of each of the 6 faces, and the indices of the 2 triangles per face
+  fn vertices(&self) -> Result<Geometry, Error> {
     use nalgebra::Vector3;
 
     let delta = self.end - self.start;
     let delta_x = delta.dot(&Vector3::x())*Vector3::x();
     let delta_y = delta.dot(&Vector3::y())*Vector3::y();
     let delta_z = delta.dot(&Vector3::z())*Vector3::z();
-
-    let mut v = Vec::new(); let mut n = Vec::new();
-    // Triangle 1
-    v.push((self.start, self.start + delta_y, self.start + delta_x)); 
-    n.push(-1.0f32*Vector3::z());
-    // Triangle 2
-    v.push((self.start + delta_x, self.start + delta_y, self.start + delta_x + delta_y));
-    n.push(-1.0f32*Vector3::z());
-
-    // Triangle 3
-    v.push((self.start, self.start + delta_x, self.start + delta_z));
-    n.push(-1.0f32*Vector3::y());
-    // Triangle 4
-    v.push((self.start + delta_z, self.start + delta_x, self.start + delta_x + delta_z));
-    n.push(-1.0f32*Vector3::y());
-
-    // Triangle 5
-    v.push((self.start, self.start + delta_z, self.start + delta_y));
-    n.push(-1.0f32*Vector3::x());
-    // Triangle 6
-    v.push((self.start + delta_y, self.start + delta_z, self.start + delta_y + delta_z));
-    n.push(-1.0f32*Vector3::x());
-
-    // Triangle 7
-    v.push(( self.start + delta_z + delta_x, self.start + delta_z + delta_y, self.start + delta_z, )); 
-    n.push(1.0f32*Vector3::z());
-    // Triangle 8
-    v.push(( self.start + delta_z + delta_x + delta_y, self.start + delta_z + delta_y, self.start + delta_z + delta_x, ));
-    n.push(1.0f32*Vector3::z());
-
-    // Triangle 9
-    v.push(( self.start + delta_y + delta_z, self.start + delta_y + delta_x, self.start + delta_y, ));
-    n.push(1.0f32*Vector3::y());
-    // Triangle 10
-    v.push(( self.start + delta_y + delta_x + delta_z, self.start + delta_y + delta_x, self.start + delta_y + delta_z, ));
-    n.push(1.0f32*Vector3::y());
-
-    // Triangle 11
-    v.push((self.start + delta_x, self.start + delta_x + delta_y, self.start + delta_x + delta_z));
-    n.push(1.0f32*Vector3::x());
-    // Triangle 12
-    v.push((self.start + delta_x + delta_z, self.start + delta_x + delta_y, self.start + delta_x + delta_y + delta_z));
-    n.push(1.0f32*Vector3::x());
-
-    Ok((v, n))
+    let s = self.start;
+
+    // Corners ordered counter-clockwise seen from outside
+    let faces = [
+      (-1.0f32*Vector3::z(), [s, s + delta_y, s + delta_x + delta_y, s + delta_x]),
+      (-1.0f32*Vector3::y(), [s, s + delta_x, s + delta_x + delta_z, s + delta_z]),
+      (-1.0f32*Vector3::x(), [s, s + delta_z, s + delta_y + delta_z, s + delta_y]),
+      ( 1.0f32*Vector3::z(), [s + delta_z, s + delta_z + delta_x, s + delta_z + delta_x + delta_y, s + delta_z + delta_y]),
+      ( 1.0f32*Vector3::y(), [s + delta_y, s + delta_y + delta_z, s + delta_y + delta_x + delta_z, s + delta_y + delta_x]),
+      ( 1.0f32*Vector3::x(), [s + delta_x, s + delta_x + delta_y, s + delta_x + delta_y + delta_z, s + delta_x + delta_z]),
+    ];
+
+    let mut v = Vec::new(); let mut n = Vec::new(); let mut indices = Vec::new();
+    for (normal, corners) in faces.into_iter() {
+      let i = v.len() as u32;
+      v.extend(corners);
+      n.extend([normal; 4]);
+      indices.extend([i, i + 1, i + 2, i, i + 2, i + 3]);
+    }
+
+    Ok((v, n, indices))
   }
 }
-
@@ -6,6 +6,8 @@ use super::*;
 pub struct HexahedronBuilder {
   start: Option<nalgebra::Point3<f32>>,
   end: Option<nalgebra::Point3<f32>>,
+  material: Option<Material>,
+  scalars: Option<(Vec<f32>, ScalarStyle)>,
 }
 
 #[cfg(feature = "wasm")]
@@ -20,14 +22,23 @@ impl HexahedronBuilder {
   /// Specify end point
   pub fn end(mut self, end: &[f32]) -> HexahedronBuilder { self.end = Some(nalgebra::Point3::from_slice(end)); self }
 
+  /// Specify material. Optional
+  pub fn material(mut self, material: Material) -> HexahedronBuilder { self.material = Some(material); self }
+
+  /// Specify the scalar values at the 8 corners, indexed by `x + 2y + 4z`, and their mapping to colours. Optional
+  pub fn scalars(mut self, scalars: &[f32], style: ScalarStyle) -> HexahedronBuilder { self.scalars = Some((scalars.to_vec(), style)); self }
+
   /// Build an `Hexahedron` object
   pub fn build(self) -> Result<Hexahedron, JsError> {
-    Ok(
-      Hexahedron::new(
-        self.start.ok_or("Start is not specified")?,
-        self.end.ok_or("End is not specified")?,
-      )?
-    )
+    let hexahedron = Hexahedron::new(
+      self.start.ok_or("Start is not specified")?,
+      self.end.ok_or("End is not specified")?,
+      self.material.unwrap_or_default(),
+    )?;
+    match self.scalars {
+      Some((scalars, style)) => hexahedron.with_scalars(&scalars, style),
+      None => Ok(hexahedron),
+    }
   }
 }
 
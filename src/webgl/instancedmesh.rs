@@ -0,0 +1,144 @@
+use super::*;
+
+pub mod instancedmeshbuilder;
+
+/// UID initialisation function
+fn nano_id() -> String { nanoid::nanoid!(6) }
+
+/// Copy of the base geometry placed by a model matrix
+#[derive(Clone)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Instance {
+  uid:    String,
+  matrix: nalgebra::Matrix4<f32>,
+  /// Colour of the instance. The material colour is used when not specified
+  #[serde(default)]
+  color:  Option<Color>,
+}
+
+/// Base geometry - indexed triangles with normals - drawn once per instance in a single instanced draw call.
+/// The edges of the display modes showing them are extracted from all instances
+#[cfg(feature = "wasm")]
+#[wasm_bindgen::prelude::wasm_bindgen]
+#[derive(Clone)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct InstancedMesh {
+  #[serde(default = "nano_id")]
+  uid:       String,
+  positions: Vec<nalgebra::Point3<f32>>,
+  normals:   Vec<nalgebra::Vector3<f32>>,
+  indices:   Vec<u32>,
+  #[serde(default)]
+  instances: Vec<Instance>,
+  #[serde(default)]
+  material:  Material,
+}
+
+impl InstancedMesh {
+  pub const TYPE_NAME: &str = "InstancedMesh";
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen::prelude::wasm_bindgen]
+impl InstancedMesh {
+  fn new(
+    positions: Vec<nalgebra::Point3<f32>>,
+    normals:   Vec<nalgebra::Vector3<f32>>,
+    indices:   Vec<u32>,
+    material:  Material) -> Result<InstancedMesh, Error> {
+    if positions.len() != normals.len() { return Err("Number of positions and normals differ".into()); }
+    if !indices.len().is_multiple_of(3) { return Err("Number of indices is not a multiple of 3".into()); }
+    if indices.iter().any(|i| *i as usize >= positions.len()) { return Err("Index exceeds the number of positions".into()); }
+    Ok( InstancedMesh { uid: nanoid::nanoid!(6), positions, normals, indices, instances: Vec::new(), material, } )
+  }
+
+  /// Retrieve the object id
+  /// Exposed to JavaScript
+  pub fn uuid(&self) -> Result<String, JsError> {
+    Ok(self.uid.clone())
+  }
+
+  /// Retrieve type name
+  /// Exposed to JavaScript
+  pub fn type_name(&self) -> Result<String, JsError> {
+    Ok(Self::TYPE_NAME.to_string())
+  }
+
+  /// Add an instance with its uid, model matrix (16 values, column-major) and optional colour.
+  /// Exposed to JavaScript
+  pub fn with_instance(mut self, uid: String, matrix: &[f32], color: Option<Color>) -> Result<InstancedMesh, JsError> {
+    if matrix.len() != 16 { return Err(Error::from("Instance matrix requires 16 values").into()); }
+    self.instances.push(Instance { uid, matrix: nalgebra::Matrix4::from_column_slice(matrix), color });
+    Ok(self)
+  }
+
+  /// Add an instance translated by `translation` (3 values), with its uid and optional colour.
+  /// Exposed to JavaScript
+  pub fn with_instance_at(self, uid: String, translation: &[f32], color: Option<Color>) -> Result<InstancedMesh, JsError> {
+    if translation.len() != 3 { return Err(Error::from("Instance translation requires 3 values").into()); }
+    let matrix = nalgebra::Matrix4::new_translation(&nalgebra::Vector3::from_row_slice(translation));
+    self.with_instance(uid, matrix.as_slice(), color)
+  }
+
+  /// Remove all instances. Exposed to JavaScript
+  pub fn without_instances(mut self) -> Self {
+    self.instances.clear(); self
+  }
+
+  /// Number of instances. Exposed to JavaScript
+  pub fn n_instances(&self) -> usize {
+    self.instances.len()
+  }
+
+  /// Replace the material. Exposed to JavaScript
+  pub fn with_material(mut self, material: Material) -> Self {
+    self.material = material; self
+  }
+
+  /// Retrieve the material. Exposed to JavaScript
+  pub fn material(&self) -> Material {
+    self.material.clone()
+  }
+
+  /// Draw the instances on the context
+  /// Exposed to JavaScript
+  pub fn draw(&self, context: &web_sys::WebGl2RenderingContext, renderer: &renderer::Renderer) -> Result<(), JsError> {
+    Drawable::draw(self, context, renderer)
+  }
+
+  pub fn clone(&self) -> Self {
+    Clone::clone(self)
+  }
+}
+
+impl Identifiable for InstancedMesh {
+  /// Retrieve the object uuid
+  fn uuid(&self) -> Result<String, Error> {
+    Ok(self.uid.clone())
+  }
+}
+
+impl Drawable for InstancedMesh {
+  /// Draw the instances on the context
+  fn draw<T>(&self, context: &web_sys::WebGl2RenderingContext, renderer: &T) -> Result<(), JsError> 
+  where T: renderer::RendererTrait {
+    if self.instances.is_empty() { return Ok(()); }
+    let indices = renderer::Indices::new(self.indices.clone());
+    let instances = renderer::Instances {
+      uids:     self.instances.iter().map(|i| i.uid.clone()).collect(),
+      matrices: self.instances.iter().map(|i| i.matrix).collect(),
+      colors:   self.instances.iter()
+        .map(|i| i.color.map(|c| c.with_alpha(c.a()*self.material.opacity())).unwrap_or(self.material.surface_color()))
+        .collect(),
+    };
+    let info = renderer::Info::InstancedTrianglesWithNormals {
+      uid: &self.uid,
+      positions: &self.positions,
+      normals: &self.normals,
+      indices: &indices,
+      instances: &instances,
+      material: &self.material,
+    };
+    Ok( renderer.draw(context, info)? )
+  }
+}
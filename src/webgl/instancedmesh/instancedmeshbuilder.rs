@@ -0,0 +1,50 @@
+use super::*;
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen::prelude::wasm_bindgen]
+#[derive(Default)]
+pub struct InstancedMeshBuilder {
+  positions: Option<Vec<nalgebra::Point3<f32>>>,
+  normals: Option<Vec<nalgebra::Vector3<f32>>>,
+  indices: Option<Vec<u32>>,
+  material: Option<Material>,
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen::prelude::wasm_bindgen]
+impl InstancedMeshBuilder {
+  /// Create an empty `InstancedMeshBuilder`
+  pub fn new() -> InstancedMeshBuilder { InstancedMeshBuilder::default() }
+
+  /// Specify the base geometry from flattened positions and normals (3 values per vertex) and triangle indices
+  pub fn geometry(mut self, positions: &[f32], normals: &[f32], indices: &[u32]) -> InstancedMeshBuilder {
+    self.positions = Some(positions.chunks_exact(3).map(nalgebra::Point3::from_slice).collect());
+    self.normals = Some(normals.chunks_exact(3).map(nalgebra::Vector3::from_row_slice).collect());
+    self.indices = Some(indices.to_vec());
+    self
+  }
+
+  /// Specify the base geometry from an `Hexahedron`, including its transformations
+  pub fn hexahedron(mut self, hexahedron: &Hexahedron) -> Result<InstancedMeshBuilder, JsError> {
+    let (positions, normals, indices) = hexahedron.geometry()?;
+    self.positions = Some(positions);
+    self.normals = Some(normals);
+    self.indices = Some(indices);
+    Ok(self)
+  }
+
+  /// Specify material. Optional
+  pub fn material(mut self, material: Material) -> InstancedMeshBuilder { self.material = Some(material); self }
+
+  /// Build an `InstancedMesh` object
+  pub fn build(self) -> Result<InstancedMesh, JsError> {
+    Ok(
+      InstancedMesh::new(
+        self.positions.ok_or("Geometry is not specified")?,
+        self.normals.ok_or("Geometry is not specified")?,
+        self.indices.ok_or("Geometry is not specified")?,
+        self.material.unwrap_or_default(),
+      )?
+    )
+  }
+}
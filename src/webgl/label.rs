@@ -0,0 +1,154 @@
+use super::*;
+
+pub mod labelbuilder;
+
+/// UID initialisation function
+fn nano_id() -> String { nanoid::nanoid!(6) }
+
+/// Size, placement and decorations of a label
+#[derive(Clone, Debug, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct LabelStyle {
+  /// Font size, in pixels
+  pub font_size:  f32,
+  /// Offset, in pixels, of the centre of the text from the anchor - x to the right, y upwards
+  pub offset:     nalgebra::Vector2<f32>,
+  /// Space, in pixels, between the text and the edge of the background box
+  pub padding:    f32,
+  /// Colour of the background box, if drawn
+  pub background: Option<Color>,
+  /// Whether a leader line is drawn from the anchor to the text box, when offset
+  pub leader:     bool,
+}
+
+impl Default for LabelStyle {
+  fn default() -> LabelStyle {
+    LabelStyle { font_size: 14.0, offset: nalgebra::Vector2::zeros(), padding: 3.0, background: None, leader: true }
+  }
+}
+
+/// Camera-facing text of constant pixel size anchored at a world point, drawn over the scene. The text is drawn
+/// with the material colour and the leader line with the material line colour
+#[cfg(feature = "wasm")]
+#[wasm_bindgen::prelude::wasm_bindgen]
+#[derive(Clone)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Label {
+  #[serde(default = "nano_id")]
+  uid:      String,
+  text:     String,
+  anchor:   nalgebra::Point3<f32>,
+  #[serde(default)]
+  style:    LabelStyle,
+  #[serde(default)]
+  material: Material,
+}
+
+impl Label {
+  pub const TYPE_NAME: &str = "Label";
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen::prelude::wasm_bindgen]
+impl Label {
+  fn new(text: String, anchor: nalgebra::Point3<f32>, style: LabelStyle, material: Material) -> Label {
+    Label { uid: nanoid::nanoid!(6), text, anchor, style, material }
+  }
+
+  /// Retrieve the object id
+  /// Exposed to JavaScript
+  pub fn uuid(&self) -> Result<String, JsError> {
+    Ok(self.uid.clone())
+  }
+
+  /// Retrieve type name
+  /// Exposed to JavaScript
+  pub fn type_name(&self) -> Result<String, JsError> {
+    Ok(Self::TYPE_NAME.to_string())
+  }
+
+  /// Replace the text. Exposed to JavaScript
+  pub fn with_text(mut self, text: String) -> Self {
+    self.text = text; self
+  }
+
+  /// Retrieve the text. Exposed to JavaScript
+  pub fn text(&self) -> String {
+    self.text.clone()
+  }
+
+  /// Move the anchor to `[x, y, z]`. Exposed to JavaScript
+  pub fn with_anchor(mut self, anchor: &[f32]) -> Result<Label, JsError> {
+    if anchor.len() != 3 { return Err("Anchor requires 3 values".into()); }
+    self.anchor = nalgebra::Point3::from_slice(anchor); Ok(self)
+  }
+
+  /// Set the font size, in pixels. Exposed to JavaScript
+  pub fn with_font_size(mut self, font_size: f32) -> Self {
+    self.style.font_size = font_size; self
+  }
+
+  /// Set the offset, in pixels, of the text from the anchor - x to the right, y upwards. Exposed to JavaScript
+  pub fn with_offset(mut self, x: f32, y: f32) -> Self {
+    self.style.offset = nalgebra::Vector2::new(x, y); self
+  }
+
+  /// Draw a background box of the given colour. Exposed to JavaScript
+  pub fn with_background(mut self, color: Color) -> Self {
+    self.style.background = Some(color); self
+  }
+
+  /// Do not draw a background box. Exposed to JavaScript
+  pub fn without_background(mut self) -> Self {
+    self.style.background = None; self
+  }
+
+  /// Set whether a leader line is drawn from the anchor to the offset text. Exposed to JavaScript
+  pub fn with_leader(mut self, leader: bool) -> Self {
+    self.style.leader = leader; self
+  }
+
+  /// Replace the material. Exposed to JavaScript
+  pub fn with_material(mut self, material: Material) -> Self {
+    self.material = material; self
+  }
+
+  /// Retrieve the material. Exposed to JavaScript
+  pub fn material(&self) -> Material {
+    self.material.clone()
+  }
+
+  /// Draw the label on the context
+  /// Exposed to JavaScript
+  pub fn draw(&self, context: &web_sys::WebGl2RenderingContext, renderer: &renderer::Renderer) -> Result<(), JsError> {
+    Drawable::draw(self, context, renderer)
+  }
+
+  pub fn clone(&self) -> Self {
+    Clone::clone(self)
+  }
+}
+
+impl Identifiable for Label {
+  /// Retrieve the object uuid
+  fn uuid(&self) -> Result<String, Error> {
+    Ok(self.uid.clone())
+  }
+}
+
+impl Drawable for Label {
+  /// Draw the label on the context
+  fn draw<T>(&self, context: &web_sys::WebGl2RenderingContext, renderer: &T) -> Result<(), JsError> 
+  where T: renderer::RendererTrait {
+    if self.text.is_empty() { return Ok(()); }
+    let info = renderer::Info::Label {
+      uid: &self.uid,
+      anchor: &self.anchor,
+      text: &self.text,
+      style: &self.style,
+      material: &self.material,
+    };
+    Ok( renderer.draw(context, info)? )
+  }
+}
@@ -0,0 +1,67 @@
+use super::*;
+
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[derive(Default)]
+/// Builder pattern for `Label` struct
+pub struct LabelBuilder {
+  text: Option<String>,
+  anchor: Option<nalgebra::Point3<f32>>,
+  font_size: Option<f32>,
+  offset: Option<nalgebra::Vector2<f32>>,
+  padding: Option<f32>,
+  background: Option<Color>,
+  leader: Option<bool>,
+  material: Option<Material>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+impl LabelBuilder {
+  /// Create an empty `LabelBuilder`
+  pub fn new() -> LabelBuilder { LabelBuilder::default() }
+
+  /// Specify the text. Lines are separated by `\n`
+  pub fn text(mut self, text: String) -> LabelBuilder { self.text = Some(text); self }
+
+  /// Specify the world point the label is anchored at
+  pub fn anchor(mut self, anchor: &[f32]) -> LabelBuilder { self.anchor = Some(nalgebra::Point3::from_slice(anchor)); self }
+
+  /// Specify the font size, in pixels. Optional
+  pub fn font_size(mut self, font_size: f32) -> LabelBuilder { self.font_size = Some(font_size); self }
+
+  /// Specify the offset, in pixels, of the text from the anchor - x to the right, y upwards. Optional
+  pub fn offset(mut self, x: f32, y: f32) -> LabelBuilder { self.offset = Some(nalgebra::Vector2::new(x, y)); self }
+
+  /// Specify the space, in pixels, between the text and the edge of the background box. Optional
+  pub fn padding(mut self, padding: f32) -> LabelBuilder { self.padding = Some(padding); self }
+
+  /// Specify the colour of the background box. Optional, no box is drawn if not specified
+  pub fn background(mut self, background: Color) -> LabelBuilder { self.background = Some(background); self }
+
+  /// Specify whether a leader line is drawn from the anchor to the offset text. Optional
+  pub fn leader(mut self, leader: bool) -> LabelBuilder { self.leader = Some(leader); self }
+
+  /// Specify material. Optional
+  pub fn material(mut self, material: Material) -> LabelBuilder { self.material = Some(material); self }
+
+  /// Build a `Label` object
+  pub fn build(self) -> Result<Label, JsError> {
+    let default = LabelStyle::default();
+    let font_size = self.font_size.unwrap_or(default.font_size);
+    if font_size <= 0.0 { return Err("Font size must be positive".into()); }
+    let style = LabelStyle {
+      font_size,
+      offset: self.offset.unwrap_or(default.offset),
+      padding: self.padding.unwrap_or(default.padding),
+      background: self.background,
+      leader: self.leader.unwrap_or(default.leader),
+    };
+    Ok(
+      Label::new(
+        self.text.ok_or("Text not specified")?,
+        self.anchor.ok_or("Anchor not specified")?,
+        style,
+        self.material.unwrap_or_default(),
+      )
+    )
+  }
+}
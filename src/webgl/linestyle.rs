@@ -0,0 +1,121 @@
+use super::*;
+
+/// Shape of the free ends of lines
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub enum LineCap {
+  /// Extended by half the line width
+  #[default]
+  Square = 0,
+  /// Half disc of the line width
+  Round = 1,
+}
+
+/// Shape of the connection between consecutive segments
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub enum LineJoin {
+  /// Segments extended by half the line width
+  #[default]
+  Square = 0,
+  /// Disc of the line width
+  Round = 1,
+}
+
+/// Units of the dash pattern lengths
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub enum DashUnits {
+  /// Pixels, restarting at each segment
+  #[default]
+  Screen = 1,
+  /// World units, continuous along connected segments
+  World = 2,
+}
+
+/// Width, ends and dash pattern of lines. Lines wider than a pixel, with round ends or dashed are drawn
+/// as screen-space quads
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[derive(Clone, Debug, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct LineStyle {
+  width:      f32,
+  cap:        LineCap,
+  join:       LineJoin,
+  dash:       Vec<f32>,
+  dash_units: DashUnits,
+}
+
+impl Default for LineStyle {
+  fn default() -> LineStyle {
+    LineStyle { width: 1.0, cap: LineCap::default(), join: LineJoin::default(), dash: Vec::new(), dash_units: DashUnits::default() }
+  }
+}
+
+impl LineStyle {
+  /// Maximum number of lengths in a dash pattern
+  pub const MAX_DASH: usize = 8;
+
+  /// Width, in pixels
+  pub fn width(&self) -> f32 { self.width }
+
+  /// Shape of the free ends
+  pub fn cap(&self) -> LineCap { self.cap }
+
+  /// Shape of the connection between segments
+  pub fn join(&self) -> LineJoin { self.join }
+
+  /// Alternating dash and gap lengths. Empty for solid lines
+  pub fn dash(&self) -> &[f32] { &self.dash }
+
+  /// Units of the dash pattern
+  pub fn dash_units(&self) -> DashUnits { self.dash_units }
+
+  /// Whether the lines can be drawn as 1 pixel WebGL `LINES`
+  pub fn is_thin(&self) -> bool { self.width <= 1.0 && self.cap == LineCap::Square && self.join == LineJoin::Square && self.dash.is_empty() }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+impl LineStyle {
+  /// Create the default style: solid 1 pixel wide lines with square ends
+  pub fn new() -> LineStyle { LineStyle::default() }
+
+  /// Set the width, in pixels
+  pub fn with_width(mut self, width: f32) -> Result<LineStyle, JsError> {
+    if width <= 0.0 { return Err("Line width must be positive".into()); }
+    self.width = width; Ok(self)
+  }
+
+  /// Set the shape of the free ends
+  pub fn with_cap(mut self, cap: LineCap) -> LineStyle { self.cap = cap; self }
+
+  /// Set the shape of the connection between segments
+  pub fn with_join(mut self, join: LineJoin) -> LineStyle { self.join = join; self }
+
+  /// Set the dash pattern as alternating dash and gap lengths, in `units`
+  pub fn with_dash(mut self, pattern: Vec<f32>, units: DashUnits) -> Result<LineStyle, JsError> {
+    if pattern.is_empty() || pattern.len() > Self::MAX_DASH { return Err(format!("Dash pattern requires 1 to {} lengths", Self::MAX_DASH).into()); }
+    if pattern.iter().any(|l| *l < 0.0) || pattern.iter().sum::<f32>() <= 0.0 { return Err("Dash lengths must be positive".into()); }
+    self.dash = pattern; self.dash_units = units; Ok(self)
+  }
+
+  /// Draw solid lines
+  pub fn without_dash(mut self) -> LineStyle { self.dash = Vec::new(); self }
+
+  /// Expose clone functionality to JavaScript
+  pub fn clone(&self) -> LineStyle { Clone::clone(self) }
+
+  /// Convert line style to json
+  pub fn to_json(&self) -> Result<String, JsError> {
+    Ok(serde_json::to_string(&self).map_err(|e| format!("{e}"))?)
+  }
+
+  /// Create line style from json
+  pub fn from_json(json: &str) -> Result<LineStyle, JsError> {
+    Ok(serde_json::from_str(json).map_err(|e| format!("{e}"))?)
+  }
+}
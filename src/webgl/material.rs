@@ -0,0 +1,81 @@
+use super::*;
+
+pub mod materialbuilder;
+
+/// Appearance of a drawable element
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[derive(Clone, Debug, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Material {
+  color:         Color,
+  opacity:       f32,
+  line_color:    Color,
+  lines_visible: bool,
+  line_style:    LineStyle,
+  shininess:     f32,
+  specular:      f32,
+  display_mode:  Option<renderer::DisplayMode>,
+}
+
+impl Default for Material {
+  fn default() -> Material {
+    Material {
+      color:         Color::rgb(0.3, 0.3, 0.3),
+      opacity:       1.0,
+      line_color:    Color::rgb(0.9, 0.9, 0.9),
+      lines_visible: true,
+      line_style:    LineStyle::default(),
+      shininess:     32.0,
+      specular:      0.25,
+      display_mode:  None,
+    }
+  }
+}
+
+impl Material {
+  /// Base colour of surfaces, with the opacity applied to the alpha component
+  pub fn surface_color(&self) -> Color { self.color.with_alpha(self.color.a() * self.opacity) }
+
+  /// Colour of lines, with the opacity applied to the alpha component
+  pub fn line_color(&self) -> Color { self.line_color.with_alpha(self.line_color.a() * self.opacity) }
+
+  /// Whether lines are drawn
+  pub fn lines_visible(&self) -> bool { self.lines_visible }
+
+  /// Width, ends and dash pattern of lines
+  pub fn line_style(&self) -> &LineStyle { &self.line_style }
+
+  /// Opacity in the range `[0, 1]`
+  pub fn opacity(&self) -> f32 { self.opacity }
+
+  /// Blinn-Phong specular exponent
+  pub fn shininess(&self) -> f32 { self.shininess }
+
+  /// Strength of the specular highlight
+  pub fn specular(&self) -> f32 { self.specular }
+
+  /// Display mode overriding the one of the `Renderer`, if any
+  pub fn display_mode(&self) -> Option<renderer::DisplayMode> { self.display_mode }
+
+  /// Copy of the material with the surface and line colours blended towards `color` by `amount` in `[0, 1]`
+  pub fn tinted(&self, color: &Color, amount: f32) -> Material {
+    Material { color: self.color.mix(color, amount), line_color: self.line_color.mix(color, amount), ..Clone::clone(self) }
+  }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+impl Material {
+  /// Expose clone functionality to JavaScript
+  pub fn clone(&self) -> Material { Clone::clone(self) }
+
+  /// Convert material to json
+  pub fn to_json(&self) -> Result<String, JsError> {
+    Ok(serde_json::to_string(&self).map_err(|e| format!("{e}"))?)
+  }
+
+  /// Create material from json
+  pub fn from_json(json: &str) -> Result<Material, JsError> {
+    Ok(serde_json::from_str(json).map_err(|e| format!("{e}"))?)
+  }
+}
@@ -0,0 +1,101 @@
+use super::*;
+
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[derive(Default)]
+/// Builder pattern for `Material` struct. Unspecified parameters take their default value
+pub struct MaterialBuilder {
+  color: Option<Color>,
+  opacity: Option<f32>,
+  line_color: Option<Color>,
+  lines_visible: Option<bool>,
+  line_style: Option<LineStyle>,
+  shininess: Option<f32>,
+  specular: Option<f32>,
+  display_mode: Option<renderer::DisplayMode>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+impl MaterialBuilder {
+  /// Create an empty `MaterialBuilder`
+  pub fn new() -> MaterialBuilder { MaterialBuilder::default() }
+
+  /// Specify the base colour
+  pub fn color(mut self, color: Color) -> MaterialBuilder { self.color = Some(color); self }
+
+  /// Specify the opacity, in the range `[0, 1]`
+  pub fn opacity(mut self, opacity: f32) -> MaterialBuilder { self.opacity = Some(opacity); self }
+
+  /// Specify the line colour
+  pub fn line_color(mut self, line_color: Color) -> MaterialBuilder { self.line_color = Some(line_color); self }
+
+  /// Specify whether lines are drawn
+  pub fn lines_visible(mut self, lines_visible: bool) -> MaterialBuilder { self.lines_visible = Some(lines_visible); self }
+
+  /// Specify the width, ends and dash pattern of lines
+  pub fn line_style(mut self, line_style: LineStyle) -> MaterialBuilder { self.line_style = Some(line_style); self }
+
+  /// Specify the Blinn-Phong specular exponent - larger values give smaller highlights
+  pub fn shininess(mut self, shininess: f32) -> MaterialBuilder { self.shininess = Some(shininess); self }
+
+  /// Specify the strength of the specular highlight - zero for a matte surface
+  pub fn specular(mut self, specular: f32) -> MaterialBuilder { self.specular = Some(specular); self }
+
+  /// Specify the display mode of the element, overriding the one of the `Renderer`
+  pub fn display_mode(mut self, display_mode: renderer::DisplayMode) -> MaterialBuilder { self.display_mode = Some(display_mode); self }
+
+  /// Build a `Material` object
+  pub fn build(self) -> Result<Material, JsError> {
+    Ok(self.build_material()?)
+  }
+}
+
+impl MaterialBuilder {
+  /// Build a `Material` object, with the error as a string
+  pub fn build_material(self) -> Result<Material, String> {
+    let default = Material::default();
+    let opacity = self.opacity.unwrap_or(default.opacity);
+    if !(0.0..=1.0).contains(&opacity) { return Err(format!("Opacity {opacity} is not in the range [0, 1]")); }
+    Ok(
+      Material {
+        color: self.color.unwrap_or(default.color),
+        opacity,
+        line_color: self.line_color.unwrap_or(default.line_color),
+        lines_visible: self.lines_visible.unwrap_or(default.lines_visible),
+        line_style: self.line_style.unwrap_or(default.line_style),
+        shininess: self.shininess.unwrap_or(default.shininess),
+        specular: self.specular.unwrap_or(default.specular),
+        display_mode: self.display_mode,
+      }
+    )
+  }
+}
+
+#[cfg(all(test, feature = "wasm"))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn unspecified_parameters_take_their_default_value() {
+    let material = MaterialBuilder::new().build_material().unwrap();
+    assert_eq!(material, Material::default());
+  }
+
+  #[test]
+  fn opacity_bounds_are_accepted() {
+    assert!(MaterialBuilder::new().opacity(0.0).build_material().is_ok());
+    assert!(MaterialBuilder::new().opacity(1.0).build_material().is_ok());
+  }
+
+  #[test]
+  fn opacity_outside_of_unit_range_is_rejected() {
+    assert!(MaterialBuilder::new().opacity(1.5).build_material().is_err());
+    assert!(MaterialBuilder::new().opacity(-0.1).build_material().is_err());
+    assert!(MaterialBuilder::new().opacity(f32::NAN).build_material().is_err());
+  }
+
+  #[test]
+  fn specified_parameters_are_kept() {
+    let material = MaterialBuilder::new().opacity(0.5).shininess(8.0).lines_visible(false).build_material().unwrap();
+    assert_eq!((material.opacity, material.shininess, material.lines_visible), (0.5, 8.0, false));
+  }
+}
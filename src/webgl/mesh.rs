@@ -0,0 +1,168 @@
+use super::*;
+
+pub mod meshbuilder;
+
+/// UID initialisation function
+fn nano_id() -> String { nanoid::nanoid!(6) }
+
+/// Indexed triangles with normals, optionally coloured by a scalar value per position or texture mapped by texture
+/// coordinates per position
+#[cfg(feature = "wasm")]
+#[wasm_bindgen::prelude::wasm_bindgen]
+#[derive(Clone)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Mesh {
+  #[serde(default = "nano_id")]
+  uid:       String,
+  positions: Vec<nalgebra::Point3<f32>>,
+  normals:   Vec<nalgebra::Vector3<f32>>,
+  indices:   Vec<u32>,
+  #[serde(default)]
+  uvs:       Vec<nalgebra::Point2<f32>>,
+  #[serde(default)]
+  texture:   Option<Texture>,
+  #[serde(default)]
+  scalars:   Vec<f32>,
+  #[serde(default)]
+  scalar_style: ScalarStyle,
+  #[serde(default)]
+  material:  Material,
+}
+
+impl Mesh {
+  pub const TYPE_NAME: &str = "Mesh";
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen::prelude::wasm_bindgen]
+impl Mesh {
+  fn new(
+    positions: Vec<nalgebra::Point3<f32>>,
+    normals:   Vec<nalgebra::Vector3<f32>>,
+    indices:   Vec<u32>,
+    material:  Material) -> Result<Mesh, Error> {
+    if positions.len() != normals.len() { return Err("Number of positions and normals differ".into()); }
+    if !indices.len().is_multiple_of(3) { return Err("Number of indices is not a multiple of 3".into()); }
+    if indices.iter().any(|i| *i as usize >= positions.len()) { return Err("Index exceeds the number of positions".into()); }
+    Ok( Mesh { uid: nanoid::nanoid!(6), positions, normals, indices, uvs: Vec::new(), texture: None,
+      scalars: Vec::new(), scalar_style: ScalarStyle::default(), material, } )
+  }
+
+  /// Retrieve the object id
+  /// Exposed to JavaScript
+  pub fn uuid(&self) -> Result<String, JsError> {
+    Ok(self.uid.clone())
+  }
+
+  /// Retrieve type name
+  /// Exposed to JavaScript
+  pub fn type_name(&self) -> Result<String, JsError> {
+    Ok(Self::TYPE_NAME.to_string())
+  }
+
+  /// Map the `texture` by the flattened texture coordinates `uvs` (2 values per position).
+  /// Exposed to JavaScript
+  pub fn with_texture(mut self, texture: Texture, uvs: &[f32]) -> Result<Mesh, JsError> {
+    if uvs.len() != 2 * self.positions.len() { return Err(Error::from("Texture coordinates require 2 values per position").into()); }
+    self.uvs = uvs.chunks_exact(2).map(nalgebra::Point2::from_slice).collect();
+    self.texture = Some(texture);
+    Ok(self)
+  }
+
+  /// Remove the texture. Exposed to JavaScript
+  pub fn without_texture(mut self) -> Self {
+    self.texture = None; self.uvs.clear(); self
+  }
+
+  /// Retrieve the texture, if any. Exposed to JavaScript
+  pub fn texture(&self) -> Option<Texture> {
+    self.texture.clone()
+  }
+
+  /// Colour the mesh by the `scalars` (1 value per position) mapped through the colormap of `style`, instead of
+  /// its texture or material colour. Exposed to JavaScript
+  pub fn with_scalars(mut self, scalars: &[f32], style: ScalarStyle) -> Result<Mesh, JsError> {
+    if scalars.len() != self.positions.len() { return Err(Error::from("Scalars require 1 value per position").into()); }
+    self.scalars = scalars.to_vec();
+    self.scalar_style = style;
+    Ok(self)
+  }
+
+  /// Replace the mapping of the scalars to colours. Exposed to JavaScript
+  pub fn with_scalar_style(mut self, style: ScalarStyle) -> Self {
+    self.scalar_style = style; self
+  }
+
+  /// Remove the scalars. Exposed to JavaScript
+  pub fn without_scalars(mut self) -> Self {
+    self.scalars.clear(); self
+  }
+
+  /// Retrieve the mapping of the scalars to colours. Exposed to JavaScript
+  pub fn scalar_style(&self) -> ScalarStyle {
+    self.scalar_style.clone()
+  }
+
+  /// Replace the material. Exposed to JavaScript
+  pub fn with_material(mut self, material: Material) -> Self {
+    self.material = material; self
+  }
+
+  /// Retrieve the material. Exposed to JavaScript
+  pub fn material(&self) -> Material {
+    self.material.clone()
+  }
+
+  /// Draw the mesh on the context
+  /// Exposed to JavaScript
+  pub fn draw(&self, context: &web_sys::WebGl2RenderingContext, renderer: &renderer::Renderer) -> Result<(), JsError> {
+    Drawable::draw(self, context, renderer)
+  }
+
+  pub fn clone(&self) -> Self {
+    Clone::clone(self)
+  }
+}
+
+impl Identifiable for Mesh {
+  /// Retrieve the object uuid
+  fn uuid(&self) -> Result<String, Error> {
+    Ok(self.uid.clone())
+  }
+}
+
+impl Drawable for Mesh {
+  /// Draw the mesh on the context, coloured by the scalars if set, textured if a texture is set
+  fn draw<T>(&self, context: &web_sys::WebGl2RenderingContext, renderer: &T) -> Result<(), JsError>
+  where T: renderer::RendererTrait {
+    let indices = renderer::Indices::new(self.indices.clone());
+    let info = match &self.texture {
+      _ if !self.scalars.is_empty() => renderer::Info::ScalarTriangles {
+        uid: &self.uid,
+        positions: &self.positions,
+        normals: &self.normals,
+        scalars: &self.scalars,
+        indices: &indices,
+        style: &self.scalar_style,
+        material: &self.material,
+      },
+      Some(texture) => renderer::Info::TexturedTriangles {
+        uid: &self.uid,
+        positions: &self.positions,
+        normals: &self.normals,
+        uvs: &self.uvs,
+        indices: &indices,
+        texture,
+        material: &self.material,
+      },
+      None => renderer::Info::IndexedTrianglesWithNormals {
+        uid: &self.uid,
+        positions: &self.positions,
+        normals: &self.normals,
+        indices: &indices,
+        material: &self.material,
+      },
+    };
+    Ok( renderer.draw(context, info)? )
+  }
+}
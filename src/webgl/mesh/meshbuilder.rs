@@ -0,0 +1,64 @@
+use super::*;
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen::prelude::wasm_bindgen]
+#[derive(Default)]
+pub struct MeshBuilder {
+  positions: Option<Vec<nalgebra::Point3<f32>>>,
+  normals: Option<Vec<nalgebra::Vector3<f32>>>,
+  indices: Option<Vec<u32>>,
+  texture: Option<(Texture, Vec<f32>)>,
+  scalars: Option<(Vec<f32>, ScalarStyle)>,
+  material: Option<Material>,
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen::prelude::wasm_bindgen]
+impl MeshBuilder {
+  /// Create an empty `MeshBuilder`
+  pub fn new() -> MeshBuilder { MeshBuilder::default() }
+
+  /// Specify the geometry from flattened positions and normals (3 values per vertex) and triangle indices
+  pub fn geometry(mut self, positions: &[f32], normals: &[f32], indices: &[u32]) -> MeshBuilder {
+    self.positions = Some(positions.chunks_exact(3).map(nalgebra::Point3::from_slice).collect());
+    self.normals = Some(normals.chunks_exact(3).map(nalgebra::Vector3::from_row_slice).collect());
+    self.indices = Some(indices.to_vec());
+    self
+  }
+
+  /// Specify the geometry from an `Hexahedron`, including its transformations
+  pub fn hexahedron(mut self, hexahedron: &Hexahedron) -> Result<MeshBuilder, JsError> {
+    let (positions, normals, indices) = hexahedron.geometry()?;
+    self.positions = Some(positions);
+    self.normals = Some(normals);
+    self.indices = Some(indices);
+    Ok(self)
+  }
+
+  /// Specify the texture and its flattened texture coordinates (2 values per vertex). Optional
+  pub fn texture(mut self, texture: Texture, uvs: &[f32]) -> MeshBuilder { self.texture = Some((texture, uvs.to_vec())); self }
+
+  /// Specify the scalar values (1 value per vertex) and their mapping to colours. Optional
+  pub fn scalars(mut self, scalars: &[f32], style: ScalarStyle) -> MeshBuilder { self.scalars = Some((scalars.to_vec(), style)); self }
+
+  /// Specify material. Optional
+  pub fn material(mut self, material: Material) -> MeshBuilder { self.material = Some(material); self }
+
+  /// Build a `Mesh` object
+  pub fn build(self) -> Result<Mesh, JsError> {
+    let mesh = Mesh::new(
+      self.positions.ok_or("Geometry is not specified")?,
+      self.normals.ok_or("Geometry is not specified")?,
+      self.indices.ok_or("Geometry is not specified")?,
+      self.material.unwrap_or_default(),
+    )?;
+    let mesh = match self.texture {
+      Some((texture, uvs)) => mesh.with_texture(texture, &uvs)?,
+      None => mesh,
+    };
+    match self.scalars {
+      Some((scalars, style)) => mesh.with_scalars(&scalars, style),
+      None => Ok(mesh),
+    }
+  }
+}
@@ -0,0 +1,230 @@
+use super::*;
+
+pub mod pointcloudbuilder;
+
+/// UID initialisation function
+fn nano_id() -> String { nanoid::nanoid!(6) }
+
+/// Positions, and optional colours or scalar values, of the points of a `PointCloud`, stored flattened. Shared by
+/// the copies of the cloud and by the GPU buffers cached by the `Renderer`, which are released once no copy remains
+pub struct PointData {
+  uid:         String,
+  positions:   Vec<f32>,
+  colors:      Vec<f32>,
+  scalars:     Vec<f32>,
+  bounds:      Option<(nalgebra::Point3<f32>, nalgebra::Point3<f32>)>,
+  range:       (f32, f32),
+  transparent: bool,
+}
+
+impl PointData {
+  /// Create the data of `positions` (3 values per point), `colors` (4 values per point, or empty) and `scalars`
+  /// (1 value per point, or empty)
+  fn new(positions: Vec<f32>, colors: Vec<f32>, scalars: Vec<f32>) -> PointData {
+    let bounds = positions.chunks_exact(3)
+      .map(nalgebra::Point3::from_slice)
+      .fold(None, |r: Option<(nalgebra::Point3<f32>, nalgebra::Point3<f32>)>, p| Some(match r {
+        Some((min, max)) => (min.inf(&p), max.sup(&p)),
+        None => (p, p),
+      }));
+    let range = ScalarStyle::default().resolved_range(&scalars);
+    let transparent = colors.chunks_exact(4).any(|c| c[3] < 1.0);
+    PointData { uid: nanoid::nanoid!(6), positions, colors, scalars, bounds, range, transparent }
+  }
+
+  /// Identifier of the data, shared by the copies of the cloud
+  pub fn uid(&self) -> &str { &self.uid }
+
+  /// Number of points
+  pub fn len(&self) -> usize { self.positions.len() / 3 }
+
+  /// Whether there is no point
+  pub fn is_empty(&self) -> bool { self.positions.is_empty() }
+
+  /// Flattened positions, 3 values per point
+  pub fn positions(&self) -> &[f32] { &self.positions }
+
+  /// Flattened RGBA colours, 4 values per point. Empty when the points are drawn with the material colour or scalars
+  pub fn colors(&self) -> &[f32] { &self.colors }
+
+  /// Scalar values, 1 per point. Empty when the points are not coloured by scalars
+  pub fn scalars(&self) -> &[f32] { &self.scalars }
+
+  /// Range `(min, max)` of the finite scalar values, `(0, 1)` if none
+  pub fn scalar_range(&self) -> (f32, f32) { self.range }
+
+  /// Axis aligned bounding box `(min, max)` of the points, if any
+  pub fn bounding_box(&self) -> Option<(nalgebra::Point3<f32>, nalgebra::Point3<f32>)> { self.bounds }
+
+  /// Whether a point colour is transparent
+  pub fn is_transparent(&self) -> bool { self.transparent }
+}
+
+/// Points drawn as camera-facing sprites, coloured per point, by a scalar value per point mapped through a colormap,
+/// or with the material colour. The point data is uploaded to the GPU once and shared by the copies of the cloud.
+/// Picking resolves the cloud uid and the index of the point
+#[cfg(feature = "wasm")]
+#[wasm_bindgen::prelude::wasm_bindgen]
+#[derive(Clone)]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(into = "PointCloudJson", try_from = "PointCloudJson")]
+pub struct PointCloud {
+  uid:          String,
+  data:         std::rc::Rc<PointData>,
+  style:        PointStyle,
+  scalar_style: ScalarStyle,
+  material:     Material,
+}
+
+impl PointCloud {
+  pub const TYPE_NAME: &str = "PointCloud";
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen::prelude::wasm_bindgen]
+impl PointCloud {
+  fn new(positions: Vec<f32>, style: PointStyle, material: Material) -> Result<PointCloud, Error> {
+    if !positions.len().is_multiple_of(3) { return Err("Number of position values is not a multiple of 3".into()); }
+    Ok( PointCloud { uid: nanoid::nanoid!(6), data: std::rc::Rc::new(PointData::new(positions, Vec::new(), Vec::new())),
+      style, scalar_style: ScalarStyle::default(), material, } )
+  }
+
+  /// Retrieve the object id
+  /// Exposed to JavaScript
+  pub fn uuid(&self) -> Result<String, JsError> {
+    Ok(self.uid.clone())
+  }
+
+  /// Retrieve type name
+  /// Exposed to JavaScript
+  pub fn type_name(&self) -> Result<String, JsError> {
+    Ok(Self::TYPE_NAME.to_string())
+  }
+
+  /// Number of points. Exposed to JavaScript
+  pub fn n_points(&self) -> usize {
+    self.data.len()
+  }
+
+  /// Colour the points by the flattened RGBA `colors` (4 values per point), instead of their scalars or material
+  /// colour. Exposed to JavaScript
+  pub fn with_colors(mut self, colors: &[f32]) -> Result<PointCloud, JsError> {
+    if colors.len() != 4 * self.data.len() { return Err(Error::from("Colors require 4 values per point").into()); }
+    self.data = std::rc::Rc::new(PointData::new(self.data.positions.clone(), colors.to_vec(), Vec::new()));
+    Ok(self)
+  }
+
+  /// Colour the points by the `scalars` (1 value per point) mapped through the colormap of `style`, instead of
+  /// their colours or material colour. Exposed to JavaScript
+  pub fn with_scalars(mut self, scalars: &[f32], style: ScalarStyle) -> Result<PointCloud, JsError> {
+    if scalars.len() != self.data.len() { return Err(Error::from("Scalars require 1 value per point").into()); }
+    self.data = std::rc::Rc::new(PointData::new(self.data.positions.clone(), Vec::new(), scalars.to_vec()));
+    self.scalar_style = style;
+    Ok(self)
+  }
+
+  /// Remove the colours and scalars, the points being drawn with the material colour. Exposed to JavaScript
+  pub fn without_colors(mut self) -> Self {
+    self.data = std::rc::Rc::new(PointData::new(self.data.positions.clone(), Vec::new(), Vec::new()));
+    self
+  }
+
+  /// Replace the mapping of the scalars to colours. Exposed to JavaScript
+  pub fn with_scalar_style(mut self, style: ScalarStyle) -> Self {
+    self.scalar_style = style; self
+  }
+
+  /// Retrieve the mapping of the scalars to colours. Exposed to JavaScript
+  pub fn scalar_style(&self) -> ScalarStyle {
+    self.scalar_style.clone()
+  }
+
+  /// Replace the point style. Exposed to JavaScript
+  pub fn with_style(mut self, style: PointStyle) -> Self {
+    self.style = style; self
+  }
+
+  /// Retrieve the point style. Exposed to JavaScript
+  pub fn style(&self) -> PointStyle {
+    self.style.clone()
+  }
+
+  /// Replace the material. Exposed to JavaScript
+  pub fn with_material(mut self, material: Material) -> Self {
+    self.material = material; self
+  }
+
+  /// Retrieve the material. Exposed to JavaScript
+  pub fn material(&self) -> Material {
+    self.material.clone()
+  }
+
+  /// Draw the point cloud on the context
+  /// Exposed to JavaScript
+  pub fn draw(&self, context: &web_sys::WebGl2RenderingContext, renderer: &renderer::Renderer) -> Result<(), JsError> {
+    Drawable::draw(self, context, renderer)
+  }
+
+  pub fn clone(&self) -> Self {
+    Clone::clone(self)
+  }
+}
+
+impl Identifiable for PointCloud {
+  /// Retrieve the object uuid
+  fn uuid(&self) -> Result<String, Error> {
+    Ok(self.uid.clone())
+  }
+}
+
+impl Drawable for PointCloud {
+  /// Draw the points on the context
+  fn draw<T>(&self, context: &web_sys::WebGl2RenderingContext, renderer: &T) -> Result<(), JsError>
+  where T: renderer::RendererTrait {
+    let info = renderer::Info::Points {
+      uid: &self.uid,
+      points: &self.data,
+      style: &self.style,
+      scalar_style: &self.scalar_style,
+      material: &self.material,
+    };
+    Ok( renderer.draw(context, info)? )
+  }
+}
+
+/// Serialized form of a `PointCloud`
+#[derive(serde::Serialize, serde::Deserialize)]
+struct PointCloudJson {
+  #[serde(default = "nano_id")]
+  uid:          String,
+  positions:    Vec<f32>,
+  #[serde(default)]
+  colors:       Vec<f32>,
+  #[serde(default)]
+  scalars:      Vec<f32>,
+  #[serde(default)]
+  style:        PointStyle,
+  #[serde(default)]
+  scalar_style: ScalarStyle,
+  #[serde(default)]
+  material:     Material,
+}
+
+impl From<PointCloud> for PointCloudJson {
+  fn from(cloud: PointCloud) -> PointCloudJson {
+    PointCloudJson { uid: cloud.uid, positions: cloud.data.positions.clone(), colors: cloud.data.colors.clone(),
+      scalars: cloud.data.scalars.clone(), style: cloud.style, scalar_style: cloud.scalar_style, material: cloud.material }
+  }
+}
+
+impl TryFrom<PointCloudJson> for PointCloud {
+  type Error = String;
+  fn try_from(json: PointCloudJson) -> Result<PointCloud, String> {
+    let n = json.positions.len() / 3;
+    if json.positions.len() != 3 * n { return Err("Number of position values is not a multiple of 3".to_string()); }
+    if !json.colors.is_empty() && json.colors.len() != 4 * n { return Err("Colors require 4 values per point".to_string()); }
+    if !json.scalars.is_empty() && json.scalars.len() != n { return Err("Scalars require 1 value per point".to_string()); }
+    Ok( PointCloud { uid: json.uid, data: std::rc::Rc::new(PointData::new(json.positions, json.colors, json.scalars)),
+      style: json.style, scalar_style: json.scalar_style, material: json.material } )
+  }
+}
@@ -0,0 +1,52 @@
+use super::*;
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen::prelude::wasm_bindgen]
+#[derive(Default)]
+/// Builder pattern for `PointCloud` struct
+pub struct PointCloudBuilder {
+  positions: Option<Vec<f32>>,
+  colors: Option<Vec<f32>>,
+  scalars: Option<(Vec<f32>, ScalarStyle)>,
+  style: Option<PointStyle>,
+  material: Option<Material>,
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen::prelude::wasm_bindgen]
+impl PointCloudBuilder {
+  /// Create an empty `PointCloudBuilder`
+  pub fn new() -> PointCloudBuilder { PointCloudBuilder::default() }
+
+  /// Specify the flattened positions (3 values per point)
+  pub fn positions(mut self, positions: &[f32]) -> PointCloudBuilder { self.positions = Some(positions.to_vec()); self }
+
+  /// Specify the flattened RGBA colours (4 values per point). Optional
+  pub fn colors(mut self, colors: &[f32]) -> PointCloudBuilder { self.colors = Some(colors.to_vec()); self }
+
+  /// Specify the scalar values (1 value per point) and their mapping to colours. Optional
+  pub fn scalars(mut self, scalars: &[f32], style: ScalarStyle) -> PointCloudBuilder { self.scalars = Some((scalars.to_vec(), style)); self }
+
+  /// Specify the point style. Optional
+  pub fn style(mut self, style: PointStyle) -> PointCloudBuilder { self.style = Some(style); self }
+
+  /// Specify material. Optional
+  pub fn material(mut self, material: Material) -> PointCloudBuilder { self.material = Some(material); self }
+
+  /// Build a `PointCloud` object
+  pub fn build(self) -> Result<PointCloud, JsError> {
+    let cloud = PointCloud::new(
+      self.positions.ok_or("Positions are not specified")?,
+      self.style.unwrap_or_default(),
+      self.material.unwrap_or_default(),
+    )?;
+    let cloud = match self.colors {
+      Some(colors) => cloud.with_colors(&colors)?,
+      None => cloud,
+    };
+    match self.scalars {
+      Some((scalars, style)) => cloud.with_scalars(&scalars, style),
+      None => Ok(cloud),
+    }
+  }
+}
@@ -0,0 +1,99 @@
+use super::*;
+
+/// Shape of the sprite drawn for each point
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub enum PointShape {
+  /// Square of the point size
+  Square = 0,
+  /// Disc of the point size
+  #[default]
+  Round = 1,
+}
+
+/// Units of the point size
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub enum PointSizeUnits {
+  /// Pixels, constant on screen
+  #[default]
+  Screen = 1,
+  /// World units, shrinking with the distance to the camera
+  World = 2,
+}
+
+/// Size, shape and shading of the sprites of a point cloud
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[derive(Clone, Debug, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct PointStyle {
+  size:              f32,
+  size_units:        PointSizeUnits,
+  shape:             PointShape,
+  eye_dome_lighting: f32,
+}
+
+impl Default for PointStyle {
+  fn default() -> PointStyle {
+    PointStyle { size: 3.0, size_units: PointSizeUnits::default(), shape: PointShape::default(), eye_dome_lighting: 0.0 }
+  }
+}
+
+impl PointStyle {
+  /// Sprite size, in the size units
+  pub fn size(&self) -> f32 { self.size }
+
+  /// Units of the sprite size
+  pub fn size_units(&self) -> PointSizeUnits { self.size_units }
+
+  /// Shape of the sprites
+  pub fn shape(&self) -> PointShape { self.shape }
+
+  /// Strength of the eye-dome lighting. 0 when disabled
+  pub fn eye_dome_lighting(&self) -> f32 { self.eye_dome_lighting }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+impl PointStyle {
+  /// Create the default style: round sprites of 3 pixels, without eye-dome lighting
+  pub fn new() -> PointStyle { PointStyle::default() }
+
+  /// Set the sprite size, in the size units
+  pub fn with_size(mut self, size: f32) -> Result<PointStyle, JsError> {
+    if size.is_nan() || size <= 0.0 { return Err("Point size must be positive".into()); }
+    self.size = size; Ok(self)
+  }
+
+  /// Set the units of the sprite size
+  pub fn with_size_units(mut self, size_units: PointSizeUnits) -> PointStyle { self.size_units = size_units; self }
+
+  /// Set the shape of the sprites
+  pub fn with_shape(mut self, shape: PointShape) -> PointStyle { self.shape = shape; self }
+
+  /// Shade the points by eye-dome lighting of the given strength, darkening the points behind their neighbours
+  /// on screen to perceive depth without normals. The clouds with eye-dome lighting drawn in a frame are shaded
+  /// together, with the largest strength. Only opaque clouds are shaded
+  pub fn with_eye_dome_lighting(mut self, strength: f32) -> Result<PointStyle, JsError> {
+    if strength.is_nan() || strength < 0.0 { return Err("Eye-dome lighting strength must not be negative".into()); }
+    self.eye_dome_lighting = strength; Ok(self)
+  }
+
+  /// Disable eye-dome lighting
+  pub fn without_eye_dome_lighting(mut self) -> PointStyle { self.eye_dome_lighting = 0.0; self }
+
+  /// Expose clone functionality to JavaScript
+  pub fn clone(&self) -> PointStyle { Clone::clone(self) }
+
+  /// Convert point style to json
+  pub fn to_json(&self) -> Result<String, JsError> {
+    Ok(serde_json::to_string(&self).map_err(|e| format!("{e}"))?)
+  }
+
+  /// Create point style from json
+  pub fn from_json(json: &str) -> Result<PointStyle, JsError> {
+    Ok(serde_json::from_str(json).map_err(|e| format!("{e}"))?)
+  }
+}
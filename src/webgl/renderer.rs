@@ -3,21 +3,46 @@ use super::*;
 mod rendererbuilder; pub use rendererbuilder::RendererBuilder;
 mod renderer; pub use renderer::Renderer;
 mod picker; pub use picker::{Picker, builder::PickerBuilder};
+mod background; pub use background::Background;
+mod lighting; pub use lighting::Lighting;
+mod transparency; pub use transparency::Transparency;
+mod displaymode; pub use displaymode::DisplayMode;
+mod renderersettings; pub use renderersettings::RendererSettings;
+mod shadows; pub use shadows::Shadows;
+mod antialiasing; pub use antialiasing::Antialiasing;
+mod postprocessing; pub use postprocessing::{PostProcessing, PostPass, Uniform};
+mod customprogram; pub use customprogram::{CustomProgram, Primitive};
+mod highlight; pub use highlight::Highlight;
+mod axistriad; pub use axistriad::{AxisTriad, Corner};
+mod clipping; pub use clipping::{Clipping, ClipPlane};
+mod colorlegend; pub use colorlegend::{ColorLegend, Orientation};
+mod contextloss; pub use contextloss::ContextLossWatcher;
+mod batchbuffers; pub use batchbuffers::BatchKey;
+mod bounds;
+mod edges;
+mod eyedome;
+mod glyphatlas;
+mod pointbuffers;
+mod programbackground;
+mod programcomposite;
+mod programlabels;
+mod programlegend;
 mod programlines;
+mod programoutline;
+mod programpoints;
+mod programshadow;
 mod programtriangleswithnormals;
+mod programwidelines;
+mod screenshot;
+mod textures;
 mod utils;
+mod info; pub use info::{Info, OwnedInfo, Indices, Instances};
+mod recorder; pub use recorder::Recorder;
+mod statistics; pub use statistics::RenderStatistics;
 
-pub enum Info<'a> {
-  Lines{
-    uid:      &'a String,
-    vertices: &'a Vec<(nalgebra::Point3<f32>, nalgebra::Point3<f32>)>,
-  },
-  TrianglesWithNormals {
-    uid:      &'a String,
-    vertices: &'a Vec<(nalgebra::Point3<f32>, nalgebra::Point3<f32>, nalgebra::Point3<f32>)>,
-    normals:  &'a Vec<(nalgebra::Vector3<f32>, nalgebra::Vector3<f32>, nalgebra::Vector3<f32>)>
-  },
-}
+/// Internal format of the depth buffers of the frame targets. The depth of the target the scene is drawn into is
+/// blitted to the post-processing depth texture, which requires identical formats
+const DEPTH_FORMAT: u32 = web_sys::WebGl2RenderingContext::DEPTH24_STENCIL8;
 
 pub trait RendererTrait {
   fn init(&self, context: &web_sys::WebGl2RenderingContext) -> Result<(), Error>;
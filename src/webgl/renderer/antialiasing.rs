@@ -0,0 +1,157 @@
+use super::*;
+
+/// Anti-aliasing applied by the `Renderer`
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub enum Antialiasing {
+  /// No anti-aliasing, beyond the one of the canvas context
+  #[default]
+  None,
+  /// Rendering into a multisampled renderbuffer with the given number of samples per pixel, resolved using `blitFramebuffer`.
+  /// The number of samples is limited to `MAX_SAMPLES`. FXAA is applied instead when weighted blended transparency is active
+  Msaa(u32),
+  /// Fast approximate anti-aliasing post-pass
+  Fxaa,
+}
+
+/// Multisampled colour and depth renderbuffers
+pub struct MsaaTarget {
+  width:       i32,
+  height:      i32,
+  samples:     i32,
+  framebuffer: web_sys::WebGlFramebuffer,
+  _color:      web_sys::WebGlRenderbuffer,
+  _depth:      web_sys::WebGlRenderbuffer,
+}
+
+impl MsaaTarget {
+  /// Create the target with up to `samples` samples, or return `None` if multisampling is not available
+  pub fn new(context: &web_sys::WebGl2RenderingContext, width: i32, height: i32, samples: u32) -> Result<Option<MsaaTarget>, Error> {
+    let samples = Self::samples(context, samples);
+    if samples < 2 { return Ok(None); }
+
+    let color = Self::renderbuffer(context, width, height, samples, web_sys::WebGl2RenderingContext::RGBA8)?;
+    let depth = Self::renderbuffer(context, width, height, samples, DEPTH_FORMAT)?;
+    let framebuffer = context.create_framebuffer().ok_or("Unable to create frame buffer")?;
+    context.bind_framebuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER, Some(&framebuffer));
+    context.framebuffer_renderbuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER,
+      web_sys::WebGl2RenderingContext::COLOR_ATTACHMENT0,
+      web_sys::WebGl2RenderingContext::RENDERBUFFER,
+      Some(&color)
+    );
+    context.framebuffer_renderbuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER,
+      web_sys::WebGl2RenderingContext::DEPTH_STENCIL_ATTACHMENT,
+      web_sys::WebGl2RenderingContext::RENDERBUFFER,
+      Some(&depth)
+    );
+    let status = context.check_framebuffer_status(web_sys::WebGl2RenderingContext::FRAMEBUFFER);
+    context.bind_framebuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER, None);
+    if status != web_sys::WebGl2RenderingContext::FRAMEBUFFER_COMPLETE { return Ok(None); }
+    Ok(Some(MsaaTarget { width, height, samples, framebuffer, _color: color, _depth: depth }))
+  }
+
+  /// Whether the target matches the size and number of samples provided
+  pub fn has_size(&self, context: &web_sys::WebGl2RenderingContext, width: i32, height: i32, samples: u32) -> bool { 
+    self.width == width && self.height == height && self.samples == Self::samples(context, samples)
+  }
+
+  /// Bind the framebuffer
+  pub fn bind(&self, context: &web_sys::WebGl2RenderingContext) {
+    context.bind_framebuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER, Some(&self.framebuffer));
+  }
+
+  /// Framebuffer drawn into
+  pub fn framebuffer(&self) -> &web_sys::WebGlFramebuffer { &self.framebuffer }
+
+  /// Resolve the samples into the `output` framebuffer - the default framebuffer if `None`
+  pub fn resolve(&self, context: &web_sys::WebGl2RenderingContext, output: Option<&web_sys::WebGlFramebuffer>) {
+    context.bind_framebuffer(web_sys::WebGl2RenderingContext::READ_FRAMEBUFFER, Some(&self.framebuffer));
+    context.bind_framebuffer(web_sys::WebGl2RenderingContext::DRAW_FRAMEBUFFER, output);
+    context.blit_framebuffer(0, 0, self.width, self.height, 0, 0, self.width, self.height,
+      web_sys::WebGl2RenderingContext::COLOR_BUFFER_BIT, web_sys::WebGl2RenderingContext::NEAREST);
+    context.bind_framebuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER, output);
+  }
+
+  /// Number of samples supported, up to `samples`
+  fn samples(context: &web_sys::WebGl2RenderingContext, samples: u32) -> i32 {
+    let max_samples = context.get_parameter(web_sys::WebGl2RenderingContext::MAX_SAMPLES).ok()
+      .and_then(|v| v.as_f64()).unwrap_or(0.0) as u32;
+    samples.min(max_samples) as i32
+  }
+
+  fn renderbuffer(context: &web_sys::WebGl2RenderingContext, width: i32, height: i32, samples: i32, internal_format: u32) -> Result<web_sys::WebGlRenderbuffer, Error> {
+    let renderbuffer = context.create_renderbuffer().ok_or("Unable to create render buffer")?;
+    context.bind_renderbuffer(web_sys::WebGl2RenderingContext::RENDERBUFFER, Some(&renderbuffer));
+    context.renderbuffer_storage_multisample(web_sys::WebGl2RenderingContext::RENDERBUFFER, samples, internal_format, width, height);
+    Ok(renderbuffer)
+  }
+}
+
+/// Colour texture and depth renderbuffer, drawn to the default framebuffer by the FXAA pass
+pub struct FxaaTarget {
+  width:       i32,
+  height:      i32,
+  framebuffer: web_sys::WebGlFramebuffer,
+  color:       web_sys::WebGlTexture,
+  _depth:      web_sys::WebGlRenderbuffer,
+}
+
+impl FxaaTarget {
+  /// Create the target
+  pub fn new(context: &web_sys::WebGl2RenderingContext, width: i32, height: i32) -> Result<FxaaTarget, Error> {
+    let color = context.create_texture().ok_or("Unable to create texture")?;
+    context.bind_texture(web_sys::WebGl2RenderingContext::TEXTURE_2D, Some(&color));
+    context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_MIN_FILTER, web_sys::WebGl2RenderingContext::LINEAR.try_into()?);
+    context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_MAG_FILTER, web_sys::WebGl2RenderingContext::LINEAR.try_into()?);
+    context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_WRAP_S, web_sys::WebGl2RenderingContext::CLAMP_TO_EDGE.try_into()?);
+    context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_WRAP_T, web_sys::WebGl2RenderingContext::CLAMP_TO_EDGE.try_into()?);
+    context.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+      web_sys::WebGl2RenderingContext::TEXTURE_2D, 0, web_sys::WebGl2RenderingContext::RGBA8.try_into()?, width, height, 0, 
+      web_sys::WebGl2RenderingContext::RGBA, web_sys::WebGl2RenderingContext::UNSIGNED_BYTE, None,
+    )?;
+
+    let depth = context.create_renderbuffer().ok_or("Unable to create render buffer")?;
+    context.bind_renderbuffer(web_sys::WebGl2RenderingContext::RENDERBUFFER, Some(&depth));
+    context.renderbuffer_storage(web_sys::WebGl2RenderingContext::RENDERBUFFER, DEPTH_FORMAT, width, height);
+
+    let framebuffer = context.create_framebuffer().ok_or("Unable to create frame buffer")?;
+    context.bind_framebuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER, Some(&framebuffer));
+    context.framebuffer_texture_2d(web_sys::WebGl2RenderingContext::FRAMEBUFFER,
+      web_sys::WebGl2RenderingContext::COLOR_ATTACHMENT0,
+      web_sys::WebGl2RenderingContext::TEXTURE_2D,
+      Some(&color),
+      0,       // level
+    );
+    context.framebuffer_renderbuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER,
+      web_sys::WebGl2RenderingContext::DEPTH_STENCIL_ATTACHMENT,
+      web_sys::WebGl2RenderingContext::RENDERBUFFER,
+      Some(&depth)
+    );
+    context.bind_framebuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER, None);
+    Ok(FxaaTarget { width, height, framebuffer, color, _depth: depth })
+  }
+
+  /// Whether the target matches the size provided
+  pub fn has_size(&self, width: i32, height: i32) -> bool { self.width == width && self.height == height }
+
+  /// Bind the framebuffer
+  pub fn bind(&self, context: &web_sys::WebGl2RenderingContext) {
+    context.bind_framebuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER, Some(&self.framebuffer));
+  }
+
+  /// Framebuffer drawn into
+  pub fn framebuffer(&self) -> &web_sys::WebGlFramebuffer { &self.framebuffer }
+
+  /// Apply the FXAA pass, drawing into the `output` framebuffer - the default framebuffer if `None`
+  pub fn resolve(&self, 
+    context: &web_sys::WebGl2RenderingContext,
+    counters: &statistics::Counters,
+    program: &std::rc::Rc<std::cell::RefCell<Option<web_sys::WebGlProgram>>>,
+    output: Option<&web_sys::WebGlFramebuffer>,
+    ) -> Result<(), Error> {
+    context.bind_framebuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER, output);
+    programcomposite::ProgramComposite::draw(context, counters, program, programcomposite::CompositeMode::Fxaa, &self.color, None, (self.width, self.height))?;
+    context.enable(web_sys::WebGl2RenderingContext::DEPTH_TEST);
+    Ok(())
+  }
+}
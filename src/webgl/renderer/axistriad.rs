@@ -0,0 +1,84 @@
+/// Corner of the canvas an overlay is drawn in
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub enum Corner {
+  #[default]
+  BottomLeft,
+  BottomRight,
+  TopLeft,
+  TopRight,
+}
+
+impl Corner {
+  /// Bottom left pixel of a `width` x `height` area in this corner of a `size` frame, `margin` pixels from the edges
+  pub fn origin(&self, size: (i32, i32), width: i32, height: i32, margin: i32) -> (i32, i32) {
+    let left = match self { Corner::BottomLeft | Corner::TopLeft => margin, Corner::BottomRight | Corner::TopRight => size.0 - width - margin };
+    let bottom = match self { Corner::BottomLeft | Corner::BottomRight => margin, Corner::TopLeft | Corner::TopRight => size.1 - height - margin };
+    (left, bottom)
+  }
+}
+
+/// X, Y and Z arrows showing the orientation of the world axes, drawn in a corner viewport that follows
+/// the camera rotation only
+#[derive(Clone, Debug, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct AxisTriad {
+  pub visible: bool,
+  pub corner:  Corner,
+  /// Width and height of the viewport, in pixels
+  pub size:    u32,
+}
+
+impl Default for AxisTriad {
+  fn default() -> AxisTriad {
+    AxisTriad { visible: false, corner: Corner::BottomLeft, size: 96 }
+  }
+}
+
+impl AxisTriad {
+  /// Directions, colours and names of the axes
+  pub const AXES: [([f32; 3], [f32; 3], &'static str); 3] = [
+    ([1.0, 0.0, 0.0], [0.9, 0.2, 0.2], "X"),
+    ([0.0, 1.0, 0.0], [0.2, 0.75, 0.25], "Y"),
+    ([0.0, 0.0, 1.0], [0.25, 0.45, 1.0], "Z"),
+  ];
+  /// Length of the arrow shafts, the arrows being of unit length
+  pub const SHAFT: f32 = 0.72;
+  /// Radius of the arrow heads
+  pub const HEAD_RADIUS: f32 = 0.08;
+
+  /// Bottom left pixel, width and height of the viewport in a frame of `size` pixels
+  pub fn viewport(&self, size: (i32, i32)) -> (i32, i32, i32, i32) {
+    let s = self.size as i32;
+    let (x, y) = self.corner.origin(size, s, s, 0);
+    (x, y, s, s)
+  }
+
+  /// Orthographic view of the unit arrows rotated as the world by the `view` matrix
+  pub fn matrix(view: &nalgebra::Matrix4<f32>) -> nalgebra::Matrix4<f32> {
+    let mut rotation = nalgebra::Matrix4::identity();
+    rotation.fixed_view_mut::<3, 3>(0, 0).copy_from(&view.fixed_view::<3, 3>(0, 0));
+    // Margin left for the axis names
+    nalgebra::Matrix4::new_orthographic(-1.35, 1.35, -1.35, 1.35, -2.0, 2.0) * rotation
+  }
+
+  /// Flattened triangles of the cone heading the arrow along `axis`
+  pub fn head(axis: &nalgebra::Vector3<f32>) -> Vec<f32> {
+    const N: usize = 12;
+    let u = if axis.x.abs() < 0.9 { nalgebra::Vector3::x() } else { nalgebra::Vector3::y() }.cross(axis).normalize();
+    let v = axis.cross(&u);
+    let base = axis * Self::SHAFT;
+    let rim = |i: usize| {
+      let a = 2.0 * std::f32::consts::PI * (i % N) as f32 / N as f32;
+      base + Self::HEAD_RADIUS * (a.cos() * u + a.sin() * v)
+    };
+    (0..N).flat_map(|i| {
+      let (p1, p2) = (rim(i), rim(i + 1));
+      [*axis, p1, p2, base, p2, p1]
+    })
+    .flat_map(|p| [p.x, p.y, p.z])
+    .collect()
+  }
+}
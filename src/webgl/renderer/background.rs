@@ -0,0 +1,108 @@
+use super::*;
+
+/// Background drawn by the `Renderer` at the start of each frame
+#[derive(Clone)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub enum Background {
+  /// Uniform colour
+  Solid(Color),
+  /// Vertical gradient from `bottom` to `top`, drawn as a full-screen pass
+  VerticalGradient { top: Color, bottom: Color },
+  /// Image supplied as RGBA bytes (row by row, from the top row), stretched over the viewport
+  Image { width: u32, height: u32, data: Vec<u8> },
+  /// Fully transparent output, to composite the canvas over the HTML page.
+  /// Requires the WebGL context to be created with `alpha: true`
+  Transparent,
+}
+
+impl Default for Background {
+  fn default() -> Background { Background::Solid(Color::from_u8(6, 78, 59, 255)) }
+}
+
+impl Background {
+  /// Colour used to clear the colour buffer
+  fn clear_color(&self) -> Color {
+    match self {
+      Background::Solid(color) => *color,
+      Background::VerticalGradient { .. } | Background::Image { .. } => Color::new(0.0, 0.0, 0.0, 1.0),
+      Background::Transparent => Color::new(0.0, 0.0, 0.0, 0.0),
+    }
+  }
+
+  /// Clear the colour and depth buffers and draw the background
+  pub fn draw(&self,
+    context: &web_sys::WebGl2RenderingContext,
+    counters: &statistics::Counters,
+    program: &std::rc::Rc<std::cell::RefCell<Option<web_sys::WebGlProgram>>>,
+    texture: &std::rc::Rc<std::cell::RefCell<Option<web_sys::WebGlTexture>>>,
+    ) -> Result<(), Error> {
+    let c = self.clear_color();
+    context.clear_color(c.r(), c.g(), c.b(), c.a());
+    context.clear(web_sys::WebGl2RenderingContext::COLOR_BUFFER_BIT | web_sys::WebGl2RenderingContext::DEPTH_BUFFER_BIT);
+
+    let (mode, top, bottom) = match self {
+      Background::VerticalGradient { top, bottom } => (0, *top, *bottom),
+      Background::Image { .. } => (1, c, c),
+      _ => return Ok(()),
+    };
+
+    if program.borrow().is_none() {
+      *program.borrow_mut() = Some(programbackground::ProgramBackground::webgl_program(context)?);
+    }
+    let program = program.borrow();
+    let program = program.as_ref().ok_or("Unable to retrieve program to draw background...")?;
+    counters.use_program(context, program);
+
+    if let Background::Image { width, height, data } = self {
+      if texture.borrow().is_none() {
+        *texture.borrow_mut() = Some(Self::image_texture(context, *width, *height, data)?);
+      }
+      context.active_texture(web_sys::WebGl2RenderingContext::TEXTURE0);
+      context.bind_texture(web_sys::WebGl2RenderingContext::TEXTURE_2D, texture.borrow().as_ref());
+      let u_image = context.get_uniform_location(program, "u_image");
+      context.uniform1i(u_image.as_ref(), 0);
+    }
+
+    // Full screen quad
+    let positions: Vec<f32> = vec!(
+      -1.0, -1.0, 0.0,   1.0, -1.0, 0.0,   1.0, 1.0, 0.0,
+      -1.0, -1.0, 0.0,   1.0,  1.0, 0.0,  -1.0, 1.0, 0.0,
+    );
+    utils::bind(context, counters, program, "a_position", &positions)?;
+
+    let u_mode = context.get_uniform_location(program, "u_mode");
+    context.uniform1i(u_mode.as_ref(), mode);
+    let u_top = context.get_uniform_location(program, "u_top");
+    context.uniform4f(u_top.as_ref(), top.r(), top.g(), top.b(), top.a());
+    let u_bottom = context.get_uniform_location(program, "u_bottom");
+    context.uniform4f(u_bottom.as_ref(), bottom.r(), bottom.g(), bottom.b(), bottom.a());
+
+    context.disable(web_sys::WebGl2RenderingContext::DEPTH_TEST);
+    context.depth_mask(false);
+    context.draw_arrays(web_sys::WebGl2RenderingContext::TRIANGLES, 0, 6);
+    counters.record_draw(web_sys::WebGl2RenderingContext::TRIANGLES, 6, 1);
+    context.depth_mask(true);
+    Ok(())
+  }
+
+  /// Upload the background image into a texture
+  fn image_texture(context: &web_sys::WebGl2RenderingContext, width: u32, height: u32, data: &[u8]) -> Result<web_sys::WebGlTexture, Error> {
+    let texture = context.create_texture().ok_or("Unable to create texture")?;
+    context.bind_texture(web_sys::WebGl2RenderingContext::TEXTURE_2D, Some(&texture));
+    context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_MIN_FILTER, web_sys::WebGl2RenderingContext::LINEAR.try_into()?);
+    context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_WRAP_S, web_sys::WebGl2RenderingContext::CLAMP_TO_EDGE.try_into()?);
+    context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_WRAP_T, web_sys::WebGl2RenderingContext::CLAMP_TO_EDGE.try_into()?);
+    context.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+      web_sys::WebGl2RenderingContext::TEXTURE_2D,       // target
+      0,                                                 // level
+      web_sys::WebGl2RenderingContext::RGBA.try_into()?, // internal format
+      width.try_into()?,                                 // width
+      height.try_into()?,                                // height
+      0,                                                 // border
+      web_sys::WebGl2RenderingContext::RGBA,             // format
+      web_sys::WebGl2RenderingContext::UNSIGNED_BYTE,    // type
+      Some(data),                                        // data
+    )?;
+    Ok(texture)
+  }
+}
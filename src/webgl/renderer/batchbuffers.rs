@@ -0,0 +1,109 @@
+use super::*;
+
+/// Identity of the geometry merged by a `Batch`, shared by the copies of the batch until its elements change.
+/// The renderers cache the GPU buffers of the geometry while its identity is alive
+#[derive(Debug)]
+pub struct BatchKey {
+  uid: String,
+}
+
+impl BatchKey {
+  /// Create a new identity
+  pub fn new() -> std::rc::Rc<BatchKey> { std::rc::Rc::new(BatchKey { uid: nanoid::nanoid!(6) }) }
+
+  /// Retrieve the uid
+  pub fn uid(&self) -> &str { &self.uid }
+}
+
+/// GPU buffers of merged lines or triangles
+pub struct BatchBuffers {
+  /// Positions, 3 values per vertex
+  pub positions: web_sys::WebGlBuffer,
+  /// Normals, 3 values per vertex, for triangles
+  pub normals:   Option<web_sys::WebGlBuffer>,
+  /// Index of the originating element, 1 per vertex, created on first use by picking
+  pub elements:  Option<web_sys::WebGlBuffer>,
+  indices:       web_sys::WebGlBuffer,
+  index_type:    u32,
+  n_indices:     usize,
+}
+
+impl BatchBuffers {
+  /// Bind the positions to the attribute `position_key` and the normals, if any, to `a_normal`.
+  /// Assumes that the program has already been set
+  pub fn bind(&self, context: &web_sys::WebGl2RenderingContext, program: &web_sys::WebGlProgram, position_key: &str) {
+    utils::bind_buffer(context, program, position_key, Some(&self.positions), 3);
+    if let Some(normals) = self.normals.as_ref() { utils::bind_buffer(context, program, "a_normal", Some(normals), 3); }
+  }
+
+  /// Issue the draw call for `mode` primitives from the indices
+  pub fn draw(&self, context: &web_sys::WebGl2RenderingContext, counters: &statistics::Counters, mode: u32) -> Result<(), Error> {
+    context.bind_buffer(web_sys::WebGl2RenderingContext::ELEMENT_ARRAY_BUFFER, Some(&self.indices));
+    context.draw_elements_with_i32(mode, self.n_indices.try_into()?, self.index_type, 0);
+    counters.record_draw(mode, self.n_indices, 1);
+    Ok(())
+  }
+}
+
+/// GPU buffers of the merged geometry drawn, uploaded once and shared by the copies of a batch. The buffers are
+/// deleted once all copies of their `BatchKey` are dropped - when the batch is dropped or merged again
+#[derive(Default)]
+pub struct BatchBufferCache {
+  buffers: std::collections::HashMap<String, (std::rc::Weak<BatchKey>, BatchBuffers)>,
+}
+
+impl BatchBufferCache {
+  /// Number of merged geometries with GPU buffers
+  pub fn len(&self) -> usize { self.buffers.len() }
+
+  /// Buffers of the indexed lines or triangles `info` merged as `key`, uploaded on first use. The element
+  /// buffer is created from `elements` if given
+  pub fn buffers(&mut self,
+    context: &web_sys::WebGl2RenderingContext,
+    counters: &statistics::Counters,
+    key: &std::rc::Rc<BatchKey>,
+    info: &Info,
+    elements: Option<&[u32]>,
+    ) -> Result<&BatchBuffers, Error> {
+    if !self.buffers.contains_key(key.uid()) {
+      let (positions, normals, indices) = match info {
+        Info::IndexedLines { positions, indices, .. } => (positions, None, indices),
+        Info::IndexedTrianglesWithNormals { positions, normals, indices, .. } => (positions, Some(normals), indices),
+        _ => return Err("Merged geometry is neither indexed lines nor indexed triangles".into()),
+      };
+      let buffers = BatchBuffers {
+        positions:  utils::upload(context, counters, &utils::point3_to_vecf32(&positions.iter().collect()))?,
+        normals:    match normals {
+          Some(normals) => Some(utils::upload(context, counters, &utils::vector3_to_vecf32(&normals.iter().collect()))?),
+          None => None,
+        },
+        elements:   None,
+        indices:    utils::upload_indices(context, counters, indices)?,
+        index_type: indices.gl_type(),
+        n_indices:  indices.len(),
+      };
+      self.buffers.insert(key.uid().to_string(), (std::rc::Rc::downgrade(key), buffers));
+    }
+    let (_, buffers) = self.buffers.get_mut(key.uid()).ok_or("Unable to retrieve merged geometry buffers...")?;
+    if let (Some(elements), None) = (elements, buffers.elements.as_ref()) {
+      buffers.elements = Some(utils::upload_uint(context, counters, elements)?);
+    }
+    Ok(buffers)
+  }
+
+  /// Delete the GPU buffers no longer referenced
+  pub fn release_unused(&mut self, context: &web_sys::WebGl2RenderingContext) {
+    self.buffers.retain(|_, (key, buffers)| {
+      let used = key.strong_count() > 0;
+      if !used {
+        for buffer in [Some(&buffers.positions), buffers.normals.as_ref(), buffers.elements.as_ref(), Some(&buffers.indices)].into_iter().flatten() {
+          context.delete_buffer(Some(buffer));
+        }
+      }
+      used
+    });
+  }
+
+  /// Forget the GPU buffers of a lost context, without deleting them. They are uploaded again on next use
+  pub fn invalidate(&mut self) { self.buffers.clear(); }
+}
@@ -0,0 +1,49 @@
+use super::*;
+
+type Bounds = Option<(nalgebra::Point3<f32>, nalgebra::Point3<f32>)>;
+
+/// Bounding boxes of the elements drawn, keyed by element uid and by a fingerprint of their geometry so that they
+/// are computed once per geometry. The bounding boxes not used since the previous frame are released
+#[derive(Default)]
+pub struct BoundsCache {
+  bounds: std::collections::HashMap<(String, u64), Bounds>,
+  used:   std::collections::HashSet<(String, u64)>,
+}
+
+impl BoundsCache {
+  /// Axis aligned bounding box `(min, max)` of the vertices of `info`, if any
+  pub fn bounds(&mut self, info: &Info) -> Bounds {
+    let key = match info {
+      // Stored by the point cloud
+      Info::Points { points, .. } => return points.bounding_box(),
+      // Merged geometry is identified by its key
+      Info::Batched { key, .. } => (key.uid().to_string(), 0),
+      _ => (info.uid().to_string(), fingerprint(info)),
+    };
+    let bounds = *self.bounds.entry(key.clone()).or_insert_with(|| info.bounding_box());
+    self.used.insert(key);
+    bounds
+  }
+
+  /// Release the bounding boxes not used since the previous call
+  pub fn release_unused(&mut self) {
+    let used = std::mem::take(&mut self.used);
+    self.bounds.retain(|key, _| used.contains(key));
+  }
+}
+
+/// Hash of the positions of `info`
+fn fingerprint(info: &Info) -> u64 {
+  use std::hash::{Hash, Hasher};
+  let mut hasher = std::collections::hash_map::DefaultHasher::new();
+  let mut hash_point = |p: &nalgebra::Point3<f32>| { p.x.to_bits().hash(&mut hasher); p.y.to_bits().hash(&mut hasher); p.z.to_bits().hash(&mut hasher); };
+  match info {
+    Info::Lines { vertices, .. } => vertices.iter().flat_map(|(s, e)| [s, e]).for_each(&mut hash_point),
+    Info::TrianglesWithNormals { vertices, .. } => vertices.iter().flat_map(|(p1, p2, p3)| [p1, p2, p3]).for_each(&mut hash_point),
+    Info::IndexedLines { positions, .. } | Info::IndexedTrianglesWithNormals { positions, .. }
+    | Info::TexturedTriangles { positions, .. } | Info::ScalarTriangles { positions, .. }
+    | Info::Custom { positions, .. } => positions.iter().for_each(&mut hash_point),
+    _ => (),
+  }
+  hasher.finish()
+}
@@ -0,0 +1,303 @@
+use super::*;
+
+/// Set the plane equations `[a, b, c, d]`, keeping the points with `ax + by + cz + d >= 0`, on a program including the
+/// `clipping` chunk of the shader library
+pub fn set_uniforms(context: &web_sys::WebGl2RenderingContext, program: &web_sys::WebGlProgram, equations: &[[f32; 4]]) {
+  let u_n_clip_planes = context.get_uniform_location(program, "u_nClipPlanes");
+  context.uniform1i(u_n_clip_planes.as_ref(), equations.len().min(Clipping::MAX_EQUATIONS) as i32);
+  if !equations.is_empty() {
+    let u_clip_planes = context.get_uniform_location(program, "u_clipPlanes");
+    context.uniform4fv_with_f32_array(u_clip_planes.as_ref(), &equations.iter().take(Clipping::MAX_EQUATIONS).flatten().copied().collect::<Vec<f32>>());
+  }
+}
+
+/// Plane cutting away the geometry on the side its normal points to
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct ClipPlane {
+  normal: nalgebra::Vector3<f32>,
+  point:  nalgebra::Point3<f32>,
+}
+
+impl ClipPlane {
+  /// Equation `[a, b, c, d]` of the plane, positive on the side kept
+  pub fn equation(&self) -> [f32; 4] {
+    let n = -self.normal;
+    [n.x, n.y, n.z, -n.dot(&self.point.coords)]
+  }
+
+  /// Unit normal, pointing to the side cut away
+  pub fn normal_vector(&self) -> nalgebra::Vector3<f32> { self.normal }
+
+  /// Plane through `point` with the `normal`, normalised, pointing to the side cut away
+  fn from_normal(normal: nalgebra::Vector3<f32>, point: nalgebra::Point3<f32>) -> Result<ClipPlane, String> {
+    if !normal.iter().chain(point.iter()).all(|v| v.is_finite()) { return Err("Clipping plane requires finite values".into()); }
+    if normal.norm() == 0.0 { return Err("Clipping plane normal must not be null".into()); }
+    Ok(ClipPlane { normal: normal.normalize(), point })
+  }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+impl ClipPlane {
+  /// Create the plane through `point` with the `normal` pointing to the side cut away, as slices `[x, y, z]`
+  pub fn new(normal: &[f32], point: &[f32]) -> Result<ClipPlane, JsError> {
+    if normal.len() != 3 || point.len() != 3 { return Err("Clipping plane requires a normal and a point of 3 values".into()); }
+    Ok(ClipPlane::from_normal(nalgebra::Vector3::from_row_slice(normal), nalgebra::Point3::from_slice(point))?)
+  }
+
+  /// Retrieve the unit normal `[x, y, z]`
+  pub fn normal(&self) -> Vec<f32> { self.normal.as_slice().to_vec() }
+
+  /// Retrieve the point `[x, y, z]` the plane goes through
+  pub fn point(&self) -> Vec<f32> { self.point.coords.as_slice().to_vec() }
+
+  /// Plane moved by `distance` along its normal
+  pub fn translated(&self, distance: f32) -> ClipPlane {
+    ClipPlane { point: self.point + distance * self.normal, ..*self }
+  }
+
+  /// Plane cutting away the other side
+  pub fn flipped(&self) -> ClipPlane {
+    ClipPlane { normal: -self.normal, ..*self }
+  }
+
+  /// Expose clone functionality to JavaScript
+  pub fn clone(&self) -> ClipPlane { Clone::clone(self) }
+}
+
+/// Axis aligned box outside of which geometry is cut away
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ClipBox {
+  min: nalgebra::Point3<f32>,
+  max: nalgebra::Point3<f32>,
+}
+
+impl ClipBox {
+  /// Box from `min` to `max`
+  fn new(min: nalgebra::Point3<f32>, max: nalgebra::Point3<f32>) -> Result<ClipBox, String> {
+    if min.iter().zip(max.iter()).any(|(a, b)| a > b) { return Err("Clipping box minimum exceeds its maximum".into()); }
+    Ok(ClipBox { min, max })
+  }
+}
+
+/// Clipping planes and clipping box of a section view, with the colour of the caps closing the cut solids.
+/// Held by the `Camera` so that it is serialized with the view and applied when picking
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[derive(Clone, Debug, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(into = "ClippingJson", try_from = "ClippingJson")]
+pub struct Clipping {
+  planes:    Vec<ClipPlane>,
+  clip_box:  Option<ClipBox>,
+  caps:      bool,
+  cap_color: Color,
+}
+
+impl Default for Clipping {
+  fn default() -> Clipping {
+    Clipping { planes: Vec::new(), clip_box: None, caps: true, cap_color: Color::rgb(0.8, 0.35, 0.3) }
+  }
+}
+
+impl Clipping {
+  /// Maximum number of clipping planes, in addition to the six planes of the clipping box
+  pub const MAX_PLANES: usize = 6;
+  /// Maximum number of plane equations supported by the programs
+  pub const MAX_EQUATIONS: usize = Self::MAX_PLANES + 6;
+
+  /// Whether no geometry is clipped
+  pub fn is_empty(&self) -> bool { self.planes.is_empty() && self.clip_box.is_none() }
+
+  /// Whether cut solids are closed by caps
+  pub fn caps(&self) -> bool { self.caps }
+
+  /// Colour of the caps
+  pub fn cap_color(&self) -> Color { self.cap_color }
+
+  /// Equations `[a, b, c, d]` of the clipping planes followed by the ones of the clipping box faces,
+  /// keeping the points with `ax + by + cz + d >= 0`
+  pub fn equations(&self) -> Vec<[f32; 4]> {
+    let mut equations = self.planes.iter().map(|p| p.equation()).collect::<Vec<_>>();
+    if let Some(ClipBox { min, max }) = self.clip_box {
+      for i in 0..3 {
+        let mut n = [0.0; 4];
+        n[i] = 1.0; n[3] = -min[i];
+        equations.push(n);
+        let mut n = [0.0; 4];
+        n[i] = -1.0; n[3] = max[i];
+        equations.push(n);
+      }
+    }
+    equations
+  }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+impl Clipping {
+  /// Create a clipping without any plane or box
+  pub fn new() -> Clipping { Clipping::default() }
+
+  /// Add a clipping plane
+  pub fn with_plane(mut self, plane: ClipPlane) -> Result<Clipping, JsError> {
+    if self.planes.len() >= Self::MAX_PLANES { return Err(format!("A maximum of {} clipping planes is supported", Self::MAX_PLANES).into()); }
+    self.planes.push(plane); Ok(self)
+  }
+
+  /// Replace the clipping plane at `index`
+  pub fn with_plane_at(mut self, index: usize, plane: ClipPlane) -> Result<Clipping, JsError> {
+    *self.planes.get_mut(index).ok_or(format!("No clipping plane at index {index}"))? = plane; Ok(self)
+  }
+
+  /// Move the clipping plane at `index` by `distance` along its normal
+  pub fn with_translated_plane(mut self, index: usize, distance: f32) -> Result<Clipping, JsError> {
+    let plane = self.planes.get_mut(index).ok_or(format!("No clipping plane at index {index}"))?;
+    *plane = plane.translated(distance); Ok(self)
+  }
+
+  /// Remove the clipping plane at `index`
+  pub fn without_plane(mut self, index: usize) -> Clipping {
+    if index < self.planes.len() { self.planes.remove(index); }
+    self
+  }
+
+  /// Remove all clipping planes
+  pub fn without_planes(mut self) -> Clipping { self.planes.clear(); self }
+
+  /// Number of clipping planes
+  pub fn n_planes(&self) -> usize { self.planes.len() }
+
+  /// Retrieve the clipping plane at `index`
+  pub fn plane(&self, index: usize) -> Option<ClipPlane> { self.planes.get(index).copied() }
+
+  /// Cut away the geometry outside of the axis aligned box from `min` to `max`, as slices `[x, y, z]`
+  pub fn with_box(mut self, min: &[f32], max: &[f32]) -> Result<Clipping, JsError> {
+    if min.len() != 3 || max.len() != 3 { return Err("Clipping box requires a minimum and maximum of 3 values".into()); }
+    self.clip_box = Some(ClipBox::new(nalgebra::Point3::from_slice(min), nalgebra::Point3::from_slice(max))?); Ok(self)
+  }
+
+  /// Move the clipping box by `[dx, dy, dz]`
+  pub fn with_translated_box(mut self, dx: f32, dy: f32, dz: f32) -> Clipping {
+    if let Some(ClipBox { min, max }) = self.clip_box.as_mut() {
+      let d = nalgebra::Vector3::new(dx, dy, dz);
+      *min += d; *max += d;
+    }
+    self
+  }
+
+  /// Remove the clipping box
+  pub fn without_box(mut self) -> Clipping { self.clip_box = None; self }
+
+  /// Retrieve the minimum corner `[x, y, z]` of the clipping box, if any
+  pub fn box_min(&self) -> Option<Vec<f32>> { self.clip_box.map(|b| b.min.coords.as_slice().to_vec()) }
+
+  /// Retrieve the maximum corner `[x, y, z]` of the clipping box, if any
+  pub fn box_max(&self) -> Option<Vec<f32>> { self.clip_box.map(|b| b.max.coords.as_slice().to_vec()) }
+
+  /// Set whether cut solids are closed by caps. Caps require a stencil buffer: anti-aliasing, transparency or
+  /// post-processing targets provide one, otherwise the canvas context must be created with `stencil: true`
+  pub fn with_caps(mut self, caps: bool) -> Clipping { self.caps = caps; self }
+
+  /// Set the colour of the caps
+  pub fn with_cap_color(mut self, color: Color) -> Clipping { self.cap_color = color; self }
+
+  /// Expose clone functionality to JavaScript
+  pub fn clone(&self) -> Clipping { Clone::clone(self) }
+
+  /// Convert clipping to json
+  pub fn to_json(&self) -> Result<String, JsError> {
+    Ok(serde_json::to_string(&self).map_err(|e| format!("{e}"))?)
+  }
+
+  /// Create clipping from json
+  pub fn from_json(json: &str) -> Result<Clipping, JsError> {
+    Ok(serde_json::from_str(json).map_err(|e| format!("{e}"))?)
+  }
+}
+
+/// Serialized form of a `Clipping`
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+struct ClippingJson {
+  planes:    Vec<ClipPlane>,
+  #[serde(rename = "box")]
+  clip_box:  Option<ClipBox>,
+  caps:      bool,
+  cap_color: Color,
+}
+
+impl Default for ClippingJson {
+  fn default() -> ClippingJson { Clipping::default().into() }
+}
+
+impl From<Clipping> for ClippingJson {
+  fn from(clipping: Clipping) -> ClippingJson {
+    ClippingJson { planes: clipping.planes, clip_box: clipping.clip_box, caps: clipping.caps, cap_color: clipping.cap_color }
+  }
+}
+
+impl TryFrom<ClippingJson> for Clipping {
+  type Error = String;
+  fn try_from(json: ClippingJson) -> Result<Clipping, String> {
+    if json.planes.len() > Clipping::MAX_PLANES { return Err(format!("A maximum of {} clipping planes is supported", Clipping::MAX_PLANES)); }
+    // Validated as when built
+    let planes = json.planes.iter().map(|p| ClipPlane::from_normal(p.normal, p.point)).collect::<Result<Vec<_>, _>>()?;
+    let clip_box = json.clip_box.map(|b| ClipBox::new(b.min, b.max)).transpose()?;
+    Ok( Clipping { planes, clip_box, caps: json.caps, cap_color: json.cap_color } )
+  }
+}
+
+#[cfg(all(test, feature = "wasm"))]
+mod tests {
+  use super::*;
+
+  /// Clipping with a plane cutting away `x > 1` and the unit box
+  fn clipping() -> Clipping {
+    let plane = ClipPlane::new(&[2.0, 0.0, 0.0], &[1.0, 0.0, 0.0]).unwrap();
+    Clipping::new().with_plane(plane).unwrap().with_box(&[0.0, 0.0, 0.0], &[1.0, 1.0, 1.0]).unwrap()
+  }
+
+  #[test]
+  fn plane_normal_is_normalised() {
+    let plane = ClipPlane::new(&[0.0, 3.0, 4.0], &[0.0, 0.0, 0.0]).unwrap();
+    assert_eq!(plane.normal(), vec![0.0, 0.6, 0.8]);
+  }
+
+  #[test]
+  fn plane_equation_keeps_the_side_opposite_to_the_normal() {
+    let [a, b, c, d] = ClipPlane::new(&[2.0, 0.0, 0.0], &[1.0, 0.0, 0.0]).unwrap().equation();
+    assert_eq!([a, b, c, d], [-1.0, 0.0, 0.0, 1.0]);
+    assert!(a * 0.5 + d > 0.0);
+    assert!(a * 1.5 + d < 0.0);
+  }
+
+  #[test]
+  fn box_adds_six_equations_after_the_planes() {
+    let equations = clipping().equations();
+    assert_eq!(equations.len(), 7);
+    assert_eq!(&equations[1..3], &[[1.0, 0.0, 0.0, 0.0], [-1.0, 0.0, 0.0, 1.0]]);
+  }
+
+  #[test]
+  fn json_round_trip() {
+    let clipping = clipping().with_caps(false);
+    let json = serde_json::to_string(&clipping).unwrap();
+    assert_eq!(serde_json::from_str::<Clipping>(&json).unwrap(), clipping);
+  }
+
+  #[test]
+  fn json_plane_normal_is_normalised() {
+    let clipping = serde_json::from_str::<Clipping>(r#"{"planes":[{"normal":[0,0,2],"point":[0,0,1]}]}"#).unwrap();
+    assert_eq!(clipping.plane(0).map(|p| p.normal()), Some(vec![0.0, 0.0, 1.0]));
+  }
+
+  #[test]
+  fn json_rejects_invalid_clipping() {
+    let plane = r#"{"normal":[1,0,0],"point":[0,0,0]}"#;
+    let planes = [plane; Clipping::MAX_PLANES + 1].join(",");
+    assert!(serde_json::from_str::<Clipping>(&format!(r#"{{"planes":[{planes}]}}"#)).is_err());
+    assert!(serde_json::from_str::<Clipping>(r#"{"planes":[{"normal":[0,0,0],"point":[0,0,0]}]}"#).is_err());
+    assert!(serde_json::from_str::<Clipping>(r#"{"box":{"min":[0,2,0],"max":[1,1,1]}}"#).is_err());
+  }
+}
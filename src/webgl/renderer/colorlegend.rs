@@ -0,0 +1,235 @@
+use super::*;
+
+/// Direction of the gradient bar of a `ColorLegend`
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub enum Orientation {
+  /// Minimum at the bottom, tick values to the right
+  #[default]
+  Vertical = 0,
+  /// Minimum on the left, tick values below
+  Horizontal = 1,
+}
+
+/// Colour legend drawn over a corner of the rendered image: gradient of the colormap of a `ScalarStyle`, tick
+/// values, title and units over the range of the style. Without a range set by `ScalarStyle::with_range`, the legend
+/// shows the range colouring the first element drawn in the frame over the range of its own values - other such
+/// elements being coloured over theirs - and is not drawn if there is none
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[derive(Clone, Debug, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ColorLegend {
+  style:       ScalarStyle,
+  title:       String,
+  units:       String,
+  orientation: Orientation,
+  corner:      Corner,
+  length:      u32,
+  thickness:   u32,
+  ticks:       u32,
+  font_size:   f32,
+  text_color:  Color,
+  background:  Option<Color>,
+}
+
+impl Default for ColorLegend {
+  fn default() -> ColorLegend {
+    ColorLegend {
+      style:       ScalarStyle::default(),
+      title:       String::new(),
+      units:       String::new(),
+      orientation: Orientation::Vertical,
+      corner:      Corner::BottomRight,
+      length:      200,
+      thickness:   16,
+      ticks:       5,
+      font_size:   12.0,
+      text_color:  Color::rgb(0.1, 0.1, 0.1),
+      background:  Some(Color::new(1.0, 1.0, 1.0, 0.8)),
+    }
+  }
+}
+
+/// Placement of the parts of a `ColorLegend`, in pixels of the image with y upwards
+pub struct LegendLayout {
+  /// Background box `[left, bottom, right, top]`
+  pub frame:  [f32; 4],
+  /// Gradient bar `[left, bottom, right, top]`
+  pub bar:    [f32; 4],
+  /// Tick marks `[left, bottom, right, top]`
+  pub ticks:  Vec<[f32; 4]>,
+  /// Centre and text of the tick values and the title
+  pub texts:  Vec<([f32; 2], String)>,
+}
+
+impl ColorLegend {
+  /// Maximum number of ticks
+  pub const MAX_TICKS: u32 = 21;
+  /// Distance, in pixels, between the legend and the edges of the image
+  pub const MARGIN: i32 = 12;
+  /// Number of bands up to which ticks are placed at the band limits
+  const MAX_BAND_TICKS: u32 = 10;
+
+  /// Scalar style whose colormap is shown
+  pub fn style(&self) -> &ScalarStyle { &self.style }
+
+  /// Font size of the texts, in pixels
+  pub fn font_size(&self) -> f32 { self.font_size }
+
+  /// Colour of the texts, tick marks and bar outline
+  pub fn text_color(&self) -> Color { self.text_color }
+
+  /// Colour of the background box, if drawn
+  pub fn background(&self) -> Option<Color> { self.background }
+
+  /// Title line: the title followed by the units in brackets
+  fn heading(&self) -> String {
+    match (self.title.is_empty(), self.units.is_empty()) {
+      (_, true) => self.title.clone(),
+      (true, false) => format!("[{}]", self.units),
+      (false, false) => format!("{} [{}]", self.title, self.units),
+    }
+  }
+
+  /// Tick values over `(min, max)`: the band limits when banded with few bands, evenly spaced otherwise
+  pub fn tick_values(&self, (min, max): (f32, f32)) -> Vec<f32> {
+    let bands = self.style.bands();
+    let n = if bands > 0 && bands <= Self::MAX_BAND_TICKS { bands } else { self.ticks.max(2) - 1 };
+    (0..=n).map(|i| min + (max - min) * i as f32 / n as f32).collect()
+  }
+
+  /// Text of the tick `value`, with the decimals resolving the `step` between ticks
+  pub fn format_tick(value: f32, step: f32) -> String {
+    let magnitude = value.abs().max(step.abs());
+    if magnitude != 0.0 && !(1e-3..1e5).contains(&magnitude) { return format!("{value:.2e}"); }
+    let decimals = if step > 0.0 { (-step.log10().floor()).clamp(0.0, 6.0) as usize } else { 0 };
+    // Avoid "-0"
+    let value = if value == 0.0 { 0.0 } else { value };
+    format!("{value:.decimals$}")
+  }
+
+  /// Place the legend for the range `(min, max)` in its corner of an image of `size` pixels, the width of
+  /// texts being given by `measure`
+  pub fn layout<F>(&self, (min, max): (f32, f32), size: (i32, i32), measure: F) -> LegendLayout
+  where F: Fn(&str) -> f32 {
+    let values = self.tick_values((min, max));
+    let step = (max - min) / (values.len() - 1) as f32;
+    let labels = values.iter().map(|v| Self::format_tick(*v, step)).collect::<Vec<_>>();
+    let label_width = labels.iter().map(|l| measure(l)).fold(0.0, f32::max);
+    let heading = self.heading();
+    let heading_width = if heading.is_empty() { 0.0 } else { measure(&heading) };
+
+    let line_height = glyphatlas::GlyphAtlas::CELL_HEIGHT / glyphatlas::GlyphAtlas::FONT_SIZE * self.font_size;
+    let padding = 0.5 * self.font_size;
+    let gap = 0.25 * self.font_size;
+    let heading_height = if heading.is_empty() { 0.0 } else { line_height + gap };
+    let tick = 0.25 * self.thickness as f32;
+    let (length, thickness) = (self.length as f32, self.thickness as f32);
+
+    let (width, height) = match self.orientation {
+      Orientation::Vertical => (
+        (thickness + tick + gap + label_width).max(heading_width),
+        heading_height + length + line_height,
+      ),
+      Orientation::Horizontal => (
+        (length + label_width).max(heading_width),
+        heading_height + thickness + tick + gap + line_height,
+      ),
+    };
+    let (width, height) = (width + 2.0 * padding, height + 2.0 * padding);
+    let (x0, y0) = self.corner.origin(size, width.ceil() as i32, height.ceil() as i32, Self::MARGIN);
+    let (x0, y0) = (x0 as f32, y0 as f32);
+
+    let mut texts = Vec::new();
+    let (bar, ticks) = match self.orientation {
+      Orientation::Vertical => {
+        let (left, bottom) = (x0 + padding, y0 + padding + 0.5 * line_height);
+        let bar = [left, bottom, left + thickness, bottom + length];
+        let ticks = values.iter().zip(labels.iter()).map(|(v, label)| {
+          let y = bottom + length * (v - min) / (max - min);
+          texts.push(([bar[2] + tick + gap + 0.5 * measure(label), y], label.clone()));
+          [bar[2], y - 0.5, bar[2] + tick, y + 0.5]
+        }).collect();
+        if !heading.is_empty() { texts.push(([left + 0.5 * heading_width, bar[3] + 0.5 * line_height + gap + 0.5 * line_height], heading)); }
+        (bar, ticks)
+      },
+      Orientation::Horizontal => {
+        let left = x0 + 0.5 * (width - length);
+        let bottom = y0 + padding + line_height + gap + tick;
+        let bar = [left, bottom, left + length, bottom + thickness];
+        let ticks = values.iter().zip(labels.iter()).map(|(v, label)| {
+          let x = left + length * (v - min) / (max - min);
+          texts.push(([x, bottom - tick - gap - 0.5 * line_height], label.clone()));
+          [x - 0.5, bottom - tick, x + 0.5, bottom]
+        }).collect();
+        if !heading.is_empty() { texts.push(([x0 + 0.5 * width, bar[3] + gap + 0.5 * line_height], heading)); }
+        (bar, ticks)
+      },
+    };
+    LegendLayout { frame: [x0, y0, x0 + width, y0 + height], bar, ticks, texts }
+  }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+impl ColorLegend {
+  /// Create a vertical legend of the colormap of `style`, in the bottom right corner
+  pub fn new(style: ScalarStyle) -> ColorLegend { ColorLegend { style, ..ColorLegend::default() } }
+
+  /// Set the scalar style whose colormap is shown
+  pub fn with_style(mut self, style: ScalarStyle) -> ColorLegend { self.style = style; self }
+
+  /// Set the title
+  pub fn with_title(mut self, title: String) -> ColorLegend { self.title = title; self }
+
+  /// Set the units, shown in brackets after the title
+  pub fn with_units(mut self, units: String) -> ColorLegend { self.units = units; self }
+
+  /// Set the orientation of the gradient bar
+  pub fn with_orientation(mut self, orientation: Orientation) -> ColorLegend { self.orientation = orientation; self }
+
+  /// Set the corner of the image the legend is drawn in
+  pub fn with_corner(mut self, corner: Corner) -> ColorLegend { self.corner = corner; self }
+
+  /// Set the length and thickness, in pixels, of the gradient bar
+  pub fn with_bar_size(mut self, length: u32, thickness: u32) -> Result<ColorLegend, JsError> {
+    if length == 0 || thickness == 0 { return Err("Legend bar length and thickness must be positive".into()); }
+    self.length = length; self.thickness = thickness; Ok(self)
+  }
+
+  /// Set the number of tick values, including the range limits. Ticks are placed at the band limits instead
+  /// when the style has up to 10 bands
+  pub fn with_ticks(mut self, ticks: u32) -> Result<ColorLegend, JsError> {
+    if !(2..=Self::MAX_TICKS).contains(&ticks) { return Err(format!("Number of ticks must be between 2 and {}", Self::MAX_TICKS).into()); }
+    self.ticks = ticks; Ok(self)
+  }
+
+  /// Set the font size of the texts, in pixels
+  pub fn with_font_size(mut self, font_size: f32) -> Result<ColorLegend, JsError> {
+    if font_size.is_nan() || font_size <= 0.0 { return Err("Font size must be positive".into()); }
+    self.font_size = font_size; Ok(self)
+  }
+
+  /// Set the colour of the texts, tick marks and bar outline
+  pub fn with_text_color(mut self, color: Color) -> ColorLegend { self.text_color = color; self }
+
+  /// Set the colour of the background box
+  pub fn with_background(mut self, color: Color) -> ColorLegend { self.background = Some(color); self }
+
+  /// Draw the legend without background box
+  pub fn without_background(mut self) -> ColorLegend { self.background = None; self }
+
+  /// Expose clone functionality to JavaScript
+  pub fn clone(&self) -> ColorLegend { Clone::clone(self) }
+
+  /// Convert colour legend to json
+  pub fn to_json(&self) -> Result<String, JsError> {
+    Ok(serde_json::to_string(&self).map_err(|e| format!("{e}"))?)
+  }
+
+  /// Create colour legend from json
+  pub fn from_json(json: &str) -> Result<ColorLegend, JsError> {
+    Ok(serde_json::from_str(json).map_err(|e| format!("{e}"))?)
+  }
+}
@@ -0,0 +1,112 @@
+use super::*;
+
+/// State of the WebGL context at the start of a frame
+#[derive(Clone, Copy, PartialEq)]
+pub enum ContextStatus {
+  /// The GPU resources created are valid
+  Valid,
+  /// The context is lost: nothing can be drawn
+  Lost,
+  /// The context was restored since the previous frame: the GPU resources created before are invalid
+  Restored,
+}
+
+/// Detect the loss and restoration of the WebGL context the GPU resources of a renderer are created in. A sentinel
+/// buffer is created with the resources: once the context is restored, the buffer - as all objects of the lost
+/// context - is no longer valid
+#[derive(Clone, Default)]
+pub struct ContextState {
+  sentinel: std::rc::Rc<std::cell::RefCell<Option<web_sys::WebGlBuffer>>>,
+  lost:     std::rc::Rc<std::cell::Cell<bool>>,
+}
+
+impl ContextState {
+  /// Check the context at the start of a frame
+  pub fn check(&self, context: &web_sys::WebGl2RenderingContext) -> Result<ContextStatus, Error> {
+    if context.is_context_lost() {
+      self.lost.set(true);
+      return Ok(ContextStatus::Lost);
+    }
+    self.lost.set(false);
+    let valid = self.sentinel.borrow().as_ref().map(|s| context.is_buffer(Some(s)));
+    if valid != Some(true) {
+      let sentinel = context.create_buffer().ok_or("Unable to create buffer")?;
+      // Buffers become valid once bound
+      context.bind_buffer(web_sys::WebGl2RenderingContext::ARRAY_BUFFER, Some(&sentinel));
+      context.bind_buffer(web_sys::WebGl2RenderingContext::ARRAY_BUFFER, None);
+      *self.sentinel.borrow_mut() = Some(sentinel);
+    }
+    Ok(if valid == Some(false) { ContextStatus::Restored } else { ContextStatus::Valid })
+  }
+
+  /// Whether the context was lost at the last check
+  pub fn is_lost(&self) -> bool { self.lost.get() }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen::prelude::wasm_bindgen]
+/// Listen to the `webglcontextlost` and `webglcontextrestored` events of a canvas. The default handling of the
+/// loss is prevented so that the browser restores the context, and the JavaScript callbacks provided are called
+/// with the events. The `Renderer` and `Picker` recreate their GPU resources on the first frame after the restoration.
+/// The listeners are removed when the watcher is dropped
+pub struct ContextLossWatcher {
+  canvas:      web_sys::HtmlCanvasElement,
+  lost:        std::rc::Rc<std::cell::Cell<bool>>,
+  n_restored:  std::rc::Rc<std::cell::Cell<u32>>,
+  on_lost:     wasm_bindgen::closure::Closure<dyn FnMut(web_sys::Event)>,
+  on_restored: wasm_bindgen::closure::Closure<dyn FnMut(web_sys::Event)>,
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen::prelude::wasm_bindgen]
+impl ContextLossWatcher {
+  /// Watch the context of `canvas`, calling `on_lost` and `on_restored` - if provided - with the events
+  pub fn new(canvas: web_sys::HtmlCanvasElement, on_lost: Option<js_sys::Function>, on_restored: Option<js_sys::Function>) -> Result<ContextLossWatcher, JsError> {
+    let lost = std::rc::Rc::new(std::cell::Cell::new(false));
+    let n_restored = std::rc::Rc::new(std::cell::Cell::new(0));
+
+    let on_lost = {
+      let lost = lost.clone();
+      wasm_bindgen::closure::Closure::<dyn FnMut(web_sys::Event)>::new(move |event: web_sys::Event| {
+        event.prevent_default();
+        lost.set(true);
+        if let Some(callback) = &on_lost {
+          if let Err(e) = callback.call1(&wasm_bindgen::JsValue::NULL, &event) { web_sys::console::error_1(&e); }
+        }
+      })
+    };
+    let on_restored = {
+      let (lost, n_restored) = (lost.clone(), n_restored.clone());
+      wasm_bindgen::closure::Closure::<dyn FnMut(web_sys::Event)>::new(move |event: web_sys::Event| {
+        lost.set(false);
+        n_restored.set(n_restored.get() + 1);
+        if let Some(callback) = &on_restored {
+          if let Err(e) = callback.call1(&wasm_bindgen::JsValue::NULL, &event) { web_sys::console::error_1(&e); }
+        }
+      })
+    };
+    canvas.add_event_listener_with_callback(Self::LOST, wasm_bindgen::JsCast::unchecked_ref(on_lost.as_ref()))?;
+    canvas.add_event_listener_with_callback(Self::RESTORED, wasm_bindgen::JsCast::unchecked_ref(on_restored.as_ref()))?;
+    Ok(ContextLossWatcher { canvas, lost, n_restored, on_lost, on_restored })
+  }
+
+  /// Whether the context is lost
+  pub fn is_lost(&self) -> bool { self.lost.get() }
+
+  /// Number of times the context was restored
+  pub fn n_restored(&self) -> u32 { self.n_restored.get() }
+}
+
+#[cfg(feature = "wasm")]
+impl ContextLossWatcher {
+  const LOST: &str = "webglcontextlost";
+  const RESTORED: &str = "webglcontextrestored";
+}
+
+#[cfg(feature = "wasm")]
+impl Drop for ContextLossWatcher {
+  fn drop(&mut self) {
+    let _ = self.canvas.remove_event_listener_with_callback(Self::LOST, wasm_bindgen::JsCast::unchecked_ref(self.on_lost.as_ref()));
+    let _ = self.canvas.remove_event_listener_with_callback(Self::RESTORED, wasm_bindgen::JsCast::unchecked_ref(self.on_restored.as_ref()));
+  }
+}
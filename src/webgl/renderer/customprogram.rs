@@ -0,0 +1,194 @@
+use super::*;
+
+/// Primitives assembled from the vertices of a `CustomProgram`
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub enum Primitive {
+  /// One point per vertex, of size `gl_PointSize`
+  Points = 0,
+  /// One line per pair of vertices
+  Lines = 1,
+  /// One triangle per triplet of vertices
+  #[default]
+  Triangles = 2,
+}
+
+impl Primitive {
+  /// WebGL mode drawing the primitive
+  fn mode(&self) -> u32 {
+    match self {
+      Primitive::Points => web_sys::WebGl2RenderingContext::POINTS,
+      Primitive::Lines => web_sys::WebGl2RenderingContext::LINES,
+      Primitive::Triangles => web_sys::WebGl2RenderingContext::TRIANGLES,
+    }
+  }
+}
+
+/// Shader program supplied by the user, drawing the geometry of `CustomElement`s - or of the `Info::Custom`
+/// emitted by a `Drawable` of a downstream crate. The sources, in GLSL ES 3.00 without version directive, may include
+/// the chunks of the shader library and have access to:
+/// - `a_position`: `vec3` position of the vertex,
+/// - the attributes declared, of the declared number of components,
+/// - `uWorldview`, `uWorldviewProjection` and `uWorldviewInverseTranspose`: camera matrices,
+/// - `u_color`: surface colour of the material, line colour for lines,
+/// - `u_resolution`: size of the viewport in pixels,
+/// - the uniforms of the clipping planes, lighting and shadows used by the `clipping` and `lighting` chunks, the
+///   vertex shader of a program including `lighting` writing `vShadowCoord` as `uLightMatrix` times the world position,
+/// - the uniforms declared, set by each element.
+///
+/// The program is compiled with `PICKING` defined for picking and highlighting, the fragment shader then writing
+/// `u_pickId` as `fragColor`. The program name and sources identify the compiled program in the renderer
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[derive(Clone, Debug, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct CustomProgram {
+  name: String,
+  vertex_shader_source: String,
+  fragment_shader_source: String,
+  #[serde(default)]
+  primitive: Primitive,
+  #[serde(default)]
+  attributes: Vec<(String, i32)>,
+  #[serde(default)]
+  uniforms: Vec<String>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+impl CustomProgram {
+  /// Create the program named `name` from its vertex and fragment shader sources. Exposed to JavaScript
+  pub fn new(name: &str, vertex_shader_source: &str, fragment_shader_source: &str) -> CustomProgram {
+    CustomProgram { name: name.to_string(), vertex_shader_source: vertex_shader_source.to_string(),
+      fragment_shader_source: fragment_shader_source.to_string(), primitive: Primitive::default(),
+      attributes: Vec::new(), uniforms: Vec::new(), }
+  }
+
+  /// Declare the attribute `name` of `size` components per vertex, replacing any previous declaration.
+  /// Exposed to JavaScript
+  pub fn with_attribute(mut self, name: &str, size: i32) -> Result<CustomProgram, JsError> {
+    if !(1..=4).contains(&size) { return Err(Error::from(format!("Attribute {} requires 1 to 4 components", name)).into()); }
+    if name == "a_position" { return Err(Error::from("Attribute a_position is provided by the renderer").into()); }
+    self.attributes.retain(|(n, _)| n != name);
+    self.attributes.push((name.to_string(), size));
+    Ok(self)
+  }
+
+  /// Declare the uniform `name`, to be set by each element drawn. Exposed to JavaScript
+  pub fn with_uniform(mut self, name: &str) -> CustomProgram {
+    if !self.uniforms.iter().any(|n| n == name) { self.uniforms.push(name.to_string()); }
+    self
+  }
+
+  /// Replace the primitive drawn. Exposed to JavaScript
+  pub fn with_primitive(mut self, primitive: Primitive) -> CustomProgram {
+    self.primitive = primitive; self
+  }
+
+  pub fn clone(&self) -> Self {
+    Clone::clone(self)
+  }
+
+  /// Convert the program to json
+  pub fn to_json(&self) -> Result<String, JsError> {
+    Ok(serde_json::to_string(&self).map_err(|e| format!("{e}"))?)
+  }
+
+  /// Create a program from json
+  pub fn from_json(json: &str) -> Result<CustomProgram, JsError> {
+    Ok(serde_json::from_str(json).map_err(|e| format!("{e}"))?)
+  }
+}
+
+impl CustomProgram {
+  /// Name of the program
+  pub fn name(&self) -> &str { &self.name }
+
+  /// Primitive drawn
+  pub fn primitive(&self) -> Primitive { self.primitive }
+
+  /// Attributes declared, with their number of components
+  pub fn attributes(&self) -> &[(String, i32)] { &self.attributes }
+
+  /// Uniforms declared
+  pub fn uniforms(&self) -> &[String] { &self.uniforms }
+
+  /// Check that the vertex data and uniform values supplied match the declarations
+  pub fn validate(&self, n_vertices: usize, attributes: &[(String, Vec<f32>)], uniforms: &[(String, Uniform)]) -> Result<(), Error> {
+    for (name, size) in self.attributes.iter() {
+      let (_, values) = attributes.iter().find(|(n, _)| n == name)
+        .ok_or(format!("Attribute {} of program {} is not supplied", name, self.name))?;
+      if values.len() != *size as usize * n_vertices {
+        return Err(format!("Attribute {} of program {} requires {} values per vertex", name, self.name, size).into());
+      }
+    }
+    if let Some(name) = self.uniforms.iter().find(|name| !uniforms.iter().any(|(n, _)| n == *name)) {
+      return Err(format!("Uniform {} of program {} is not supplied", name, self.name).into());
+    }
+    Ok(())
+  }
+
+  /// Compiled program, cached by name and by a hash of the sources so that programs of the same name with different
+  /// sources are compiled apart. The picking variant is compiled with `PICKING` defined
+  pub(super) fn webgl_program(&self,
+    context: &web_sys::WebGl2RenderingContext,
+    cache: &ProgramCache,
+    picking: bool,
+    ) -> Result<web_sys::WebGlProgram, Error> {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    self.vertex_shader_source.hash(&mut hasher);
+    self.fragment_shader_source.hash(&mut hasher);
+    let name = format!("custom:{}:{:x}", self.name, hasher.finish());
+    let builder = WebGlProgramBuilder::new()
+      .context(context)
+      .vertex_shader_source(&self.vertex_shader_source)
+      .fragment_shader_source(&self.fragment_shader_source)
+      .cache(cache, &name);
+    if picking { builder.define("PICKING").build() } else { builder.build() }
+  }
+
+  /// Set the camera matrices, flattened in column order, and the viewport `resolution` on the program in use
+  pub(super) fn set_camera_uniforms(&self,
+    context: &web_sys::WebGl2RenderingContext,
+    program: &web_sys::WebGlProgram,
+    view: &[f32],
+    matrix: &[f32],
+    transpose_inverse_view: &[f32],
+    resolution: (i32, i32),
+    ) {
+    let u_worldview = context.get_uniform_location(program, "uWorldview");
+    context.uniform_matrix4fv_with_f32_array(u_worldview.as_ref(), false, view);
+    let u_worldview_projection = context.get_uniform_location(program, "uWorldviewProjection");
+    context.uniform_matrix4fv_with_f32_array(u_worldview_projection.as_ref(), false, matrix);
+    let u_worldview_inverse_transpose = context.get_uniform_location(program, "uWorldviewInverseTranspose");
+    context.uniform_matrix4fv_with_f32_array(u_worldview_inverse_transpose.as_ref(), false, transpose_inverse_view);
+    let u_resolution = context.get_uniform_location(program, "u_resolution");
+    context.uniform2f(u_resolution.as_ref(), resolution.0 as f32, resolution.1 as f32);
+  }
+
+  /// Bind `positions` and the declared `attributes`, set the declared `uniforms` and draw with the program in use.
+  /// Attributes unused by the program are skipped
+  #[allow(clippy::too_many_arguments)]
+  pub(super) fn draw(&self,
+    context: &web_sys::WebGl2RenderingContext,
+    counters: &statistics::Counters,
+    program: &web_sys::WebGlProgram,
+    positions: &[f32],
+    attributes: &[(String, Vec<f32>)],
+    indices: Option<&Indices>,
+    uniforms: &[(String, Uniform)],
+    ) -> Result<(), Error> {
+    let n_vertices = positions.len() / 3;
+    self.validate(n_vertices, attributes, uniforms)?;
+    utils::bind(context, counters, program, "a_position", positions)?;
+    for (name, size) in self.attributes.iter() {
+      if context.get_attrib_location(program, name) < 0 { continue; }
+      let (_, values) = attributes.iter().find(|(n, _)| n == name).ok_or("Unable to retrieve attribute...")?;
+      utils::bind_with_size(context, counters, program, name, values, *size)?;
+    }
+    for (name, value) in uniforms.iter().filter(|(n, _)| self.uniforms.contains(n)) {
+      value.set(context, context.get_uniform_location(program, name).as_ref());
+    }
+    utils::draw(context, counters, self.primitive.mode(), n_vertices, indices)
+  }
+}
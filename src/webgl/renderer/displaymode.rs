@@ -0,0 +1,34 @@
+use super::*;
+
+/// How triangles are displayed
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub enum DisplayMode {
+  /// Lit surfaces
+  #[default]
+  Shaded,
+  /// Edges of every triangle, without surfaces
+  Wireframe,
+  /// Lit surfaces with feature edges overlaid
+  ShadedWithEdges,
+  /// Feature edges, with surfaces only hiding the edges behind them
+  HiddenLine,
+}
+
+impl DisplayMode {
+  /// Whether surfaces are drawn with colour
+  pub fn shows_surfaces(&self) -> bool { matches!(self, DisplayMode::Shaded | DisplayMode::ShadedWithEdges) }
+
+  /// Compute the edges drawn in this mode, if any
+  pub fn edges(&self, 
+    vertices: &[(nalgebra::Point3<f32>, nalgebra::Point3<f32>, nalgebra::Point3<f32>)],
+    crease_angle: f32,
+    ) -> Option<Vec<(nalgebra::Point3<f32>, nalgebra::Point3<f32>)>> {
+    match self {
+      DisplayMode::Shaded => None,
+      DisplayMode::Wireframe => Some(edges::triangle_edges(vertices)),
+      DisplayMode::ShadedWithEdges | DisplayMode::HiddenLine => Some(edges::feature_edges(vertices, crease_angle)),
+    }
+  }
+}
@@ -0,0 +1,140 @@
+// Extraction of edges from triangle soups. Vertices are welded using their position
+// quantised relative to the extent of the geometry.
+
+type Point = nalgebra::Point3<f32>;
+type Key = (i64, i64, i64);
+type Normals = Vec<nalgebra::Vector3<f32>>;
+type Edges = std::rc::Rc<Vec<(Point, Point)>>;
+
+/// Relative tolerance used to weld vertices
+const WELD_TOLERANCE: f32 = 1e-6;
+
+/// Return the factor applied to coordinates before rounding them to a vertex key
+fn weld_scale<'a>(points: impl Iterator<Item = &'a Point>) -> f32 {
+  let (min, max) = points.fold((Point::from([f32::MAX; 3]), Point::from([f32::MIN; 3])), 
+    |(min, max), p| (min.inf(p), max.sup(p)));
+  let extent = (max - min).amax();
+  if extent.is_finite() && extent > 0.0 { 1.0 / (extent * WELD_TOLERANCE) } else { 1.0 }
+}
+
+fn key(p: &Point, scale: f32) -> Key {
+  ((p.x * scale).round() as i64, (p.y * scale).round() as i64, (p.z * scale).round() as i64)
+}
+
+fn edge_key(a: Key, b: Key) -> (Key, Key) { if a <= b { (a, b) } else { (b, a) } }
+
+/// Unique edges of all triangles
+pub fn triangle_edges(vertices: &[(Point, Point, Point)]) -> Vec<(Point, Point)> {
+  let scale = weld_scale(vertices.iter().flat_map(|(p1, p2, p3)| [p1, p2, p3]));
+  let mut seen = std::collections::HashSet::new();
+  vertices.iter()
+  .flat_map(|(p1, p2, p3)| [(*p1, *p2), (*p2, *p3), (*p3, *p1)])
+  .filter(|(a, b)| seen.insert(edge_key(key(a, scale), key(b, scale))))
+  .collect()
+}
+
+/// Feature edges: boundary and non-manifold edges, and edges between faces whose normals differ 
+/// by more than `crease_angle` (in radians)
+pub fn feature_edges(vertices: &[(Point, Point, Point)], crease_angle: f32) -> Vec<(Point, Point)> {
+  let scale = weld_scale(vertices.iter().flat_map(|(p1, p2, p3)| [p1, p2, p3]));
+  let cos_crease = crease_angle.cos();
+
+  let mut index = std::collections::HashMap::new();
+  let mut edges: Vec<((Point, Point), Normals)> = Vec::new();
+  for (p1, p2, p3) in vertices.iter() {
+    let normal = (p2 - p1).cross(&(p3 - p1));
+    let normal = match normal.try_normalize(f32::EPSILON) { Some(n) => n, None => continue };
+    for (a, b) in [(p1, p2), (p2, p3), (p3, p1)] {
+      let i = *index.entry(edge_key(key(a, scale), key(b, scale)))
+        .or_insert_with(|| { edges.push(((*a, *b), Vec::new())); edges.len() - 1 });
+      edges[i].1.push(normal);
+    }
+  }
+
+  edges.into_iter()
+  .filter(|(_, normals)| normals.len() != 2 || normals[0].dot(&normals[1]) < cos_crease)
+  .map(|(edge, _)| edge)
+  .collect()
+}
+
+/// Edges of the elements drawn, keyed by element uid and by a hash of their geometry, display mode and crease angle
+/// so that they are extracted once per geometry. The edges not drawn since the previous frame are released
+#[derive(Default)]
+pub struct EdgeCache {
+  edges: std::collections::HashMap<(String, u64), Edges>,
+  used:  std::collections::HashSet<(String, u64)>,
+}
+
+impl EdgeCache {
+  /// Edges of the element `uid` drawn from `info` in `mode`, if any
+  pub fn edges(&mut self, uid: &str, info: &super::Info, mode: super::DisplayMode, crease_angle: f32) -> Option<Edges> {
+    if mode == super::DisplayMode::Shaded { return None; }
+    let key = (uid.to_string(), fingerprint(info, mode, crease_angle));
+    if !self.edges.contains_key(&key) {
+      self.edges.insert(key.clone(), std::rc::Rc::new(mode.edges(&info.triangles(), crease_angle)?));
+    }
+    let edges = self.edges.get(&key).cloned();
+    self.used.insert(key);
+    edges
+  }
+
+  /// Release the edges not drawn since the previous call
+  pub fn release_unused(&mut self) {
+    let used = std::mem::take(&mut self.used);
+    self.edges.retain(|key, _| used.contains(key));
+  }
+}
+
+/// Hash of the triangles of `info` and of the parameters of the extraction
+fn fingerprint(info: &super::Info, mode: super::DisplayMode, crease_angle: f32) -> u64 {
+  use std::hash::{Hash, Hasher};
+  let mut hasher = std::collections::hash_map::DefaultHasher::new();
+  std::mem::discriminant(&mode).hash(&mut hasher);
+  crease_angle.to_bits().hash(&mut hasher);
+  let mut hash_point = |p: &Point| { p.x.to_bits().hash(&mut hasher); p.y.to_bits().hash(&mut hasher); p.z.to_bits().hash(&mut hasher); };
+  match info {
+    // Merged geometry is identified by its key
+    super::Info::Batched { key, .. } => key.uid().hash(&mut hasher),
+    super::Info::TrianglesWithNormals { vertices, .. } => 
+      vertices.iter().flat_map(|(p1, p2, p3)| [p1, p2, p3]).for_each(&mut hash_point),
+    super::Info::IndexedTrianglesWithNormals { positions, indices, .. } | super::Info::TexturedTriangles { positions, indices, .. }
+    | super::Info::ScalarTriangles { positions, indices, .. } => {
+      positions.iter().for_each(&mut hash_point);
+      indices.iter().for_each(|i| i.hash(&mut hasher));
+    },
+    super::Info::InstancedTrianglesWithNormals { positions, indices, instances, .. } => {
+      positions.iter().for_each(&mut hash_point);
+      indices.iter().for_each(|i| i.hash(&mut hasher));
+      instances.matrices.iter().flat_map(|m| m.iter()).for_each(|v| v.to_bits().hash(&mut hasher));
+    },
+    _ => (),
+  }
+  hasher.finish()
+}
+
+#[cfg(all(test, feature = "wasm"))]
+mod tests {
+  use super::*;
+  use crate::webgl::renderer::DisplayMode;
+
+  /// Triangles of the unit hexahedron, two per face
+  fn hexahedron_triangles() -> Vec<(Point, Point, Point)> {
+    let corner = |i: usize| Point::new((i & 1) as f32, (i >> 1 & 1) as f32, (i >> 2 & 1) as f32);
+    let faces = [[0, 2, 3, 1], [4, 5, 7, 6], [0, 1, 5, 4], [2, 6, 7, 3], [0, 4, 6, 2], [1, 3, 7, 5]];
+    faces.iter()
+    .flat_map(|[a, b, c, d]| [(corner(*a), corner(*b), corner(*c)), (corner(*a), corner(*c), corner(*d))])
+    .collect()
+  }
+
+  #[test]
+  fn hexahedron_has_12_feature_edges() {
+    let edges = DisplayMode::ShadedWithEdges.edges(&hexahedron_triangles(), 30f32.to_radians());
+    assert_eq!(edges.map(|e| e.len()), Some(12));
+  }
+
+  #[test]
+  fn hexahedron_wireframe_has_18_triangle_edges() {
+    let edges = DisplayMode::Wireframe.edges(&hexahedron_triangles(), 30f32.to_radians());
+    assert_eq!(edges.map(|e| e.len()), Some(18));
+  }
+}
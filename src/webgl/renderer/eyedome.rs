@@ -0,0 +1,181 @@
+use super::*;
+
+/// Colour and depth textures the point clouds with eye-dome lighting are drawn into, before being shaded onto the frame
+pub struct EyeDomeTarget {
+  width:       i32,
+  height:      i32,
+  framebuffer: web_sys::WebGlFramebuffer,
+  color:       web_sys::WebGlTexture,
+  depth:       web_sys::WebGlTexture,
+}
+
+impl EyeDomeTarget {
+  /// Create the target
+  pub fn new(context: &web_sys::WebGl2RenderingContext, width: i32, height: i32) -> Result<EyeDomeTarget, Error> {
+    let color = Self::texture(context, width, height, web_sys::WebGl2RenderingContext::RGBA8, web_sys::WebGl2RenderingContext::RGBA,
+      web_sys::WebGl2RenderingContext::UNSIGNED_BYTE)?;
+    let depth = Self::texture(context, width, height, web_sys::WebGl2RenderingContext::DEPTH_COMPONENT24, web_sys::WebGl2RenderingContext::DEPTH_COMPONENT,
+      web_sys::WebGl2RenderingContext::UNSIGNED_INT)?;
+
+    let framebuffer = context.create_framebuffer().ok_or("Unable to create frame buffer")?;
+    context.bind_framebuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER, Some(&framebuffer));
+    context.framebuffer_texture_2d(web_sys::WebGl2RenderingContext::FRAMEBUFFER,
+      web_sys::WebGl2RenderingContext::COLOR_ATTACHMENT0,
+      web_sys::WebGl2RenderingContext::TEXTURE_2D,
+      Some(&color),
+      0,       // level
+    );
+    context.framebuffer_texture_2d(web_sys::WebGl2RenderingContext::FRAMEBUFFER,
+      web_sys::WebGl2RenderingContext::DEPTH_ATTACHMENT,
+      web_sys::WebGl2RenderingContext::TEXTURE_2D,
+      Some(&depth),
+      0,       // level
+    );
+    let status = context.check_framebuffer_status(web_sys::WebGl2RenderingContext::FRAMEBUFFER);
+    context.bind_framebuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER, None);
+    if status != web_sys::WebGl2RenderingContext::FRAMEBUFFER_COMPLETE {
+      return Err(format!("Eye-dome lighting frame buffer incomplete: {}", status).into());
+    }
+    Ok(EyeDomeTarget { width, height, framebuffer, color, depth })
+  }
+
+  /// Whether the target matches the size provided
+  pub fn has_size(&self, width: i32, height: i32) -> bool { self.width == width && self.height == height }
+
+  /// Bind the framebuffer and clear it
+  pub fn bind(&self, context: &web_sys::WebGl2RenderingContext) {
+    context.bind_framebuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER, Some(&self.framebuffer));
+    context.clear_color(0.0, 0.0, 0.0, 0.0);
+    context.clear(web_sys::WebGl2RenderingContext::COLOR_BUFFER_BIT | web_sys::WebGl2RenderingContext::DEPTH_BUFFER_BIT);
+  }
+
+  fn texture(context: &web_sys::WebGl2RenderingContext, width: i32, height: i32, internal_format: u32, format: u32, type_: u32) -> Result<web_sys::WebGlTexture, Error> {
+    let texture = context.create_texture().ok_or("Unable to create texture")?;
+    context.bind_texture(web_sys::WebGl2RenderingContext::TEXTURE_2D, Some(&texture));
+    context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_MIN_FILTER, web_sys::WebGl2RenderingContext::NEAREST.try_into()?);
+    context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_MAG_FILTER, web_sys::WebGl2RenderingContext::NEAREST.try_into()?);
+    context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_WRAP_S, web_sys::WebGl2RenderingContext::CLAMP_TO_EDGE.try_into()?);
+    context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_WRAP_T, web_sys::WebGl2RenderingContext::CLAMP_TO_EDGE.try_into()?);
+    context.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+      web_sys::WebGl2RenderingContext::TEXTURE_2D, 0, internal_format.try_into()?, width, height, 0, format, type_, None,
+    )?;
+    Ok(texture)
+  }
+}
+
+/// Full screen pass shading the points of an `EyeDomeTarget` by the log-depth difference with their neighbours on
+/// screen, and drawing them onto the current framebuffer with their depth
+pub struct ProgramEyeDome { }
+
+impl ProgramEyeDome {
+  /// Distance, in pixels, of the neighbours sampled
+  const RADIUS: f32 = 1.5;
+
+  pub fn webgl_program(
+    context: &web_sys::WebGl2RenderingContext,
+    ) -> Result<web_sys::WebGlProgram, Error> {
+    WebGlProgramBuilder::new()
+      .context(context)
+      .vertex_shader_source(Self::VERTEX_SHADER_SOURCE)
+      .fragment_shader_source(Self::FRAGMENT_SHADER_SOURCE)
+      .build()
+  }
+
+  /// Draw the points of `target` shaded with `strength`, their depth being linearised by the inverse of the
+  /// `projection` matrix. Depth test is enabled on return.
+  pub fn draw(
+    context: &web_sys::WebGl2RenderingContext,
+    counters: &statistics::Counters,
+    program: &std::rc::Rc<std::cell::RefCell<Option<web_sys::WebGlProgram>>>,
+    target: &EyeDomeTarget,
+    strength: f32,
+    projection: &nalgebra::Matrix4<f32>,
+    ) -> Result<(), Error> {
+    if program.borrow().is_none() {
+      *program.borrow_mut() = Some(Self::webgl_program(context)?);
+    }
+    let program = program.borrow();
+    let program = program.as_ref().ok_or("Unable to retrieve eye-dome lighting program...")?;
+    counters.use_program(context, program);
+
+    context.active_texture(web_sys::WebGl2RenderingContext::TEXTURE0);
+    context.bind_texture(web_sys::WebGl2RenderingContext::TEXTURE_2D, Some(&target.color));
+    context.uniform1i(context.get_uniform_location(program, "u_color").as_ref(), 0);
+    context.active_texture(web_sys::WebGl2RenderingContext::TEXTURE1);
+    context.bind_texture(web_sys::WebGl2RenderingContext::TEXTURE_2D, Some(&target.depth));
+    context.uniform1i(context.get_uniform_location(program, "u_depth").as_ref(), 1);
+    context.active_texture(web_sys::WebGl2RenderingContext::TEXTURE0);
+    let inverse = projection.try_inverse().ok_or("Unable to inverse projection matrix")?;
+    context.uniform_matrix4fv_with_f32_array(context.get_uniform_location(program, "u_inverseProjection").as_ref(), false, inverse.as_slice());
+    context.uniform2f(context.get_uniform_location(program, "u_offset").as_ref(), Self::RADIUS / target.width as f32, Self::RADIUS / target.height as f32);
+    context.uniform1f(context.get_uniform_location(program, "u_strength").as_ref(), strength);
+
+    let positions: Vec<f32> = vec!(-1.0, -1.0,   1.0, -1.0,   1.0, 1.0,   -1.0, -1.0,   1.0, 1.0,   -1.0, 1.0);
+    utils::bind_with_size(context, counters, program, "a_position", &positions, 2)?;
+    let cull_face = context.is_enabled(web_sys::WebGl2RenderingContext::CULL_FACE);
+    context.disable(web_sys::WebGl2RenderingContext::CULL_FACE);
+    context.enable(web_sys::WebGl2RenderingContext::DEPTH_TEST);
+    context.disable(web_sys::WebGl2RenderingContext::BLEND);
+    let result = utils::draw(context, counters, web_sys::WebGl2RenderingContext::TRIANGLES, 6, None);
+    if cull_face { context.enable(web_sys::WebGl2RenderingContext::CULL_FACE); }
+    // Unbind the depth texture so that it is not sampled while attached in the next frame
+    context.active_texture(web_sys::WebGl2RenderingContext::TEXTURE1);
+    context.bind_texture(web_sys::WebGl2RenderingContext::TEXTURE_2D, None);
+    context.active_texture(web_sys::WebGl2RenderingContext::TEXTURE0);
+    result
+  }
+
+  const VERTEX_SHADER_SOURCE: &str = r#"
+     in vec2 a_position;
+
+     out vec2 v_uv;
+
+     void main()
+     {
+        v_uv = 0.5*a_position + 0.5;
+        gl_Position = vec4(a_position, 0.0, 1.0);
+     }
+    "#;
+
+  const FRAGMENT_SHADER_SOURCE: &str = r#"
+     precision highp float;
+
+     uniform sampler2D u_color;
+     uniform sampler2D u_depth;
+     uniform mat4 u_inverseProjection;
+     // Distance to the neighbours, in texture coordinates
+     uniform vec2 u_offset;
+     uniform float u_strength;
+
+     in vec2 v_uv;
+
+     out vec4 fragColor;
+
+     // Log2 of the distance to the eye plane of the depth buffer value `depth` at `uv`
+     float logDepth(vec2 uv, float depth)
+     {
+       vec4 position = u_inverseProjection*vec4(2.0*uv - 1.0, 2.0*depth - 1.0, 1.0);
+       return log2(max(abs(position.z/position.w), 1e-6));
+     }
+
+     void main()
+     {
+       float depth = texture(u_depth, v_uv).r;
+       if (depth >= 1.0) { discard; }
+       float center = logDepth(v_uv, depth);
+
+       // Neighbours in front of the point darken it; empty neighbours are ignored
+       float response = 0.0;
+       for (int i = 0; i < 8; i++) {
+         float angle = 0.785398*float(i);
+         vec2 uv = v_uv + u_offset*vec2(cos(angle), sin(angle));
+         float neighbour = texture(u_depth, uv).r;
+         if (neighbour < 1.0) { response += max(0.0, center - logDepth(uv, neighbour)); }
+       }
+       float shade = exp(-300.0*u_strength*response/8.0);
+
+       fragColor = vec4(shade*texture(u_color, v_uv).rgb, 1.0);
+       gl_FragDepth = depth;
+     }
+    "#;
+}
@@ -0,0 +1,117 @@
+use super::*;
+
+use wasm_bindgen::JsCast;
+
+/// Location and metrics of a glyph in the atlas, in atlas pixels
+#[derive(Clone, Copy, Debug)]
+pub struct Glyph {
+  pub x:       f32,
+  pub y:       f32,
+  pub width:   f32,
+  pub advance: f32,
+}
+
+/// Texture of glyphs rasterized on demand with a 2D canvas, white on a transparent background
+pub struct GlyphAtlas {
+  canvas:  web_sys::HtmlCanvasElement,
+  context: web_sys::CanvasRenderingContext2d,
+  texture: web_sys::WebGlTexture,
+  glyphs:  std::collections::HashMap<char, Glyph>,
+  cursor:  (f32, f32),
+  dirty:   bool,
+}
+
+impl GlyphAtlas {
+  /// Width and height of the atlas, in pixels
+  pub const SIZE: u32 = 1024;
+  /// Font size the glyphs are rasterized at, in pixels
+  pub const FONT_SIZE: f32 = 48.0;
+  /// Height of a row of glyphs, in pixels
+  pub const CELL_HEIGHT: f32 = 60.0;
+  /// Empty space around each glyph, in pixels, so that neighbours do not bleed when sampled
+  pub const PADDING: f32 = 2.0;
+
+  /// Create an empty atlas
+  pub fn new(context: &web_sys::WebGl2RenderingContext) -> Result<GlyphAtlas, Error> {
+    let document = web_sys::window().and_then(|w| w.document()).ok_or("Unable to retrieve document")?;
+    let canvas = document.create_element("canvas")?
+      .dyn_into::<web_sys::HtmlCanvasElement>().map_err(|_| "Unable to create canvas")?;
+    canvas.set_width(Self::SIZE);
+    canvas.set_height(Self::SIZE);
+    let context2d = canvas.get_context("2d")?.ok_or("Unable to retrieve 2D context")?
+      .dyn_into::<web_sys::CanvasRenderingContext2d>().map_err(|_| "Unable to retrieve 2D context")?;
+
+    let texture = context.create_texture().ok_or("Unable to create texture")?;
+    context.bind_texture(web_sys::WebGl2RenderingContext::TEXTURE_2D, Some(&texture));
+    context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_MIN_FILTER, web_sys::WebGl2RenderingContext::LINEAR.try_into()?);
+    context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_MAG_FILTER, web_sys::WebGl2RenderingContext::LINEAR.try_into()?);
+    context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_WRAP_S, web_sys::WebGl2RenderingContext::CLAMP_TO_EDGE.try_into()?);
+    context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_WRAP_T, web_sys::WebGl2RenderingContext::CLAMP_TO_EDGE.try_into()?);
+
+    let mut atlas = GlyphAtlas { canvas, context: context2d, texture, glyphs: std::collections::HashMap::new(), cursor: (0.0, 0.0), dirty: true };
+    atlas.clear();
+    Ok(atlas)
+  }
+
+  /// Remove all glyphs
+  fn clear(&mut self) {
+    self.context.clear_rect(0.0, 0.0, Self::SIZE as f64, Self::SIZE as f64);
+    self.context.set_font(&format!("{}px sans-serif", Self::FONT_SIZE));
+    self.context.set_text_baseline("middle");
+    self.context.set_fill_style_str("#ffffff");
+    self.glyphs.clear();
+    self.cursor = (0.0, 0.0);
+    self.dirty = true;
+  }
+
+  /// Rasterize the glyphs of `text` missing from the atlas. The atlas is cleared when full, so the glyphs
+  /// retrieved before a call may no longer be valid after it
+  pub fn prepare(&mut self, text: &str) -> Result<(), Error> {
+    if self.insert(text).is_err() {
+      self.clear();
+      self.insert(text)?;
+    }
+    Ok(())
+  }
+
+  /// Rasterize the glyphs of `text` missing from the atlas, failing when the atlas is full
+  fn insert(&mut self, text: &str) -> Result<(), Error> {
+    let size = Self::SIZE as f32;
+    for c in text.chars() {
+      if c.is_control() || self.glyphs.contains_key(&c) { continue; }
+      let advance = self.context.measure_text(&c.to_string())?.width() as f32;
+      let width = advance + 2.0*Self::PADDING;
+      if self.cursor.0 + width > size {
+        self.cursor = (0.0, self.cursor.1 + Self::CELL_HEIGHT);
+      }
+      if self.cursor.1 + Self::CELL_HEIGHT > size { return Err("Glyph atlas is full".into()); }
+      let (x, y) = self.cursor;
+      self.context.fill_text(&c.to_string(), (x + Self::PADDING) as f64, (y + 0.5*Self::CELL_HEIGHT) as f64)?;
+      self.glyphs.insert(c, Glyph { x, y, width, advance });
+      self.cursor.0 += width.ceil();
+      self.dirty = true;
+    }
+    Ok(())
+  }
+
+  /// Retrieve a glyph rasterized by `prepare`
+  pub fn glyph(&self, c: char) -> Option<&Glyph> { self.glyphs.get(&c) }
+
+  /// Width, in pixels, of the line `text` drawn at `font_size`, from the glyphs rasterized by `prepare`
+  pub fn text_width(&self, text: &str, font_size: f32) -> f32 {
+    text.chars().filter_map(|c| self.glyph(c)).map(|g| g.advance).sum::<f32>() * font_size / Self::FONT_SIZE
+  }
+
+  /// Bind the atlas texture to the active texture unit, uploading the glyphs added since the last call
+  pub fn bind(&mut self, context: &web_sys::WebGl2RenderingContext) -> Result<(), Error> {
+    context.bind_texture(web_sys::WebGl2RenderingContext::TEXTURE_2D, Some(&self.texture));
+    if self.dirty {
+      context.tex_image_2d_with_u32_and_u32_and_html_canvas_element(
+        web_sys::WebGl2RenderingContext::TEXTURE_2D, 0, web_sys::WebGl2RenderingContext::RGBA.try_into()?,
+        web_sys::WebGl2RenderingContext::RGBA, web_sys::WebGl2RenderingContext::UNSIGNED_BYTE, &self.canvas,
+      )?;
+      self.dirty = false;
+    }
+    Ok(())
+  }
+}
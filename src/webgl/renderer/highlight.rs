@@ -0,0 +1,206 @@
+use super::*;
+
+/// Styles of the hovered and selected elements: their colour is blended towards the highlight colour, and a
+/// screen-space outline of the highlight colour is drawn around them, visible through other geometry
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[derive(Clone, Debug, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Highlight {
+  hovered_color:  Color,
+  selected_color: Color,
+  tint:           f32,
+  outline_width:  u32,
+}
+
+impl Default for Highlight {
+  fn default() -> Highlight {
+    Highlight {
+      hovered_color:  Color::rgb(0.35, 0.75, 1.0),
+      selected_color: Color::rgb(1.0, 0.6, 0.1),
+      tint:           0.35,
+      outline_width:  2,
+    }
+  }
+}
+
+impl Highlight {
+  /// Maximum outline width, in pixels
+  pub const MAX_OUTLINE_WIDTH: u32 = 4;
+
+  /// Colour of the hovered element
+  pub fn hovered_color(&self) -> Color { self.hovered_color }
+
+  /// Colour of the selected elements
+  pub fn selected_color(&self) -> Color { self.selected_color }
+
+  /// Amount, in `[0, 1]`, by which the colour of highlighted elements is blended towards the highlight colour
+  pub fn tint(&self) -> f32 { self.tint }
+
+  /// Outline width, in pixels
+  pub fn outline_width(&self) -> u32 { self.outline_width }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+impl Highlight {
+  /// Create the default highlight styles: light blue when hovered, orange when selected, 2 pixels outline
+  pub fn new() -> Highlight { Highlight::default() }
+
+  /// Set the colour of the hovered element
+  pub fn with_hovered_color(mut self, color: Color) -> Highlight { self.hovered_color = color; self }
+
+  /// Set the colour of the selected elements
+  pub fn with_selected_color(mut self, color: Color) -> Highlight { self.selected_color = color; self }
+
+  /// Set the amount, in `[0, 1]`, by which the colour of highlighted elements is blended towards the highlight colour
+  pub fn with_tint(mut self, tint: f32) -> Highlight { self.tint = tint.clamp(0.0, 1.0); self }
+
+  /// Set the outline width, in pixels. 0 disables the outline
+  pub fn with_outline_width(mut self, outline_width: u32) -> Result<Highlight, JsError> {
+    if outline_width > Self::MAX_OUTLINE_WIDTH { return Err(format!("A maximum outline width of {} is supported", Self::MAX_OUTLINE_WIDTH).into()); }
+    self.outline_width = outline_width; Ok(self)
+  }
+
+  /// Expose clone functionality to JavaScript
+  pub fn clone(&self) -> Highlight { Clone::clone(self) }
+
+  /// Convert highlight styles to json
+  pub fn to_json(&self) -> Result<String, JsError> {
+    Ok(serde_json::to_string(&self).map_err(|e| format!("{e}"))?)
+  }
+
+  /// Create highlight styles from json
+  pub fn from_json(json: &str) -> Result<Highlight, JsError> {
+    Ok(serde_json::from_str(json).map_err(|e| format!("{e}"))?)
+  }
+}
+
+/// Geometry split according to the highlight colour of its elements
+pub struct Split {
+  /// Geometry to draw, with the material or instance colours of highlighted elements tinted
+  pub parts:    Vec<OwnedInfo>,
+  /// Geometry of the highlighted elements, with their highlight colour
+  pub outlines: Vec<(OwnedInfo, Color)>,
+}
+
+/// Split `info` according to the highlight colour given by `color_of` for each uid. Returns `None` if no element is highlighted
+pub fn split<F>(info: &Info, tint: f32, color_of: F) -> Option<Split>
+where F: Fn(&str) -> Option<Color> {
+  match info {
+    Info::Batched { uids, elements, info, .. } => {
+      let colors = uids.iter().map(|u| color_of(u)).collect::<Vec<_>>();
+      if colors.iter().all(Option::is_none) { return None; }
+      let (indices, material, n) = match **info {
+        Info::IndexedLines { indices, material, .. } => (indices, material, 2),
+        Info::IndexedTrianglesWithNormals { indices, material, .. } => (indices, material, 3),
+        _ => return None,
+      };
+
+      // Primitives grouped by the highlight colour of the element they originate from
+      let indices = indices.iter().collect::<Vec<usize>>();
+      let mut groups: Vec<(Option<Color>, Vec<u32>)> = Vec::new();
+      for primitive in indices.chunks(n) {
+        let color = colors[elements[primitive[0]] as usize];
+        let primitive = primitive.iter().map(|i| *i as u32);
+        match groups.iter_mut().find(|(c, _)| *c == color) {
+          Some((_, group)) => group.extend(primitive),
+          None => groups.push((color, primitive.collect())),
+        }
+      }
+
+      let mut split = Split { parts: Vec::new(), outlines: Vec::new() };
+      for (color, group) in groups.into_iter() {
+        let part = match info.to_owned_info() {
+          OwnedInfo::IndexedLines { uid, positions, .. } =>
+            OwnedInfo::IndexedLines { uid, positions, indices: Indices::new(group), material: material.clone() },
+          OwnedInfo::IndexedTrianglesWithNormals { uid, positions, normals, .. } =>
+            OwnedInfo::IndexedTrianglesWithNormals { uid, positions, normals, indices: Indices::new(group), material: material.clone() },
+          _ => return None,
+        };
+        match color {
+          Some(color) => {
+            split.outlines.push((part.clone(), color));
+            split.parts.push(part.with_material(material.tinted(&color, tint)));
+          },
+          None => split.parts.push(part),
+        }
+      }
+      Some(split)
+    },
+
+    Info::InstancedTrianglesWithNormals { uid, positions, normals, indices, instances, material } => {
+      let colors = instances.uids.iter().map(|u| color_of(u)).collect::<Vec<_>>();
+      let color = colors.iter().flatten().next().copied()?;
+      let tinted = Instances {
+        uids:     instances.uids.clone(),
+        matrices: instances.matrices.clone(),
+        colors:   instances.colors.iter().zip(colors.iter())
+          .map(|(c, h)| h.map(|h| c.mix(&h, tint)).unwrap_or(*c))
+          .collect(),
+      };
+      let highlighted = colors.iter().enumerate().filter_map(|(i, c)| c.map(|c| (i, c))).collect::<Vec<_>>();
+      let outline = Instances {
+        uids:     highlighted.iter().map(|(i, _)| instances.uids[*i].clone()).collect(),
+        matrices: highlighted.iter().map(|(i, _)| instances.matrices[*i]).collect(),
+        colors:   highlighted.iter().map(|(_, c)| *c).collect(),
+      };
+      let instanced = |instances| OwnedInfo::InstancedTrianglesWithNormals { uid: (*uid).clone(), positions: (*positions).clone(),
+        normals: (*normals).clone(), indices: (*indices).clone(), instances, material: (*material).clone() };
+      Some(Split { parts: vec![instanced(tinted)], outlines: vec![(instanced(outline), color)] })
+    },
+
+    info => {
+      let color = color_of(info.uid())?;
+      let part = info.to_owned_info();
+      Some(Split { outlines: vec![(part.clone(), color)], parts: vec![part.with_material(info.material().tinted(&color, tint))] })
+    },
+  }
+}
+
+/// Colour texture the highlighted elements are drawn into, in their highlight colour, to find the outline
+pub struct MaskTarget {
+  width:       i32,
+  height:      i32,
+  framebuffer: web_sys::WebGlFramebuffer,
+  color:       web_sys::WebGlTexture,
+}
+
+impl MaskTarget {
+  /// Create the target
+  pub fn new(context: &web_sys::WebGl2RenderingContext, width: i32, height: i32) -> Result<MaskTarget, Error> {
+    let color = context.create_texture().ok_or("Unable to create texture")?;
+    context.bind_texture(web_sys::WebGl2RenderingContext::TEXTURE_2D, Some(&color));
+    context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_MIN_FILTER, web_sys::WebGl2RenderingContext::NEAREST.try_into()?);
+    context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_MAG_FILTER, web_sys::WebGl2RenderingContext::NEAREST.try_into()?);
+    context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_WRAP_S, web_sys::WebGl2RenderingContext::CLAMP_TO_EDGE.try_into()?);
+    context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_WRAP_T, web_sys::WebGl2RenderingContext::CLAMP_TO_EDGE.try_into()?);
+    context.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+      web_sys::WebGl2RenderingContext::TEXTURE_2D, 0, web_sys::WebGl2RenderingContext::RGBA8.try_into()?, width, height, 0,
+      web_sys::WebGl2RenderingContext::RGBA, web_sys::WebGl2RenderingContext::UNSIGNED_BYTE, None,
+    )?;
+
+    let framebuffer = context.create_framebuffer().ok_or("Unable to create frame buffer")?;
+    context.bind_framebuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER, Some(&framebuffer));
+    context.framebuffer_texture_2d(web_sys::WebGl2RenderingContext::FRAMEBUFFER,
+      web_sys::WebGl2RenderingContext::COLOR_ATTACHMENT0,
+      web_sys::WebGl2RenderingContext::TEXTURE_2D,
+      Some(&color),
+      0,       // level
+    );
+    context.bind_framebuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER, None);
+    Ok(MaskTarget { width, height, framebuffer, color })
+  }
+
+  /// Whether the target matches the size provided
+  pub fn has_size(&self, width: i32, height: i32) -> bool { self.width == width && self.height == height }
+
+  /// Bind the framebuffer and clear it
+  pub fn bind(&self, context: &web_sys::WebGl2RenderingContext) {
+    context.bind_framebuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER, Some(&self.framebuffer));
+    context.clear_color(0.0, 0.0, 0.0, 0.0);
+    context.clear(web_sys::WebGl2RenderingContext::COLOR_BUFFER_BIT);
+  }
+
+  /// Colour texture
+  pub fn texture(&self) -> &web_sys::WebGlTexture { &self.color }
+}
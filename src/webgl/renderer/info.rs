@@ -0,0 +1,502 @@
+use super::*;
+
+/// Information on geometry to be drawn by a `RendererTrait`
+pub enum Info<'a> {
+  Lines{
+    uid:      &'a String,
+    vertices: &'a Vec<(nalgebra::Point3<f32>, nalgebra::Point3<f32>)>,
+    material: &'a Material,
+  },
+  TrianglesWithNormals {
+    uid:      &'a String,
+    vertices: &'a Vec<(nalgebra::Point3<f32>, nalgebra::Point3<f32>, nalgebra::Point3<f32>)>,
+    normals:  &'a Vec<(nalgebra::Vector3<f32>, nalgebra::Vector3<f32>, nalgebra::Vector3<f32>)>,
+    material: &'a Material,
+  },
+  /// Lines between pairs of `positions` referenced by `indices`
+  IndexedLines {
+    uid:       &'a String,
+    positions: &'a Vec<nalgebra::Point3<f32>>,
+    indices:   &'a Indices,
+    material:  &'a Material,
+  },
+  /// Triangles between triplets of `positions` referenced by `indices`, with a normal per position
+  IndexedTrianglesWithNormals {
+    uid:       &'a String,
+    positions: &'a Vec<nalgebra::Point3<f32>>,
+    normals:   &'a Vec<nalgebra::Vector3<f32>>,
+    indices:   &'a Indices,
+    material:  &'a Material,
+  },
+  /// Indexed triangles drawn once per instance, transformed by the instance matrix and drawn with the instance colour.
+  /// Picking resolves the instance uid
+  InstancedTrianglesWithNormals {
+    uid:       &'a String,
+    positions: &'a Vec<nalgebra::Point3<f32>>,
+    normals:   &'a Vec<nalgebra::Vector3<f32>>,
+    indices:   &'a Indices,
+    instances: &'a Instances,
+    material:  &'a Material,
+  },
+  /// Triangles between triplets of `positions` referenced by `indices`, with a normal and texture coordinates per position.
+  /// The texture colour replaces the material colour, the material opacity being applied
+  TexturedTriangles {
+    uid:       &'a String,
+    positions: &'a Vec<nalgebra::Point3<f32>>,
+    normals:   &'a Vec<nalgebra::Vector3<f32>>,
+    uvs:       &'a Vec<nalgebra::Point2<f32>>,
+    indices:   &'a Indices,
+    texture:   &'a Texture,
+    material:  &'a Material,
+  },
+  /// Triangles between triplets of `positions` referenced by `indices`, with a normal and a scalar value per position.
+  /// The colour of the scalar value through the colormap of `style` replaces the material colour, the material opacity being applied
+  ScalarTriangles {
+    uid:       &'a String,
+    positions: &'a Vec<nalgebra::Point3<f32>>,
+    normals:   &'a Vec<nalgebra::Vector3<f32>>,
+    scalars:   &'a Vec<f32>,
+    indices:   &'a Indices,
+    style:     &'a ScalarStyle,
+    material:  &'a Material,
+  },
+  /// Points drawn as sprites of `style`, coloured per point, by their scalars through the colormap of `scalar_style`,
+  /// or with the material surface colour. The GPU buffers of `points` are cached by the renderer. Picking resolves the
+  /// uid and the index of the point
+  Points {
+    uid:          &'a String,
+    points:       &'a std::rc::Rc<pointcloud::PointData>,
+    style:        &'a PointStyle,
+    scalar_style: &'a ScalarStyle,
+    material:     &'a Material,
+  },
+  /// Geometry merged from several elements. `elements` gives, for each position of `info`, the index in `uids`
+  /// of the element it originates from so that picking resolves the original uids. The GPU buffers of the
+  /// geometry are cached by the renderer while `key` is alive
+  Batched {
+    uids:     &'a Vec<String>,
+    elements: &'a Vec<u32>,
+    key:      &'a std::rc::Rc<BatchKey>,
+    info:     Box<Info<'a>>,
+  },
+  /// Text of constant pixel size anchored at a world point, drawn over the scene
+  Label {
+    uid:      &'a String,
+    anchor:   &'a nalgebra::Point3<f32>,
+    text:     &'a String,
+    style:    &'a LabelStyle,
+    material: &'a Material,
+  },
+  /// Geometry drawn by a user supplied `program`: `positions` bound to `a_position`, the values of `attributes` bound
+  /// to the attributes declared by the program and `uniforms` setting its declared uniforms. Drawn opaque, without
+  /// casting shadows. Picking resolves the uid
+  Custom {
+    uid:        &'a String,
+    program:    &'a CustomProgram,
+    positions:  &'a Vec<nalgebra::Point3<f32>>,
+    attributes: &'a Vec<(String, Vec<f32>)>,
+    indices:    Option<&'a Indices>,
+    uniforms:   &'a Vec<(String, Uniform)>,
+    material:   &'a Material,
+  },
+}
+
+/// Per-instance uids, model matrices and colours of instanced geometry
+#[derive(Clone, Debug, Default)]
+pub struct Instances {
+  pub uids:     Vec<String>,
+  pub matrices: Vec<nalgebra::Matrix4<f32>>,
+  pub colors:   Vec<Color>,
+}
+
+impl Instances {
+  /// Number of instances
+  pub fn len(&self) -> usize { self.uids.len() }
+
+  /// Whether there is no instance
+  pub fn is_empty(&self) -> bool { self.uids.is_empty() }
+}
+
+/// Index buffer of indexed geometry. `u16` indices are used when they are sufficient
+#[derive(Clone, Debug)]
+pub enum Indices {
+  U16(Vec<u16>),
+  U32(Vec<u32>),
+}
+
+impl Indices {
+  /// Create indices, using `u16` storage when all indices fit
+  pub fn new(indices: Vec<u32>) -> Indices {
+    if indices.iter().all(|i| *i <= u16::MAX as u32) {
+      Indices::U16(indices.into_iter().map(|i| i as u16).collect())
+    } else {
+      Indices::U32(indices)
+    }
+  }
+
+  /// Number of indices
+  pub fn len(&self) -> usize {
+    match self { Indices::U16(v) => v.len(), Indices::U32(v) => v.len(), }
+  }
+
+  /// Whether there is no index
+  pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+  /// Iterate over the indices
+  pub fn iter(&self) -> Box<dyn Iterator<Item = usize> + '_> {
+    match self {
+      Indices::U16(v) => Box::new(v.iter().map(|i| *i as usize)),
+      Indices::U32(v) => Box::new(v.iter().map(|i| *i as usize)),
+    }
+  }
+
+  /// WebGL type of the indices, for `drawElements`
+  pub fn gl_type(&self) -> u32 {
+    match self {
+      Indices::U16(_) => web_sys::WebGl2RenderingContext::UNSIGNED_SHORT,
+      Indices::U32(_) => web_sys::WebGl2RenderingContext::UNSIGNED_INT,
+    }
+  }
+}
+
+impl<'a> Info<'a> {
+  /// Retrieve the object uid
+  pub fn uid(&self) -> &'a String {
+    match self {
+      Info::Lines { uid, .. } => uid,
+      Info::TrianglesWithNormals { uid, .. } => uid,
+      Info::IndexedLines { uid, .. } => uid,
+      Info::IndexedTrianglesWithNormals { uid, .. } => uid,
+      Info::InstancedTrianglesWithNormals { uid, .. } => uid,
+      Info::TexturedTriangles { uid, .. } => uid,
+      Info::ScalarTriangles { uid, .. } => uid,
+      Info::Points { uid, .. } => uid,
+      Info::Batched { info, .. } => info.uid(),
+      Info::Label { uid, .. } => uid,
+      Info::Custom { uid, .. } => uid,
+    }
+  }
+
+  /// Retrieve the material
+  pub fn material(&self) -> &'a Material {
+    match self {
+      Info::Lines { material, .. } => material,
+      Info::TrianglesWithNormals { material, .. } => material,
+      Info::IndexedLines { material, .. } => material,
+      Info::IndexedTrianglesWithNormals { material, .. } => material,
+      Info::InstancedTrianglesWithNormals { material, .. } => material,
+      Info::TexturedTriangles { material, .. } => material,
+      Info::ScalarTriangles { material, .. } => material,
+      Info::Points { material, .. } => material,
+      Info::Batched { info, .. } => info.material(),
+      Info::Label { material, .. } => material,
+      Info::Custom { material, .. } => material,
+    }
+  }
+
+  /// Whether the information is drawn with transparency
+  pub fn is_transparent(&self) -> bool {
+    match self {
+      Info::Lines { material, .. } | Info::IndexedLines { material, .. } => material.line_color().a() < 1.0,
+      Info::TrianglesWithNormals { material, .. } | Info::IndexedTrianglesWithNormals { material, .. }
+      | Info::ScalarTriangles { material, .. } => material.surface_color().a() < 1.0,
+      Info::InstancedTrianglesWithNormals { instances, .. } => instances.colors.iter().any(|c| c.a() < 1.0),
+      Info::TexturedTriangles { texture, material, .. } => texture.is_transparent() || material.surface_color().a() < 1.0,
+      Info::Points { points, material, .. } => points.is_transparent() || material.surface_color().a() < 1.0,
+      Info::Batched { info, .. } => info.is_transparent(),
+      Info::Label { .. } | Info::Custom { .. } => false,
+    }
+  }
+
+  /// Whether the information is drawn as triangles
+  pub fn is_triangles(&self) -> bool {
+    match self {
+      Info::Lines { .. } | Info::IndexedLines { .. } | Info::Label { .. } | Info::Points { .. } | Info::Custom { .. } => false,
+      Info::TrianglesWithNormals { .. } | Info::IndexedTrianglesWithNormals { .. } | Info::InstancedTrianglesWithNormals { .. }
+      | Info::TexturedTriangles { .. } | Info::ScalarTriangles { .. } => true,
+      Info::Batched { info, .. } => info.is_triangles(),
+    }
+  }
+
+  /// Average of the vertices, used for depth sorting
+  pub fn centroid(&self) -> nalgebra::Point3<f32> {
+    let (sum, n) = match self {
+      Info::Batched { info, .. } => return info.centroid(),
+      Info::Label { anchor, .. } => return **anchor,
+      Info::Points { points, .. } => return points.bounding_box().map(|(min, max)| nalgebra::center(&min, &max)).unwrap_or_else(nalgebra::Point3::origin),
+      Info::Lines { vertices, .. } => vertices.iter()
+        .fold((nalgebra::Vector3::zeros(), 0), |(r, n), (s, e)| (r + s.coords + e.coords, n + 2)),
+      Info::TrianglesWithNormals { vertices, .. } => vertices.iter()
+        .fold((nalgebra::Vector3::zeros(), 0), |(r, n), (p1, p2, p3)| (r + p1.coords + p2.coords + p3.coords, n + 3)),
+      Info::IndexedLines { positions, .. } | Info::IndexedTrianglesWithNormals { positions, .. }
+      | Info::TexturedTriangles { positions, .. } | Info::ScalarTriangles { positions, .. }
+      | Info::Custom { positions, .. } => positions.iter()
+        .fold((nalgebra::Vector3::zeros(), 0), |(r, n), p| (r + p.coords, n + 1)),
+      Info::InstancedTrianglesWithNormals { instances, .. } => instances.matrices.iter()
+        .fold((nalgebra::Vector3::zeros(), 0), |(r, n), m| (r + m.column(3).xyz(), n + 1)),
+    };
+    nalgebra::Point3::from(if n > 0 { sum / n as f32 } else { sum })
+  }
+
+  /// Axis aligned bounding box `(min, max)` of the vertices, if any
+  pub fn bounding_box(&self) -> Option<(nalgebra::Point3<f32>, nalgebra::Point3<f32>)> {
+    let points: Vec<nalgebra::Point3<f32>> = match self {
+      Info::Lines { vertices, .. } => vertices.iter().flat_map(|(s, e)| [*s, *e]).collect(),
+      Info::TrianglesWithNormals { vertices, .. } => vertices.iter().flat_map(|(p1, p2, p3)| [*p1, *p2, *p3]).collect(),
+      Info::IndexedLines { positions, .. } | Info::IndexedTrianglesWithNormals { positions, .. }
+      | Info::TexturedTriangles { positions, .. } | Info::ScalarTriangles { positions, .. }
+      | Info::Custom { positions, .. } => (*positions).clone(),
+      Info::InstancedTrianglesWithNormals { positions, instances, .. } => instances.matrices.iter()
+        .flat_map(|m| positions.iter().map(|p| m.transform_point(p)).collect::<Vec<_>>())
+        .collect(),
+      Info::Batched { info, .. } => return info.bounding_box(),
+      Info::Label { anchor, .. } => vec![**anchor],
+      Info::Points { points, .. } => return points.bounding_box(),
+    };
+    let first = *points.first()?;
+    Some(points.iter().fold((first, first), |(min, max), p| (min.inf(p), max.sup(p))))
+  }
+
+  /// Triangles as a list of vertex triplets. Empty for lines, points and custom geometry
+  pub fn triangles(&self) -> Vec<(nalgebra::Point3<f32>, nalgebra::Point3<f32>, nalgebra::Point3<f32>)> {
+    match self {
+      Info::TrianglesWithNormals { vertices, .. } => (*vertices).clone(),
+      Info::IndexedTrianglesWithNormals { positions, indices, .. } | Info::TexturedTriangles { positions, indices, .. }
+      | Info::ScalarTriangles { positions, indices, .. } => {
+        let indices = indices.iter().collect::<Vec<usize>>();
+        indices.chunks_exact(3)
+        .map(|t| (positions[t[0]], positions[t[1]], positions[t[2]]))
+        .collect()
+      },
+      Info::InstancedTrianglesWithNormals { positions, indices, instances, .. } => {
+        let indices = indices.iter().collect::<Vec<usize>>();
+        instances.matrices.iter()
+        .flat_map(|m| indices.chunks_exact(3)
+          .map(|t| (m.transform_point(&positions[t[0]]), m.transform_point(&positions[t[1]]), m.transform_point(&positions[t[2]])))
+          .collect::<Vec<_>>())
+        .collect()
+      },
+      Info::Batched { info, .. } => info.triangles(),
+      Info::Lines { .. } | Info::IndexedLines { .. } | Info::Label { .. } | Info::Points { .. } | Info::Custom { .. } => Vec::new(),
+    }
+  }
+
+  /// Make an owned copy of the information, to be drawn at a later stage
+  pub fn to_owned_info(&self) -> OwnedInfo {
+    match self {
+      Info::Lines { uid, vertices, material } => 
+        OwnedInfo::Lines { uid: (*uid).clone(), vertices: (*vertices).clone(), material: (*material).clone() },
+      Info::TrianglesWithNormals { uid, vertices, normals, material } => 
+        OwnedInfo::TrianglesWithNormals { uid: (*uid).clone(), vertices: (*vertices).clone(), normals: (*normals).clone(), material: (*material).clone() },
+      Info::IndexedLines { uid, positions, indices, material } =>
+        OwnedInfo::IndexedLines { uid: (*uid).clone(), positions: (*positions).clone(), indices: (*indices).clone(), material: (*material).clone() },
+      Info::IndexedTrianglesWithNormals { uid, positions, normals, indices, material } =>
+        OwnedInfo::IndexedTrianglesWithNormals { uid: (*uid).clone(), positions: (*positions).clone(), normals: (*normals).clone(), 
+          indices: (*indices).clone(), material: (*material).clone() },
+      Info::InstancedTrianglesWithNormals { uid, positions, normals, indices, instances, material } =>
+        OwnedInfo::InstancedTrianglesWithNormals { uid: (*uid).clone(), positions: (*positions).clone(), normals: (*normals).clone(),
+          indices: (*indices).clone(), instances: (*instances).clone(), material: (*material).clone() },
+      Info::TexturedTriangles { uid, positions, normals, uvs, indices, texture, material } =>
+        OwnedInfo::TexturedTriangles { uid: (*uid).clone(), positions: (*positions).clone(), normals: (*normals).clone(),
+          uvs: (*uvs).clone(), indices: (*indices).clone(), texture: (*texture).clone(), material: (*material).clone() },
+      Info::ScalarTriangles { uid, positions, normals, scalars, indices, style, material } =>
+        OwnedInfo::ScalarTriangles { uid: (*uid).clone(), positions: (*positions).clone(), normals: (*normals).clone(),
+          scalars: (*scalars).clone(), indices: (*indices).clone(), style: (*style).clone(), material: (*material).clone() },
+      Info::Points { uid, points, style, scalar_style, material } =>
+        OwnedInfo::Points { uid: (*uid).clone(), points: std::rc::Rc::clone(points), style: (*style).clone(), scalar_style: (*scalar_style).clone(),
+          material: (*material).clone() },
+      Info::Batched { uids, elements, key, info } =>
+        OwnedInfo::Batched { uids: (*uids).clone(), elements: (*elements).clone(), key: (*key).clone(), info: Box::new(info.to_owned_info()) },
+      Info::Label { uid, anchor, text, style, material } =>
+        OwnedInfo::Label { uid: (*uid).clone(), anchor: **anchor, text: (*text).clone(), style: (*style).clone(), material: (*material).clone() },
+      Info::Custom { uid, program, positions, attributes, indices, uniforms, material } =>
+        OwnedInfo::Custom { uid: (*uid).clone(), program: (*program).clone(), positions: (*positions).clone(), attributes: (*attributes).clone(),
+          indices: indices.cloned(), uniforms: (*uniforms).clone(), material: (*material).clone() },
+    }
+  }
+}
+
+/// Owned counterpart of `Info`
+#[derive(Clone)]
+pub enum OwnedInfo {
+  Lines {
+    uid:      String,
+    vertices: Vec<(nalgebra::Point3<f32>, nalgebra::Point3<f32>)>,
+    material: Material,
+  },
+  TrianglesWithNormals {
+    uid:      String,
+    vertices: Vec<(nalgebra::Point3<f32>, nalgebra::Point3<f32>, nalgebra::Point3<f32>)>,
+    normals:  Vec<(nalgebra::Vector3<f32>, nalgebra::Vector3<f32>, nalgebra::Vector3<f32>)>,
+    material: Material,
+  },
+  IndexedLines {
+    uid:       String,
+    positions: Vec<nalgebra::Point3<f32>>,
+    indices:   Indices,
+    material:  Material,
+  },
+  IndexedTrianglesWithNormals {
+    uid:       String,
+    positions: Vec<nalgebra::Point3<f32>>,
+    normals:   Vec<nalgebra::Vector3<f32>>,
+    indices:   Indices,
+    material:  Material,
+  },
+  InstancedTrianglesWithNormals {
+    uid:       String,
+    positions: Vec<nalgebra::Point3<f32>>,
+    normals:   Vec<nalgebra::Vector3<f32>>,
+    indices:   Indices,
+    instances: Instances,
+    material:  Material,
+  },
+  TexturedTriangles {
+    uid:       String,
+    positions: Vec<nalgebra::Point3<f32>>,
+    normals:   Vec<nalgebra::Vector3<f32>>,
+    uvs:       Vec<nalgebra::Point2<f32>>,
+    indices:   Indices,
+    texture:   Texture,
+    material:  Material,
+  },
+  ScalarTriangles {
+    uid:       String,
+    positions: Vec<nalgebra::Point3<f32>>,
+    normals:   Vec<nalgebra::Vector3<f32>>,
+    scalars:   Vec<f32>,
+    indices:   Indices,
+    style:     ScalarStyle,
+    material:  Material,
+  },
+  Points {
+    uid:          String,
+    points:       std::rc::Rc<pointcloud::PointData>,
+    style:        PointStyle,
+    scalar_style: ScalarStyle,
+    material:     Material,
+  },
+  Batched {
+    uids:     Vec<String>,
+    elements: Vec<u32>,
+    key:      std::rc::Rc<BatchKey>,
+    info:     Box<OwnedInfo>,
+  },
+  Label {
+    uid:      String,
+    anchor:   nalgebra::Point3<f32>,
+    text:     String,
+    style:    LabelStyle,
+    material: Material,
+  },
+  Custom {
+    uid:        String,
+    program:    CustomProgram,
+    positions:  Vec<nalgebra::Point3<f32>>,
+    attributes: Vec<(String, Vec<f32>)>,
+    indices:    Option<Indices>,
+    uniforms:   Vec<(String, Uniform)>,
+    material:   Material,
+  },
+}
+
+impl OwnedInfo {
+  /// Replace the material, of the merged geometry for batched information
+  pub fn with_material(self, material: Material) -> OwnedInfo {
+    match self {
+      OwnedInfo::Lines { uid, vertices, .. } => OwnedInfo::Lines { uid, vertices, material },
+      OwnedInfo::TrianglesWithNormals { uid, vertices, normals, .. } => OwnedInfo::TrianglesWithNormals { uid, vertices, normals, material },
+      OwnedInfo::IndexedLines { uid, positions, indices, .. } => OwnedInfo::IndexedLines { uid, positions, indices, material },
+      OwnedInfo::IndexedTrianglesWithNormals { uid, positions, normals, indices, .. } => 
+        OwnedInfo::IndexedTrianglesWithNormals { uid, positions, normals, indices, material },
+      OwnedInfo::InstancedTrianglesWithNormals { uid, positions, normals, indices, instances, .. } =>
+        OwnedInfo::InstancedTrianglesWithNormals { uid, positions, normals, indices, instances, material },
+      OwnedInfo::TexturedTriangles { uid, positions, normals, uvs, indices, texture, .. } =>
+        OwnedInfo::TexturedTriangles { uid, positions, normals, uvs, indices, texture, material },
+      OwnedInfo::ScalarTriangles { uid, positions, normals, scalars, indices, style, .. } =>
+        OwnedInfo::ScalarTriangles { uid, positions, normals, scalars, indices, style, material },
+      OwnedInfo::Points { uid, points, style, scalar_style, .. } => OwnedInfo::Points { uid, points, style, scalar_style, material },
+      OwnedInfo::Batched { uids, elements, key, info } => OwnedInfo::Batched { uids, elements, key, info: Box::new(info.with_material(material)) },
+      OwnedInfo::Label { uid, anchor, text, style, .. } => OwnedInfo::Label { uid, anchor, text, style, material },
+      OwnedInfo::Custom { uid, program, positions, attributes, indices, uniforms, .. } =>
+        OwnedInfo::Custom { uid, program, positions, attributes, indices, uniforms, material },
+    }
+  }
+
+  /// Borrow as an `Info`
+  pub fn as_info(&self) -> Info<'_> {
+    match self {
+      OwnedInfo::Lines { uid, vertices, material } => Info::Lines { uid, vertices, material },
+      OwnedInfo::TrianglesWithNormals { uid, vertices, normals, material } => Info::TrianglesWithNormals { uid, vertices, normals, material },
+      OwnedInfo::IndexedLines { uid, positions, indices, material } => Info::IndexedLines { uid, positions, indices, material },
+      OwnedInfo::IndexedTrianglesWithNormals { uid, positions, normals, indices, material } => 
+        Info::IndexedTrianglesWithNormals { uid, positions, normals, indices, material },
+      OwnedInfo::InstancedTrianglesWithNormals { uid, positions, normals, indices, instances, material } =>
+        Info::InstancedTrianglesWithNormals { uid, positions, normals, indices, instances, material },
+      OwnedInfo::TexturedTriangles { uid, positions, normals, uvs, indices, texture, material } =>
+        Info::TexturedTriangles { uid, positions, normals, uvs, indices, texture, material },
+      OwnedInfo::ScalarTriangles { uid, positions, normals, scalars, indices, style, material } =>
+        Info::ScalarTriangles { uid, positions, normals, scalars, indices, style, material },
+      OwnedInfo::Points { uid, points, style, scalar_style, material } => Info::Points { uid, points, style, scalar_style, material },
+      OwnedInfo::Batched { uids, elements, key, info } => Info::Batched { uids, elements, key, info: Box::new(info.as_info()) },
+      OwnedInfo::Label { uid, anchor, text, style, material } => Info::Label { uid, anchor, text, style, material },
+      OwnedInfo::Custom { uid, program, positions, attributes, indices, uniforms, material } =>
+        Info::Custom { uid, program, positions, attributes, indices: indices.as_ref(), uniforms, material },
+    }
+  }
+}
+
+#[cfg(all(test, feature = "wasm"))]
+mod tests {
+  use super::*;
+
+  fn p(x: f32, y: f32, z: f32) -> nalgebra::Point3<f32> { nalgebra::Point3::new(x, y, z) }
+
+  /// Indexed segments between the `positions`
+  fn lines(positions: Vec<nalgebra::Point3<f32>>) -> OwnedInfo {
+    let indices = Indices::new((0..positions.len() as u32).collect());
+    OwnedInfo::IndexedLines { uid: "lines".into(), positions, indices, material: Material::default() }
+  }
+
+  #[test]
+  fn indices_use_u16_when_sufficient() {
+    assert!(matches!(Indices::new(vec![0, 1, u16::MAX as u32]), Indices::U16(_)));
+    assert!(matches!(Indices::new(vec![0, u16::MAX as u32 + 1]), Indices::U32(_)));
+  }
+
+  #[test]
+  fn indices_keep_their_values() {
+    let values = vec![3, 0, 70_000, 2];
+    let indices = Indices::new(values.clone());
+    assert_eq!(indices.len(), 4);
+    assert_eq!(indices.iter().map(|i| i as u32).collect::<Vec<_>>(), values);
+    assert_eq!(indices.gl_type(), web_sys::WebGl2RenderingContext::UNSIGNED_INT);
+    assert_eq!(Indices::new(vec![0]).gl_type(), web_sys::WebGl2RenderingContext::UNSIGNED_SHORT);
+  }
+
+  #[test]
+  fn centroid_is_the_mean_position() {
+    let info = lines(vec![p(0.0, 0.0, 0.0), p(2.0, 0.0, 0.0), p(2.0, 4.0, 0.0), p(0.0, 4.0, 2.0)]);
+    assert_eq!(info.as_info().centroid(), p(1.0, 2.0, 0.5));
+    assert_eq!(lines(Vec::new()).as_info().centroid(), p(0.0, 0.0, 0.0));
+  }
+
+  #[test]
+  fn bounding_box_spans_the_positions() {
+    let info = lines(vec![p(1.0, -1.0, 0.0), p(-2.0, 3.0, 1.0), p(0.0, 0.0, -4.0)]);
+    assert_eq!(info.as_info().bounding_box(), Some((p(-2.0, -1.0, -4.0), p(1.0, 3.0, 1.0))));
+    assert_eq!(lines(Vec::new()).as_info().bounding_box(), None);
+  }
+
+  #[test]
+  fn bounding_box_of_instances_spans_the_transformed_positions() {
+    let instances = Instances {
+      uids:     vec!["a".into(), "b".into()],
+      matrices: vec![nalgebra::Matrix4::identity(), nalgebra::Matrix4::new_translation(&nalgebra::Vector3::new(5.0, 0.0, 0.0))],
+      colors:   vec![Color::rgb(1.0, 1.0, 1.0); 2],
+    };
+    let info = OwnedInfo::InstancedTrianglesWithNormals { uid: "instanced".into(), positions: vec![p(0.0, 0.0, 0.0), p(1.0, 1.0, 1.0)],
+      normals: Vec::new(), indices: Indices::new(Vec::new()), instances, material: Material::default() };
+    assert_eq!(info.as_info().bounding_box(), Some((p(0.0, 0.0, 0.0), p(6.0, 1.0, 1.0))));
+  }
+}
@@ -0,0 +1,177 @@
+use super::*;
+
+/// Light shining in a constant direction, eg the sun
+#[derive(Clone, Debug)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct DirectionalLight {
+  /// Direction in world coordinates from which the light comes (ie pointing towards the light)
+  direction: nalgebra::Vector3<f32>,
+  color:     Color,
+  intensity: f32,
+}
+
+/// Light emitted from a point in world coordinates
+#[derive(Clone, Debug)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct PointLight {
+  position:  nalgebra::Point3<f32>,
+  color:     Color,
+  intensity: f32,
+  /// Distance at which the light is fully attenuated. No attenuation when zero
+  #[serde(default)]
+  range:     f32,
+}
+
+/// Directional light attached to the camera, shining along the view direction
+#[derive(Clone, Debug)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Headlight {
+  color:     Color,
+  intensity: f32,
+}
+
+/// Lighting setup used by the `Renderer` to shade triangles
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[derive(Clone, Debug)]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Lighting {
+  ambient:           Color,
+  ambient_intensity: f32,
+  directional:       Vec<DirectionalLight>,
+  point:             Vec<PointLight>,
+  headlight:         Option<Headlight>,
+}
+
+impl Default for Lighting {
+  fn default() -> Lighting {
+    Lighting {
+      ambient: Color::rgb(1.0, 1.0, 1.0),
+      ambient_intensity: 0.35,
+      directional: vec!(DirectionalLight { direction: nalgebra::Vector3::new(1.0, 1.0, 1.0), color: Color::rgb(1.0, 1.0, 1.0), intensity: 0.45 }),
+      point: Vec::new(),
+      headlight: Some(Headlight { color: Color::rgb(1.0, 1.0, 1.0), intensity: 0.35 }),
+    }
+  }
+}
+
+impl Lighting {
+  /// Maximum number of directional lights (including the headlight) supported by the shader
+  pub const MAX_DIRECTIONAL_LIGHTS: usize = 4;
+  /// Maximum number of point lights supported by the shader
+  pub const MAX_POINT_LIGHTS: usize = 4;
+
+  /// Light casting shadows - the first directional light - as its index in the shader directional light uniforms 
+  /// and its direction in world coordinates
+  pub fn shadow_light(&self) -> Option<(i32, nalgebra::Vector3<f32>)> {
+    let index = self.headlight.iter().count();
+    if index >= Self::MAX_DIRECTIONAL_LIGHTS { return None; }
+    self.directional.first().map(|l| (index as i32, l.direction))
+  }
+
+  /// Set the lighting uniforms of `program`. Lights are converted to view coordinates using `view`.
+  /// Assumes that the program has already been set.
+  pub fn set_uniforms(&self,
+    context: &web_sys::WebGl2RenderingContext,
+    program: &web_sys::WebGlProgram,
+    view: &nalgebra::Matrix4<f32>,
+    ) -> Result<(), Error> {
+    let ambient = self.ambient.as_array();
+    let u_ambient = context.get_uniform_location(program, "u_ambient");
+    context.uniform3f(u_ambient.as_ref(), 
+      ambient[0] * self.ambient_intensity, ambient[1] * self.ambient_intensity, ambient[2] * self.ambient_intensity);
+
+    let directional = self.headlight.iter()
+      .map(|h| (nalgebra::Vector3::z(), h.color, h.intensity))
+      .chain(self.directional.iter().map(|l| (view.transform_vector(&l.direction).normalize(), l.color, l.intensity)))
+      .take(Self::MAX_DIRECTIONAL_LIGHTS)
+      .collect::<Vec<_>>();
+    let u_n = context.get_uniform_location(program, "u_nDirectional");
+    context.uniform1i(u_n.as_ref(), directional.len().try_into()?);
+    if !directional.is_empty() {
+      let directions = directional.iter().flat_map(|(d, _, _)| [d.x, d.y, d.z]).collect::<Vec<f32>>();
+      let colors = directional.iter().flat_map(|(_, c, i)| [c.r() * i, c.g() * i, c.b() * i]).collect::<Vec<f32>>();
+      let u_direction = context.get_uniform_location(program, "u_directionalDirection");
+      context.uniform3fv_with_f32_array(u_direction.as_ref(), directions.as_slice());
+      let u_color = context.get_uniform_location(program, "u_directionalColor");
+      context.uniform3fv_with_f32_array(u_color.as_ref(), colors.as_slice());
+    }
+
+    let point = self.point.iter().take(Self::MAX_POINT_LIGHTS).collect::<Vec<_>>();
+    let u_n = context.get_uniform_location(program, "u_nPoint");
+    context.uniform1i(u_n.as_ref(), point.len().try_into()?);
+    if !point.is_empty() {
+      let positions = point.iter().map(|l| view.transform_point(&l.position)).flat_map(|p| [p.x, p.y, p.z]).collect::<Vec<f32>>();
+      let colors = point.iter().flat_map(|l| [l.color.r() * l.intensity, l.color.g() * l.intensity, l.color.b() * l.intensity]).collect::<Vec<f32>>();
+      let ranges = point.iter().map(|l| l.range).collect::<Vec<f32>>();
+      let u_position = context.get_uniform_location(program, "u_pointPosition");
+      context.uniform3fv_with_f32_array(u_position.as_ref(), positions.as_slice());
+      let u_color = context.get_uniform_location(program, "u_pointColor");
+      context.uniform3fv_with_f32_array(u_color.as_ref(), colors.as_slice());
+      let u_range = context.get_uniform_location(program, "u_pointRange");
+      context.uniform1fv_with_f32_array(u_range.as_ref(), ranges.as_slice());
+    }
+    Ok(())
+  }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+impl Lighting {
+  /// Create the default lighting: ambient light, one directional light and a headlight
+  pub fn new() -> Lighting { Lighting::default() }
+
+  /// Create a lighting setup without any light - only to be completed using `with_*` functions
+  pub fn empty() -> Lighting {
+    Lighting { ambient: Color::rgb(1.0, 1.0, 1.0), ambient_intensity: 0.0, directional: Vec::new(), point: Vec::new(), headlight: None, }
+  }
+
+  /// Set the ambient light
+  pub fn with_ambient(mut self, color: Color, intensity: f32) -> Lighting {
+    self.ambient = color; self.ambient_intensity = intensity; self
+  }
+
+  /// Add a directional light coming from `direction` `[x, y, z]` in world coordinates
+  pub fn with_directional_light(mut self, direction: &[f32], color: Color, intensity: f32) -> Result<Lighting, JsError> {
+    if direction.len() != 3 { return Err("Direction must be specified as [x, y, z]".into()); }
+    if self.directional.len() + self.headlight.iter().count() >= Self::MAX_DIRECTIONAL_LIGHTS {
+      return Err(format!("A maximum of {} directional lights (including headlight) is supported", Self::MAX_DIRECTIONAL_LIGHTS).into());
+    }
+    self.directional.push(DirectionalLight { direction: nalgebra::Vector3::from_row_slice(direction), color, intensity });
+    Ok(self)
+  }
+
+  /// Add a point light at `position` `[x, y, z]` in world coordinates. The light is fully attenuated at distance `range`, 
+  /// or not attenuated if `range` is zero
+  pub fn with_point_light(mut self, position: &[f32], color: Color, intensity: f32, range: f32) -> Result<Lighting, JsError> {
+    if position.len() != 3 { return Err("Position must be specified as [x, y, z]".into()); }
+    if self.point.len() >= Self::MAX_POINT_LIGHTS {
+      return Err(format!("A maximum of {} point lights is supported", Self::MAX_POINT_LIGHTS).into());
+    }
+    self.point.push(PointLight { position: nalgebra::Point3::from_slice(position), color, intensity, range });
+    Ok(self)
+  }
+
+  /// Attach a light to the camera
+  pub fn with_headlight(mut self, color: Color, intensity: f32) -> Lighting {
+    self.headlight = Some(Headlight { color, intensity }); self
+  }
+
+  /// Remove the light attached to the camera
+  pub fn without_headlight(mut self) -> Lighting { self.headlight = None; self }
+
+  /// Remove all directional and point lights
+  pub fn clear_lights(mut self) -> Lighting { self.directional = Vec::new(); self.point = Vec::new(); self }
+
+  /// Expose clone functionality to JavaScript
+  pub fn clone(&self) -> Lighting { Clone::clone(self) }
+
+  /// Convert lighting to json
+  pub fn to_json(&self) -> Result<String, JsError> {
+    Ok(serde_json::to_string(&self).map_err(|e| format!("{e}"))?)
+  }
+
+  /// Create lighting from json
+  pub fn from_json(json: &str) -> Result<Lighting, JsError> {
+    Ok(serde_json::from_str(json).map_err(|e| format!("{e}"))?)
+  }
+}
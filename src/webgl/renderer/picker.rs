@@ -8,13 +8,63 @@ pub mod builder;
 pub struct Picker {
   camera: Camera,
   program: std::rc::Rc<std::cell::RefCell<Option<web_sys::WebGlProgram>>>,
-  objects: std::rc::Rc<std::cell::RefCell<Vec<String>>>,
+  programs: ProgramCache,
+  program_wide_lines: std::rc::Rc<std::cell::RefCell<Option<web_sys::WebGlProgram>>>,
+  program_labels: std::rc::Rc<std::cell::RefCell<Option<web_sys::WebGlProgram>>>,
+  glyph_atlas: std::rc::Rc<std::cell::RefCell<Option<glyphatlas::GlyphAtlas>>>,
+  labels: std::rc::Rc<std::cell::RefCell<Vec<OwnedInfo>>>,
+  program_points: std::rc::Rc<std::cell::RefCell<Option<web_sys::WebGlProgram>>>,
+  point_buffers: std::rc::Rc<std::cell::RefCell<pointbuffers::PointBufferCache>>,
+  batch_buffers: std::rc::Rc<std::cell::RefCell<batchbuffers::BatchBufferCache>>,
+  objects: std::rc::Rc<std::cell::RefCell<Vec<PickObject>>>,
   pick_position: Option<(i32, i32)>,
   pick_result: std::rc::Rc<std::cell::RefCell<Option<String>>>,
+  pick_index: std::rc::Rc<std::cell::Cell<Option<u32>>>,
+  context_state: contextloss::ContextState,
+  /// Counts of the picking draws, apart from the statistics of the renderers
+  counters: statistics::Counters,
+}
+
+/// Object drawn for picking, holding consecutive ids from `first`: one per point for point clouds, one otherwise
+struct PickObject {
+  first:    usize,
+  uid:      String,
+  n_points: Option<usize>,
+}
+
+impl PickObject {
+  /// Id following the ids of the object
+  fn end(&self) -> usize { self.first + self.n_points.unwrap_or(1) }
 }
 
 impl Picker {
   const N_RGBA_VALUES: usize = 255;
+
+  /// Id of the next object drawn
+  fn next_id(&self) -> usize { self.objects.borrow().last().map(|o| o.end()).unwrap_or(1) }
+
+  /// Record the object `uid` - a point cloud of `n_points` points if any - and return its first id
+  fn push_object(&self, uid: String, n_points: Option<usize>) -> usize {
+    let first = self.next_id();
+    self.objects.borrow_mut().push(PickObject { first, uid, n_points });
+    first
+  }
+
+  /// Colour encoding the object id `count` - starting from 1, 0 being no object
+  fn id_color(count: usize) -> [f32; 4] {
+    let n = Picker::N_RGBA_VALUES;
+    let r = count.rem_euclid(n); let count = count.div_euclid(n);
+    let g = count.rem_euclid(n); let count = count.div_euclid(n);
+    let b = count.rem_euclid(n); let count = count.div_euclid(n);
+    let a = count.rem_euclid(n);
+    [r as f32/n as f32, g as f32/n as f32, b as f32/n as f32, a as f32/n as f32]
+  }
+
+  /// Object id encoded by the colour `data` read back from the framebuffer
+  fn color_id(data: [u8; 4]) -> usize {
+    let n = Picker::N_RGBA_VALUES;
+    data[0] as usize + n*(data[1] as usize + n*(data[2] as usize + n*data[3] as usize))
+  }
 }
 
 #[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
@@ -27,6 +77,9 @@ impl Picker {
 
   /// Retrieve result
   pub fn result(&self) -> Option<String> { self.pick_result.borrow().clone() }
+
+  /// Retrieve the index of the point picked, when the result is a point cloud
+  pub fn result_index(&self) -> Option<u32> { self.pick_index.get() }
 }
 
 impl Picker {
@@ -34,18 +87,48 @@ impl Picker {
     Picker { 
       camera, 
       program: std::rc::Rc::new(std::cell::RefCell::new(None)),
+      programs: ProgramCache::default(),
+      program_wide_lines: std::rc::Rc::new(std::cell::RefCell::new(None)),
+      program_labels: std::rc::Rc::new(std::cell::RefCell::new(None)),
+      glyph_atlas: std::rc::Rc::new(std::cell::RefCell::new(None)),
+      labels: std::rc::Rc::new(std::cell::RefCell::new(Vec::new())),
+      program_points: std::rc::Rc::new(std::cell::RefCell::new(None)),
+      point_buffers: std::rc::Rc::new(std::cell::RefCell::new(pointbuffers::PointBufferCache::default())),
+      batch_buffers: std::rc::Rc::new(std::cell::RefCell::new(batchbuffers::BatchBufferCache::default())),
       objects: std::rc::Rc::new(std::cell::RefCell::new(Vec::new())),
       pick_position: None,
       pick_result: std::rc::Rc::new(std::cell::RefCell::new(None)),
+      pick_index: std::rc::Rc::new(std::cell::Cell::new(None)),
+      context_state: contextloss::ContextState::default(),
+      counters: statistics::Counters::default(),
     }
   }
 }
 
 impl RendererTrait for Picker {
-  /// Initialise render
+  /// Initialise render. Nothing is picked while the context is lost, and the programs and buffers of a lost context
+  /// are created again once restored
   fn init(&self,
     context: &web_sys::WebGl2RenderingContext,
     ) -> Result<(), Error> {
+    match self.context_state.check(context)? {
+      contextloss::ContextStatus::Lost => {
+        *self.pick_result.borrow_mut() = None;
+        self.pick_index.set(None);
+        return Ok(());
+      },
+      contextloss::ContextStatus::Restored => {
+        for program in [&self.program, &self.program_wide_lines, &self.program_labels, &self.program_points] {
+          *program.borrow_mut() = None;
+        }
+        self.programs.clear();
+        *self.glyph_atlas.borrow_mut() = None;
+        self.point_buffers.borrow_mut().invalidate();
+        self.batch_buffers.borrow_mut().invalidate();
+      },
+      contextloss::ContextStatus::Valid => (),
+    }
+    self.counters.begin_frame();
 
     let texture = context.create_texture().ok_or("Unable to create texture")?;
     context.bind_texture(web_sys::WebGl2RenderingContext::TEXTURE_2D, Some(&texture));
@@ -94,6 +177,9 @@ impl RendererTrait for Picker {
     context.clear(web_sys::WebGl2RenderingContext::COLOR_BUFFER_BIT | web_sys::WebGl2RenderingContext::DEPTH_BUFFER_BIT);
 
     *self.objects.borrow_mut() = Vec::new();
+    self.labels.borrow_mut().clear();
+    self.point_buffers.borrow_mut().release_unused(context);
+    self.batch_buffers.borrow_mut().release_unused(context);
     Ok(())
   }
 
@@ -102,13 +188,24 @@ impl RendererTrait for Picker {
     context: &web_sys::WebGl2RenderingContext,
     info: Info
     ) -> Result<(), Error> {
+    if self.context_state.is_lost() { return Ok(()); }
+    if let Info::InstancedTrianglesWithNormals { .. } = info { return self.draw_instanced(context, info); }
+    if let Info::Batched { .. } = info { return self.draw_batched(context, info); }
+    if let Info::Points { .. } = info { return self.draw_points(context, info); }
+    if let Info::Custom { .. } = info { return self.draw_custom(context, info); }
+    if let Info::Label { .. } = info {
+      // Drawn over the scene
+      self.labels.borrow_mut().push(info.to_owned_info());
+      return Ok(());
+    }
+
     // Retrieve program (initialise if required)
     if self.program.borrow().is_none() {
       *self.program.borrow_mut() = Some(ProgramPicker::webgl_program(context)?);
     }
     let program = self.program.borrow();
     let program = program.as_ref().ok_or("Unable to retrieve picker program...")?;
-    context.use_program(Some(program));
+    self.counters.use_program(context, program);
 
     // Bind camera
     /*
@@ -121,49 +218,78 @@ impl RendererTrait for Picker {
     context.uniform_matrix4fv_with_f32_array(u_matrix.as_ref(), false, self.camera.as_matrix()?.as_slice());
     
     // Set color based on id counter
-    let count = self.objects.borrow().len() + 1;
-    let n = Picker::N_RGBA_VALUES;
-    let r = count.rem_euclid(n); let count = count.div_euclid(n);
-    let g = count.rem_euclid(n); let count = count.div_euclid(n);
-    let b = count.rem_euclid(n); let count = count.div_euclid(n);
-    let a = count.rem_euclid(n);
+    let [r, g, b, a] = Picker::id_color(self.next_id());
     let u_id = context.get_uniform_location(program, "u_id");
-    context.uniform4f(u_id.as_ref(), r as f32/n as f32, g as f32/n as f32, b as f32/n as f32, a as f32/n as f32);
+    context.uniform4f(u_id.as_ref(), r, g, b, a);
+    clipping::set_uniforms(context, program, &self.camera.clipping().equations());
+
+    if let Info::Lines { material, .. } | Info::IndexedLines { material, .. } = info {
+      if !material.lines_visible() { return Ok(()); }
+    }
+    self.push_object(info.uid().clone(), None);
 
     match info {
-      Info::Lines { uid, vertices } => {
-        self.objects.borrow_mut().push(uid.clone());
+      Info::Lines { vertices, material, .. } if !material.line_style().is_thin() => {
+        let positions = utils::point3_to_vecf32(&vertices.iter().fold(Vec::new(), |mut r, (s, e)| {r.push(s); r.push(e); r}));
+        let segments = programwidelines::Segments::new(&positions, None);
+        return self.draw_wide_lines(context, &segments, &std::iter::repeat_n([r, g, b, a], segments.len()).flatten().collect::<Vec<f32>>(), material.line_style());
+      },
+      Info::IndexedLines { positions, indices, material, .. } if !material.line_style().is_thin() => {
+        let positions = utils::point3_to_vecf32(&positions.iter().collect::<Vec<_>>());
+        let segments = programwidelines::Segments::new(&positions, Some(indices));
+        return self.draw_wide_lines(context, &segments, &std::iter::repeat_n([r, g, b, a], segments.len()).flatten().collect::<Vec<f32>>(), material.line_style());
+      },
+      _ => (),
+    }
 
+    match info {
+      Info::Lines { uid: _, vertices, material: _ } => {
         // Bind vertices
         let positions = vertices.iter()
         .fold(Vec::new(), |mut r, (s, e)| {r.push(s); r.push(e); r});
         let positions = utils::point3_to_vecf32(&positions);
-        utils::bind(context, program, "a_position", &positions)?;
+        utils::bind(context, &self.counters, program, "a_position", &positions)?;
 
         // Draw
         let n: i32 = (2*vertices.len()).try_into()?;
         context.draw_arrays(web_sys::WebGl2RenderingContext::LINES, 0, n);
+        self.counters.record_draw(web_sys::WebGl2RenderingContext::LINES, 2*vertices.len(), 1);
 
       },
-      Info::TrianglesWithNormals { uid, vertices, normals: _ } => {
-        self.objects.borrow_mut().push(uid.clone());
-
+      Info::TrianglesWithNormals { uid: _, vertices, normals: _, material: _ } => {
         // Bind vertices
         let positions = vertices.iter()
         .fold(Vec::new(), |mut r, (p1, p2, p3)| {r.push(p1); r.push(p2); r.push(p3); r});
         let positions = utils::point3_to_vecf32(&positions);
-        utils::bind(context, program, "a_position", &positions)?;
+        utils::bind(context, &self.counters, program, "a_position", &positions)?;
 
         // Draw
         let n: i32 = (3*vertices.len()).try_into()?;
         context.draw_arrays(web_sys::WebGl2RenderingContext::TRIANGLES, 0, n);
+        self.counters.record_draw(web_sys::WebGl2RenderingContext::TRIANGLES, 3*vertices.len(), 1);
+      },
+      Info::IndexedLines { uid: _, positions, indices, material: _ } => {
+        let positions = positions.iter().collect::<Vec<_>>();
+        utils::bind(context, &self.counters, program, "a_position", &utils::point3_to_vecf32(&positions))?;
+        utils::draw(context, &self.counters, web_sys::WebGl2RenderingContext::LINES, positions.len(), Some(indices))?;
       },
+      Info::IndexedTrianglesWithNormals { positions, indices, .. } | Info::TexturedTriangles { positions, indices, .. }
+      | Info::ScalarTriangles { positions, indices, .. } => {
+        let positions = positions.iter().collect::<Vec<_>>();
+        utils::bind(context, &self.counters, program, "a_position", &utils::point3_to_vecf32(&positions))?;
+        utils::draw(context, &self.counters, web_sys::WebGl2RenderingContext::TRIANGLES, positions.len(), Some(indices))?;
+      },
+      // Picked by their own draw functions
+      Info::InstancedTrianglesWithNormals { .. } | Info::Batched { .. } | Info::Label { .. } | Info::Points { .. }
+      | Info::Custom { .. } => (),
     };
     Ok(())
   }
 
-  /// Post render
+  /// Post render: draw the labels over the scene and retrieve the object at the pick position
   fn end(&self, context: &web_sys::WebGl2RenderingContext) -> Result<(), Error> {
+    if self.context_state.is_lost() { return Ok(()); }
+    self.draw_labels(context)?;
     if self.pick_position.is_some() {
       let p = self.pick_position.as_ref().unwrap();
       let mut data: [u8; 4] = [0; 4];
@@ -176,55 +302,268 @@ impl RendererTrait for Picker {
         web_sys::WebGl2RenderingContext::UNSIGNED_BYTE, // Type
         Some(&mut data), // dst_data
       )?;
-      let n = Picker::N_RGBA_VALUES as u8;
-      let count = data[0] + n*(data[1] + n*(data[2] + n*data[3]));
-      let count = count as usize;
-      if count > self.objects.borrow().len() { return Err(format!("Found object {count} - which exceeds object length {}", self.objects.borrow().len()).into()); }
-      *self.pick_result.borrow_mut() = if count == 0 { 
-        None 
-      } else {
-        Some(self.objects.borrow()[count-1].clone())
-      };
+      let count = Picker::color_id(data);
+      if count >= self.next_id() { return Err(format!("Found object {count} - which exceeds object ids {}", self.next_id() - 1).into()); }
+      let objects = self.objects.borrow();
+      let object = if count == 0 { None } else { objects.get(objects.partition_point(|o| o.first <= count) - 1) };
+      *self.pick_result.borrow_mut() = object.map(|o| o.uid.clone());
+      self.pick_index.set(object.and_then(|o| o.n_points.map(|_| (count - o.first) as u32)));
     }
     context.bind_framebuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER, None);
     Ok(())
   }
 }
 
+impl Picker {
+  /// Draw line segments as screen-space quads, with an id per segment
+  fn draw_wide_lines(&self,
+    context: &web_sys::WebGl2RenderingContext,
+    segments: &programwidelines::Segments,
+    ids: &[f32],
+    style: &LineStyle,
+    ) -> Result<(), Error> {
+    let matrix = nalgebra::Matrix4::from_column_slice(self.camera.as_matrix()?.as_slice());
+    programwidelines::ProgramWideLines::draw(context, &self.counters, &self.program_wide_lines, segments, ids, style, &matrix,
+      (self.camera.get_width() as i32, self.camera.get_height() as i32), &self.camera.clipping().equations())
+  }
+
+  /// Draw the label boxes over the scene, with an id per label
+  fn draw_labels(&self, context: &web_sys::WebGl2RenderingContext) -> Result<(), Error> {
+    let labels = std::mem::take(&mut *self.labels.borrow_mut());
+    let matrix = nalgebra::Matrix4::from_column_slice(self.camera.as_matrix()?.as_slice());
+    let equations = self.camera.clipping().equations();
+    for info in labels.iter() {
+      let info = info.as_info();
+      let id = Picker::id_color(self.push_object(info.uid().clone(), None));
+      programlabels::ProgramLabels::draw(context, &self.counters, &self.program_labels, &self.glyph_atlas, &info, &matrix,
+        (self.camera.get_width() as i32, self.camera.get_height() as i32), Some(id), &equations)?;
+    }
+    Ok(())
+  }
+
+  /// Draw instanced geometry, with an id per instance
+  fn draw_instanced(&self,
+    context: &web_sys::WebGl2RenderingContext,
+    info: Info
+    ) -> Result<(), Error> {
+    let Info::InstancedTrianglesWithNormals { uid: _, positions, normals: _, indices, instances, material: _ } = info else {
+      return Err("Expected instanced geometry".into());
+    };
+    let program = &ProgramPicker::webgl_program_instanced(context, &self.programs)?;
+    self.counters.use_program(context, program);
+
+    let u_matrix = context.get_uniform_location(program, "uMatrix");
+    context.uniform_matrix4fv_with_f32_array(u_matrix.as_ref(), false, self.camera.as_matrix()?.as_slice());
+    clipping::set_uniforms(context, program, &self.camera.clipping().equations());
+
+    // One id per instance
+    let ids = instances.uids.iter().flat_map(|uid| Picker::id_color(self.push_object(uid.clone(), None))).collect::<Vec<f32>>();
+
+    let positions = positions.iter().collect::<Vec<_>>();
+    utils::bind(context, &self.counters, program, "a_position", &utils::point3_to_vecf32(&positions))?;
+    let mut locations = utils::bind_per_instance(context, &self.counters, program, "a_instanceMatrix", &utils::matrices_to_vecf32(&instances.matrices), 4, 4)?;
+    locations.append(&mut utils::bind_per_instance(context, &self.counters, program, "a_instanceId", &ids, 1, 4)?);
+    utils::draw_instanced(context, &self.counters, web_sys::WebGl2RenderingContext::TRIANGLES, positions.len(), Some(indices), instances.len())?;
+    utils::unbind_per_instance(context, &locations);
+    Ok(())
+  }
+}
+
+impl Picker {
+  /// Draw a point cloud from its cached GPU buffers, with an id per point
+  fn draw_points(&self,
+    context: &web_sys::WebGl2RenderingContext,
+    info: Info
+    ) -> Result<(), Error> {
+    let Info::Points { uid, points, style, .. } = info else {
+      return Err("Expected point cloud".into());
+    };
+    if points.is_empty() { return Ok(()); }
+    let first = self.push_object(uid.clone(), Some(points.len()));
+    let projection = nalgebra::Matrix4::from_column_slice(self.camera.as_projection_matrix()?.as_slice());
+    let pixels_per_unit = 0.5 * self.camera.get_height() * projection[(1, 1)];
+    let matrix = nalgebra::Matrix4::from_column_slice(self.camera.as_matrix()?.as_slice());
+    let mut point_buffers = self.point_buffers.borrow_mut();
+    let buffers = point_buffers.buffers(context, &self.counters, points, true)?;
+    programpoints::ProgramPoints::draw(context, &self.counters, &self.program_points, buffers, points.len(), style, programpoints::PointColoring::Ids(first),
+      &matrix, pixels_per_unit, &self.camera.clipping().equations())
+  }
+
+  /// Draw geometry with the picking variant of its custom program, writing the id of the element
+  fn draw_custom(&self,
+    context: &web_sys::WebGl2RenderingContext,
+    info: Info
+    ) -> Result<(), Error> {
+    let Info::Custom { uid, program: custom, positions, attributes, indices, uniforms, .. } = info else {
+      return Err("Expected custom geometry".into());
+    };
+    let program = &custom.webgl_program(context, &self.programs, true)?;
+    self.counters.use_program(context, program);
+
+    custom.set_camera_uniforms(context, program, &self.camera.as_view_matrix()?, &self.camera.as_matrix()?,
+      &self.camera.as_transpose_inverse_view_matrix()?, (self.camera.get_width() as i32, self.camera.get_height() as i32));
+    clipping::set_uniforms(context, program, &self.camera.clipping().equations());
+    let [r, g, b, a] = Picker::id_color(self.push_object(uid.clone(), None));
+    let u_pick_id = context.get_uniform_location(program, "u_pickId");
+    context.uniform4f(u_pick_id.as_ref(), r, g, b, a);
+
+    custom.draw(context, &self.counters, program, &utils::point3_to_vecf32(&positions.iter().collect::<Vec<_>>()), attributes, indices, uniforms)
+  }
+
+  /// Draw batched geometry, with the id of the original element per vertex
+  fn draw_batched(&self,
+    context: &web_sys::WebGl2RenderingContext,
+    info: Info
+    ) -> Result<(), Error> {
+    let Info::Batched { uids, elements, key, info } = info else {
+      return Err("Expected batched geometry".into());
+    };
+    let mode = match *info {
+      Info::IndexedLines { positions, indices, material, .. } => {
+        if !material.lines_visible() { return Ok(()); }
+        if !material.line_style().is_thin() {
+          // One id per segment, from the element of its first vertex
+          let start = self.next_id();
+          for uid in uids.iter() { self.push_object(uid.clone(), None); }
+          let segments = programwidelines::Segments::new(&utils::point3_to_vecf32(&positions.iter().collect::<Vec<_>>()), Some(indices));
+          let ids = segments.first_vertices.iter().flat_map(|v| Picker::id_color(start + elements[*v] as usize)).collect::<Vec<f32>>();
+          return self.draw_wide_lines(context, &segments, &ids, material.line_style());
+        }
+        web_sys::WebGl2RenderingContext::LINES
+      },
+      Info::IndexedTrianglesWithNormals { .. } => web_sys::WebGl2RenderingContext::TRIANGLES,
+      info => return self.draw(context, info),
+    };
+
+    let program = &ProgramPicker::webgl_program_batched(context, &self.programs)?;
+    self.counters.use_program(context, program);
+
+    let u_matrix = context.get_uniform_location(program, "uMatrix");
+    context.uniform_matrix4fv_with_f32_array(u_matrix.as_ref(), false, self.camera.as_matrix()?.as_slice());
+    clipping::set_uniforms(context, program, &self.camera.clipping().equations());
+
+    // One id per original element, following the first id
+    let u_first_id = context.get_uniform_location(program, "u_firstId");
+    context.uniform1ui(u_first_id.as_ref(), self.next_id().try_into()?);
+    for uid in uids.iter() { self.push_object(uid.clone(), None); }
+
+    let mut batch_buffers = self.batch_buffers.borrow_mut();
+    let buffers = batch_buffers.buffers(context, &self.counters, key, &info, Some(elements))?;
+    buffers.bind(context, program, "a_position");
+    utils::bind_uint_buffer(context, program, "a_element", buffers.elements.as_ref(), 1);
+    buffers.draw(context, &self.counters, mode)
+  }
+}
+
 pub struct ProgramPicker {}
 
 impl ProgramPicker {
+  /// Program drawing the geometry with the `u_id` colour
   pub fn webgl_program(
     context: &web_sys::WebGl2RenderingContext,
   ) -> Result<web_sys::WebGlProgram, Error> {
-    Ok(WebGlProgramBuilder::new()
+    WebGlProgramBuilder::new()
+      .context(context)
+      .vertex_shader_source(Self::VERTEX_SHADER_SOURCE)
+      .fragment_shader_source(Self::FRAGMENT_SHADER_SOURCE)
+      .build()
+  }
+
+  /// Program drawing instances with the per-instance `a_instanceId` colour, compiled on first use and kept in `cache`
+  pub fn webgl_program_instanced(
+    context: &web_sys::WebGl2RenderingContext,
+    cache: &ProgramCache,
+  ) -> Result<web_sys::WebGlProgram, Error> {
+    WebGlProgramBuilder::new()
       .context(context)
       .vertex_shader_source(Self::VERTEX_SHADER_SOURCE)
       .fragment_shader_source(Self::FRAGMENT_SHADER_SOURCE)
-      .build()?)
+      .define("INSTANCED")
+      .cache(cache, "picker")
+      .build()
+  }
+
+  /// Program drawing the geometry with the colour of the id `u_firstId` plus the per-vertex `a_element`, compiled on
+  /// first use and kept in `cache`
+  pub fn webgl_program_batched(
+    context: &web_sys::WebGl2RenderingContext,
+    cache: &ProgramCache,
+  ) -> Result<web_sys::WebGlProgram, Error> {
+    WebGlProgramBuilder::new()
+      .context(context)
+      .vertex_shader_source(Self::VERTEX_SHADER_SOURCE)
+      .fragment_shader_source(Self::FRAGMENT_SHADER_SOURCE)
+      .define("BATCHED")
+      .cache(cache, "picker")
+      .build()
   }
 
   const VERTEX_SHADER_SOURCE: &str = r#"
-  attribute vec4 a_position;
-  
-  // uniform mat4 u_viewProjection;
-  // uniform mat4 u_world;
+  #include <clipping_vertex>
+  #include <transforms>
+  #include <picking>
+
+  in vec4 a_position;
+  #ifdef INSTANCED
+  in vec4 a_instanceId;
+  #elif defined(BATCHED)
+  in uint a_element;
+  #endif
+
   uniform mat4 uMatrix;
-  
+  uniform vec4 u_id;
+  uniform highp uint u_firstId;
+
+  out vec4 v_id;
+
   void main() {
-    // Multiply the position by the matrix.
-    // gl_Position = u_viewProjection * u_world * a_position;
-    gl_Position = uMatrix * a_position;
+    vec4 position = worldPosition(a_position);
+    gl_Position = uMatrix * position;
+    v_clipPosition = position.xyz;
+  #ifdef INSTANCED
+    v_id = a_instanceId;
+  #elif defined(BATCHED)
+    v_id = idColor(u_firstId + a_element);
+  #else
+    v_id = u_id;
+  #endif
   }
   "#;
 
   const FRAGMENT_SHADER_SOURCE: &str = r#"
   precision mediump float;
-  
-  uniform vec4 u_id;
+  #include <clipping>
+
+  in vec4 v_id;
+
+  out vec4 fragColor;
 
   void main() {
-     gl_FragColor = u_id;
+     clip();
+     fragColor = v_id;
   }
   "#;
 }
+
+#[cfg(all(test, feature = "wasm"))]
+mod tests {
+  use super::*;
+
+  /// Bytes written to the framebuffer for the colour of the id `count`
+  fn id_bytes(count: usize) -> [u8; 4] {
+    Picker::id_color(count).map(|v| (v * 255.0).round() as u8)
+  }
+
+  #[test]
+  fn no_object_is_black() {
+    assert_eq!(id_bytes(0), [0, 0, 0, 0]);
+    assert_eq!(Picker::color_id([0, 0, 0, 0]), 0);
+  }
+
+  #[test]
+  fn ids_round_trip_through_colours() {
+    for count in [1, 254, 255, 256, 65_025, 1_000_000, 16_581_375, 16_777_216, 100_000_000] {
+      assert_eq!(Picker::color_id(id_bytes(count)), count);
+    }
+  }
+}
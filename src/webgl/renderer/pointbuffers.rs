@@ -0,0 +1,64 @@
+use super::*;
+
+/// GPU buffers of the data of a point cloud
+pub struct PointBuffers {
+  /// Positions, 3 values per point
+  pub positions: web_sys::WebGlBuffer,
+  /// RGBA colours, 4 values per point, if any
+  pub colors:    Option<web_sys::WebGlBuffer>,
+  /// Scalar values, 1 per point, if any
+  pub scalars:   Option<web_sys::WebGlBuffer>,
+  /// Point indices, 1 per point, created on first use by picking
+  pub indices:   Option<web_sys::WebGlBuffer>,
+}
+
+/// GPU buffers of the point clouds drawn, uploaded once and shared by the copies of a cloud. The buffers are
+/// deleted once all copies of their `PointData` are dropped
+#[derive(Default)]
+pub struct PointBufferCache {
+  buffers: std::collections::HashMap<String, (std::rc::Weak<pointcloud::PointData>, PointBuffers)>,
+}
+
+impl PointBufferCache {
+  /// Number of point clouds with GPU buffers
+  pub fn len(&self) -> usize { self.buffers.len() }
+
+  /// Buffers of `points`, uploaded on first use. The index buffer is created if `indexed`
+  pub fn buffers(&mut self,
+    context: &web_sys::WebGl2RenderingContext,
+    counters: &statistics::Counters,
+    points: &std::rc::Rc<pointcloud::PointData>,
+    indexed: bool,
+    ) -> Result<&PointBuffers, Error> {
+    if !self.buffers.contains_key(points.uid()) {
+      let buffers = PointBuffers {
+        positions: utils::upload(context, counters, points.positions())?,
+        colors:    if points.colors().is_empty() { None } else { Some(utils::upload(context, counters, points.colors())?) },
+        scalars:   if points.scalars().is_empty() { None } else { Some(utils::upload(context, counters, points.scalars())?) },
+        indices:   None,
+      };
+      self.buffers.insert(points.uid().to_string(), (std::rc::Rc::downgrade(points), buffers));
+    }
+    let (_, buffers) = self.buffers.get_mut(points.uid()).ok_or("Unable to retrieve point buffers...")?;
+    if indexed && buffers.indices.is_none() {
+      buffers.indices = Some(utils::upload_uint(context, counters, &(0..points.len().try_into()?).collect::<Vec<u32>>())?);
+    }
+    Ok(buffers)
+  }
+
+  /// Delete the GPU buffers no longer referenced
+  pub fn release_unused(&mut self, context: &web_sys::WebGl2RenderingContext) {
+    self.buffers.retain(|_, (data, buffers)| {
+      let used = data.strong_count() > 0;
+      if !used {
+        for buffer in [Some(&buffers.positions), buffers.colors.as_ref(), buffers.scalars.as_ref(), buffers.indices.as_ref()].into_iter().flatten() {
+          context.delete_buffer(Some(buffer));
+        }
+      }
+      used
+    });
+  }
+
+  /// Forget the GPU buffers of a lost context, without deleting them. They are uploaded again on next use
+  pub fn invalidate(&mut self) { self.buffers.clear(); }
+}
@@ -0,0 +1,335 @@
+use super::*;
+
+/// Value of a post-processing pass uniform
+#[derive(Clone, Debug, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub enum Uniform {
+  Int(i32),
+  Float(f32),
+  Vec2([f32; 2]),
+  Vec3([f32; 3]),
+  Vec4([f32; 4]),
+}
+
+impl Uniform {
+  /// Set the value on the uniform at `location` of the program in use
+  pub(super) fn set(&self, context: &web_sys::WebGl2RenderingContext, location: Option<&web_sys::WebGlUniformLocation>) {
+    match self {
+      Uniform::Int(v) => context.uniform1i(location, *v),
+      Uniform::Float(v) => context.uniform1f(location, *v),
+      Uniform::Vec2(v) => context.uniform2fv_with_f32_array(location, v),
+      Uniform::Vec3(v) => context.uniform3fv_with_f32_array(location, v),
+      Uniform::Vec4(v) => context.uniform4fv_with_f32_array(location, v),
+    }
+  }
+}
+
+/// Full screen pass of the post-processing pipeline. The fragment shader source, in GLSL ES 3.00 without version
+/// directive, defines `main` writing `fragColor`, may include the chunks of the shader library and has access to:
+/// - `v_uv`: texture coordinates of the fragment,
+/// - `u_color`: colour texture output by the previous pass, or the rendered scene for the first pass,
+/// - `u_depth`: depth texture of the rendered scene,
+/// - `u_texel`: size of a texel in texture coordinates,
+/// - the uniforms of the pass.
+#[derive(Clone, Debug, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct PostPass {
+  name: String,
+  fragment_shader_source: String,
+  uniforms: Vec<(String, Uniform)>,
+}
+
+impl PostPass {
+  /// Create a pass named `name` from the fragment shader source
+  pub fn new(name: &str, fragment_shader_source: &str) -> PostPass {
+    PostPass { name: name.to_string(), fragment_shader_source: fragment_shader_source.to_string(), uniforms: Vec::new() }
+  }
+
+  /// Set the value of a uniform, replacing any previous value
+  pub fn with_uniform(mut self, name: &str, value: Uniform) -> PostPass {
+    self.uniforms.retain(|(n, _)| n != name);
+    self.uniforms.push((name.to_string(), value));
+    self
+  }
+
+  /// Name of the pass
+  pub fn name(&self) -> &str { &self.name }
+
+  /// Exponential tone mapping with the given exposure
+  pub fn tone_mapping(exposure: f32) -> PostPass {
+    PostPass::new("tone_mapping", Self::TONE_MAPPING).with_uniform("u_exposure", Uniform::Float(exposure))
+  }
+
+  /// Darken the image towards its corners by `strength` in `[0, 1]`, from `radius` (relative to the half diagonal)
+  pub fn vignette(strength: f32, radius: f32) -> PostPass {
+    PostPass::new("vignette", Self::VIGNETTE)
+      .with_uniform("u_strength", Uniform::Float(strength))
+      .with_uniform("u_radius", Uniform::Float(radius))
+  }
+
+  /// Fast approximate anti-aliasing
+  pub fn fxaa() -> PostPass {
+    PostPass::new("fxaa", Self::FXAA)
+  }
+
+  /// Outline depth discontinuities larger than `threshold` with `color`
+  pub fn depth_outline(color: Color, threshold: f32) -> PostPass {
+    PostPass::new("depth_outline", Self::DEPTH_OUTLINE)
+      .with_uniform("u_outlineColor", Uniform::Vec4(color.as_array()))
+      .with_uniform("u_threshold", Uniform::Float(threshold))
+  }
+
+  const HEADER: &str = r#"
+     precision highp float;
+
+     in vec2 v_uv;
+
+     uniform sampler2D u_color;
+     uniform sampler2D u_depth;
+     uniform vec2 u_texel;
+
+     out vec4 fragColor;
+    "#;
+
+  const TONE_MAPPING: &str = r#"
+     uniform float u_exposure;
+     void main()
+     {
+       vec4 color = texture(u_color, v_uv);
+       fragColor = vec4(vec3(1.0) - exp(-color.rgb*u_exposure), color.a);
+     }
+    "#;
+
+  const VIGNETTE: &str = r#"
+     uniform float u_strength;
+     uniform float u_radius;
+     void main()
+     {
+       vec4 color = texture(u_color, v_uv);
+       float d = distance(v_uv, vec2(0.5))*1.41421356;
+       fragColor = vec4(color.rgb*(1.0 - u_strength*smoothstep(u_radius, 1.0, d)), color.a);
+     }
+    "#;
+
+  const FXAA: &str = r#"
+     #define u_texture u_color
+     #include <fxaa>
+
+     void main()
+     {
+       fragColor = fxaa(v_uv);
+     }
+    "#;
+
+  const DEPTH_OUTLINE: &str = r#"
+     uniform vec4 u_outlineColor;
+     uniform float u_threshold;
+     void main()
+     {
+       float d = texture(u_depth, v_uv).r;
+       float dx = abs(texture(u_depth, v_uv + vec2(u_texel.x, 0.0)).r - d) + abs(texture(u_depth, v_uv - vec2(u_texel.x, 0.0)).r - d);
+       float dy = abs(texture(u_depth, v_uv + vec2(0.0, u_texel.y)).r - d) + abs(texture(u_depth, v_uv - vec2(0.0, u_texel.y)).r - d);
+       vec4 color = texture(u_color, v_uv);
+       fragColor = (dx + dy > u_threshold) ? vec4(mix(color.rgb, u_outlineColor.rgb, u_outlineColor.a), max(color.a, u_outlineColor.a)) : color;
+     }
+    "#;
+}
+
+/// Ordered list of full screen passes applied to the rendered image
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct PostProcessing {
+  passes: Vec<PostPass>,
+}
+
+impl PostProcessing {
+  /// Append a pass, run after the existing ones
+  pub fn with_pass(mut self, pass: PostPass) -> PostProcessing { self.passes.push(pass); self }
+
+  /// Passes, in the order they are run
+  pub fn passes(&self) -> &[PostPass] { &self.passes }
+
+  /// Whether no pass is applied
+  pub fn is_empty(&self) -> bool { self.passes.is_empty() }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+impl PostProcessing {
+  /// Create an empty pipeline
+  pub fn new() -> PostProcessing { PostProcessing::default() }
+
+  /// Append an exponential tone mapping pass
+  pub fn with_tone_mapping(self, exposure: f32) -> PostProcessing { self.with_pass(PostPass::tone_mapping(exposure)) }
+
+  /// Append a vignette pass, darkening the corners by `strength` in `[0, 1]` from `radius` (relative to the half diagonal)
+  pub fn with_vignette(self, strength: f32, radius: f32) -> PostProcessing { self.with_pass(PostPass::vignette(strength, radius)) }
+
+  /// Append a fast approximate anti-aliasing pass
+  pub fn with_fxaa(self) -> PostProcessing { self.with_pass(PostPass::fxaa()) }
+
+  /// Append a pass outlining depth discontinuities larger than `threshold` with `color`
+  pub fn with_depth_outline(self, color: Color, threshold: f32) -> PostProcessing { self.with_pass(PostPass::depth_outline(color, threshold)) }
+
+  /// Remove the passes named `name`
+  pub fn without_pass(mut self, name: &str) -> PostProcessing { self.passes.retain(|p| p.name != name); self }
+
+  /// Number of passes
+  pub fn n_passes(&self) -> usize { self.passes.len() }
+
+  /// Expose clone functionality to JavaScript
+  pub fn clone(&self) -> PostProcessing { Clone::clone(self) }
+
+  /// Convert the pipeline to json
+  pub fn to_json(&self) -> Result<String, JsError> {
+    Ok(serde_json::to_string(&self).map_err(|e| format!("{e}"))?)
+  }
+
+  /// Create a pipeline from json
+  pub fn from_json(json: &str) -> Result<PostProcessing, JsError> {
+    Ok(serde_json::from_str(json).map_err(|e| format!("{e}"))?)
+  }
+}
+
+/// Colour texture and the framebuffer it is attached to
+struct ColorTarget {
+  framebuffer: web_sys::WebGlFramebuffer,
+  color:       web_sys::WebGlTexture,
+}
+
+impl ColorTarget {
+  fn new(context: &web_sys::WebGl2RenderingContext, width: i32, height: i32, depth: Option<&web_sys::WebGlTexture>) -> Result<ColorTarget, Error> {
+    let color = texture(context, width, height, web_sys::WebGl2RenderingContext::RGBA8, web_sys::WebGl2RenderingContext::RGBA,
+      web_sys::WebGl2RenderingContext::UNSIGNED_BYTE, web_sys::WebGl2RenderingContext::LINEAR)?;
+    let framebuffer = context.create_framebuffer().ok_or("Unable to create frame buffer")?;
+    context.bind_framebuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER, Some(&framebuffer));
+    context.framebuffer_texture_2d(web_sys::WebGl2RenderingContext::FRAMEBUFFER,
+      web_sys::WebGl2RenderingContext::COLOR_ATTACHMENT0,
+      web_sys::WebGl2RenderingContext::TEXTURE_2D,
+      Some(&color),
+      0,       // level
+    );
+    if let Some(depth) = depth {
+      context.framebuffer_texture_2d(web_sys::WebGl2RenderingContext::FRAMEBUFFER,
+        web_sys::WebGl2RenderingContext::DEPTH_STENCIL_ATTACHMENT,
+        web_sys::WebGl2RenderingContext::TEXTURE_2D,
+        Some(depth),
+        0,       // level
+      );
+    }
+    let status = context.check_framebuffer_status(web_sys::WebGl2RenderingContext::FRAMEBUFFER);
+    context.bind_framebuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER, None);
+    if status != web_sys::WebGl2RenderingContext::FRAMEBUFFER_COMPLETE {
+      return Err(format!("Post-processing frame buffer incomplete: {}", status).into());
+    }
+    Ok(ColorTarget { framebuffer, color })
+  }
+}
+
+fn texture(context: &web_sys::WebGl2RenderingContext, width: i32, height: i32, internal_format: u32, format: u32, type_: u32, filter: u32) -> Result<web_sys::WebGlTexture, Error> {
+  let texture = context.create_texture().ok_or("Unable to create texture")?;
+  context.bind_texture(web_sys::WebGl2RenderingContext::TEXTURE_2D, Some(&texture));
+  context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_MIN_FILTER, filter.try_into()?);
+  context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_MAG_FILTER, filter.try_into()?);
+  context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_WRAP_S, web_sys::WebGl2RenderingContext::CLAMP_TO_EDGE.try_into()?);
+  context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_WRAP_T, web_sys::WebGl2RenderingContext::CLAMP_TO_EDGE.try_into()?);
+  context.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+    web_sys::WebGl2RenderingContext::TEXTURE_2D, 0, internal_format.try_into()?, width, height, 0, format, type_, None,
+  )?;
+  Ok(texture)
+}
+
+/// Scene colour and depth textures the frame is rendered into, and the ping-pong targets of the passes
+pub struct PostTargets {
+  width:  i32,
+  height: i32,
+  scene:  ColorTarget,
+  depth:  web_sys::WebGlTexture,
+  ping:   ColorTarget,
+  pong:   ColorTarget,
+}
+
+impl PostTargets {
+  /// Create the targets
+  pub fn new(context: &web_sys::WebGl2RenderingContext, width: i32, height: i32) -> Result<PostTargets, Error> {
+    // Format and type of `DEPTH_FORMAT`
+    let depth = texture(context, width, height, DEPTH_FORMAT, web_sys::WebGl2RenderingContext::DEPTH_STENCIL,
+      web_sys::WebGl2RenderingContext::UNSIGNED_INT_24_8, web_sys::WebGl2RenderingContext::NEAREST)?;
+    let scene = ColorTarget::new(context, width, height, Some(&depth))?;
+    let ping = ColorTarget::new(context, width, height, None)?;
+    let pong = ColorTarget::new(context, width, height, None)?;
+    Ok(PostTargets { width, height, scene, depth, ping, pong })
+  }
+
+  /// Whether the targets match the size provided
+  pub fn has_size(&self, width: i32, height: i32) -> bool { self.width == width && self.height == height }
+
+  /// Framebuffer the scene is rendered into
+  pub fn framebuffer(&self) -> &web_sys::WebGlFramebuffer { &self.scene.framebuffer }
+
+  /// Copy the depth buffer of `framebuffer`, the scene was rendered into, to the scene depth texture. The depth
+  /// buffer must have the internal format `DEPTH_FORMAT`
+  pub fn copy_depth(&self, context: &web_sys::WebGl2RenderingContext, framebuffer: &web_sys::WebGlFramebuffer) {
+    context.bind_framebuffer(web_sys::WebGl2RenderingContext::READ_FRAMEBUFFER, Some(framebuffer));
+    context.bind_framebuffer(web_sys::WebGl2RenderingContext::DRAW_FRAMEBUFFER, Some(&self.scene.framebuffer));
+    context.blit_framebuffer(0, 0, self.width, self.height, 0, 0, self.width, self.height,
+      web_sys::WebGl2RenderingContext::DEPTH_BUFFER_BIT, web_sys::WebGl2RenderingContext::NEAREST);
+    context.bind_framebuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER, None);
+  }
+
+  /// Run the `passes` in order, each reading the colour output by the previous one, the last drawing into the
+  /// `output` framebuffer - the default framebuffer if `None`. Programs are compiled on first use and cached by source
+  pub fn run(&self,
+    context: &web_sys::WebGl2RenderingContext,
+    counters: &statistics::Counters,
+    programs: &ProgramCache,
+    passes: &[PostPass],
+    output: Option<&web_sys::WebGlFramebuffer>,
+    ) -> Result<(), Error> {
+    context.disable(web_sys::WebGl2RenderingContext::DEPTH_TEST);
+    context.disable(web_sys::WebGl2RenderingContext::BLEND);
+    context.viewport(0, 0, self.width, self.height);
+    let mut source = &self.scene.color;
+    for (i, pass) in passes.iter().enumerate() {
+      let target = if i + 1 == passes.len() { None } else if i % 2 == 0 { Some(&self.ping) } else { Some(&self.pong) };
+      context.bind_framebuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER, target.map(|t| &t.framebuffer).or(output));
+
+      let fragment_shader_source = format!("{}{}", PostPass::HEADER, pass.fragment_shader_source);
+      let program = &WebGlProgramBuilder::new()
+        .context(context)
+        .vertex_shader_source(programcomposite::ProgramComposite::VERTEX_SHADER_SOURCE)
+        .fragment_shader_source(&fragment_shader_source)
+        .cache(programs, &fragment_shader_source)
+        .build()?;
+      counters.use_program(context, program);
+
+      context.active_texture(web_sys::WebGl2RenderingContext::TEXTURE0);
+      context.bind_texture(web_sys::WebGl2RenderingContext::TEXTURE_2D, Some(source));
+      context.uniform1i(context.get_uniform_location(program, "u_color").as_ref(), 0);
+      context.active_texture(web_sys::WebGl2RenderingContext::TEXTURE1);
+      context.bind_texture(web_sys::WebGl2RenderingContext::TEXTURE_2D, Some(&self.depth));
+      context.uniform1i(context.get_uniform_location(program, "u_depth").as_ref(), 1);
+      context.active_texture(web_sys::WebGl2RenderingContext::TEXTURE0);
+      context.uniform2f(context.get_uniform_location(program, "u_texel").as_ref(), 1.0 / self.width as f32, 1.0 / self.height as f32);
+      for (name, value) in pass.uniforms.iter() {
+        value.set(context, context.get_uniform_location(program, name).as_ref());
+      }
+
+      let positions: Vec<f32> = vec!(
+        -1.0, -1.0, 0.0,   1.0, -1.0, 0.0,   1.0, 1.0, 0.0,
+        -1.0, -1.0, 0.0,   1.0,  1.0, 0.0,  -1.0, 1.0, 0.0,
+      );
+      utils::bind(context, counters, program, "a_position", &positions)?;
+      context.draw_arrays(web_sys::WebGl2RenderingContext::TRIANGLES, 0, 6);
+      counters.record_draw(web_sys::WebGl2RenderingContext::TRIANGLES, 6, 1);
+
+      if let Some(target) = target { source = &target.color; }
+    }
+    // Unbind the depth texture so that it is not sampled while attached in the next frame
+    context.active_texture(web_sys::WebGl2RenderingContext::TEXTURE1);
+    context.bind_texture(web_sys::WebGl2RenderingContext::TEXTURE_2D, None);
+    context.active_texture(web_sys::WebGl2RenderingContext::TEXTURE0);
+    context.enable(web_sys::WebGl2RenderingContext::DEPTH_TEST);
+    Ok(())
+  }
+}
@@ -0,0 +1,47 @@
+use super::*;
+
+pub struct ProgramBackground { }
+
+impl ProgramBackground {
+  pub fn webgl_program(
+    context: &web_sys::WebGl2RenderingContext,
+    ) -> Result<web_sys::WebGlProgram, Error> {
+    WebGlProgramBuilder::new()
+      .context(context)
+      .vertex_shader_source(Self::VERTEX_SHADER_SOURCE)
+      .fragment_shader_source(Self::FRAGMENT_SHADER_SOURCE)
+      .build()
+  }
+
+  const VERTEX_SHADER_SOURCE: &str = r#"
+     in vec4 a_position;
+     out vec2 v_uv;
+     void main()
+     {
+        v_uv = 0.5*(a_position.xy + 1.0);
+        gl_Position = vec4(a_position.xy, 0.0, 1.0);
+     }
+    "#;
+
+  const FRAGMENT_SHADER_SOURCE: &str = r#"
+     precision mediump float;
+
+     in vec2 v_uv;
+
+     uniform int u_mode;
+     uniform vec4 u_top;
+     uniform vec4 u_bottom;
+     uniform sampler2D u_image;
+
+     out vec4 fragColor;
+
+     void main()
+     {
+       if (u_mode == 1) {
+         fragColor = texture(u_image, vec2(v_uv.x, 1.0 - v_uv.y));
+       } else {
+         fragColor = mix(u_bottom, u_top, v_uv.y);
+       }
+     }
+    "#;
+}
@@ -0,0 +1,113 @@
+use super::*;
+
+/// Operation applied by the composite program, matching `u_mode` in the fragment shader
+#[derive(Clone, Copy, PartialEq)]
+pub enum CompositeMode {
+  /// Copy the texture
+  Copy = 0,
+  /// Resolve the weighted blended accumulation and revealage textures
+  WeightedBlended = 1,
+  /// Copy the texture with fast approximate anti-aliasing
+  Fxaa = 2,
+}
+
+/// Full screen pass combining textures onto the current framebuffer
+pub struct ProgramComposite { }
+
+impl ProgramComposite {
+  pub fn webgl_program(
+    context: &web_sys::WebGl2RenderingContext,
+    ) -> Result<web_sys::WebGlProgram, Error> {
+    WebGlProgramBuilder::new()
+      .context(context)
+      .vertex_shader_source(Self::VERTEX_SHADER_SOURCE)
+      .fragment_shader_source(Self::FRAGMENT_SHADER_SOURCE)
+      .build()
+  }
+
+  /// Draw a full screen quad sampling `texture` (and `second` if required by the mode) of size `(width, height)`.
+  /// Depth test is disabled on return.
+  pub fn draw(
+    context: &web_sys::WebGl2RenderingContext,
+    counters: &statistics::Counters,
+    program: &std::rc::Rc<std::cell::RefCell<Option<web_sys::WebGlProgram>>>,
+    mode: CompositeMode,
+    texture: &web_sys::WebGlTexture,
+    second: Option<&web_sys::WebGlTexture>,
+    size: (i32, i32),
+    ) -> Result<(), Error> {
+    if program.borrow().is_none() {
+      *program.borrow_mut() = Some(Self::webgl_program(context)?);
+    }
+    let program = program.borrow();
+    let program = program.as_ref().ok_or("Unable to retrieve composite program...")?;
+    counters.use_program(context, program);
+
+    context.active_texture(web_sys::WebGl2RenderingContext::TEXTURE0);
+    context.bind_texture(web_sys::WebGl2RenderingContext::TEXTURE_2D, Some(texture));
+    let u_texture = context.get_uniform_location(program, "u_texture");
+    context.uniform1i(u_texture.as_ref(), 0);
+    if let Some(second) = second {
+      context.active_texture(web_sys::WebGl2RenderingContext::TEXTURE1);
+      context.bind_texture(web_sys::WebGl2RenderingContext::TEXTURE_2D, Some(second));
+      let u_second = context.get_uniform_location(program, "u_second");
+      context.uniform1i(u_second.as_ref(), 1);
+      context.active_texture(web_sys::WebGl2RenderingContext::TEXTURE0);
+    }
+    let u_mode = context.get_uniform_location(program, "u_mode");
+    context.uniform1i(u_mode.as_ref(), mode as i32);
+    let u_texel = context.get_uniform_location(program, "u_texel");
+    context.uniform2f(u_texel.as_ref(), 1.0 / size.0 as f32, 1.0 / size.1 as f32);
+
+    let positions: Vec<f32> = vec!(
+      -1.0, -1.0, 0.0,   1.0, -1.0, 0.0,   1.0, 1.0, 0.0,
+      -1.0, -1.0, 0.0,   1.0,  1.0, 0.0,  -1.0, 1.0, 0.0,
+    );
+    utils::bind(context, counters, program, "a_position", &positions)?;
+
+    context.disable(web_sys::WebGl2RenderingContext::DEPTH_TEST);
+    context.draw_arrays(web_sys::WebGl2RenderingContext::TRIANGLES, 0, 6);
+    counters.record_draw(web_sys::WebGl2RenderingContext::TRIANGLES, 6, 1);
+    Ok(())
+  }
+
+  /// Full screen quad vertex shader, providing `v_uv` texture coordinates
+  pub const VERTEX_SHADER_SOURCE: &str = r#"
+     in vec4 a_position;
+     out vec2 v_uv;
+     void main()
+     {
+        v_uv = 0.5*(a_position.xy + 1.0);
+        gl_Position = vec4(a_position.xy, 0.0, 1.0);
+     }
+    "#;
+
+  const FRAGMENT_SHADER_SOURCE: &str = r#"
+     precision highp float;
+
+     in vec2 v_uv;
+
+     uniform int u_mode;
+     uniform sampler2D u_texture;
+     uniform sampler2D u_second;
+     uniform vec2 u_texel;
+
+     #include <fxaa>
+
+     out vec4 fragColor;
+
+     void main()
+     {
+       if (u_mode == 2) {
+         fragColor = fxaa(v_uv);
+       } else if (u_mode == 1) {
+         float revealage = texture(u_second, v_uv).r;
+         if (revealage >= 1.0) { discard; }
+         vec4 accum = texture(u_texture, v_uv);
+         fragColor = vec4(accum.rgb/max(accum.a, 1e-5), 1.0 - revealage);
+       } else {
+         fragColor = texture(u_texture, v_uv);
+       }
+     }
+    "#;
+}
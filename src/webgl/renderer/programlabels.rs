@@ -0,0 +1,201 @@
+use super::*;
+
+/// Screen-space quads of a label, in pixels relative to the projected anchor, with y upwards
+struct Quads {
+  offsets: Vec<f32>,
+  uvs:     Vec<f32>,
+  colors:  Vec<f32>,
+}
+
+impl Quads {
+  /// Texture coordinates of quads drawn with a solid colour
+  const SOLID: [f32; 2] = [-1.0, -1.0];
+
+  /// Add the quad of corners `[bottom left, bottom right, top right, top left]`
+  fn push(&mut self, corners: [[f32; 2]; 4], uvs: [[f32; 2]; 4], color: [f32; 4]) {
+    for i in [0, 1, 2, 0, 2, 3] {
+      self.offsets.extend(corners[i]);
+      self.uvs.extend(uvs[i]);
+      self.colors.extend(color);
+    }
+  }
+
+  /// Add the axis aligned rectangle `[left, bottom, right, top]`
+  fn push_rect(&mut self, rect: [f32; 4], uv: [f32; 4], color: [f32; 4]) {
+    let [x0, y0, x1, y1] = rect;
+    let [u0, v0, u1, v1] = uv;
+    self.push([[x0, y0], [x1, y0], [x1, y1], [x0, y1]], [[u0, v0], [u1, v0], [u1, v1], [u0, v1]], color);
+  }
+}
+
+/// Program drawing labels over the scene: text from the glyph atlas, background box and leader line
+pub struct ProgramLabels { }
+
+impl ProgramLabels {
+  pub fn webgl_program(
+    context: &web_sys::WebGl2RenderingContext,
+    ) -> Result<web_sys::WebGlProgram, Error> {
+    WebGlProgramBuilder::new()
+      .context(context)
+      .vertex_shader_source(Self::VERTEX_SHADER_SOURCE)
+      .fragment_shader_source(Self::FRAGMENT_SHADER_SOURCE)
+      .build()
+  }
+
+  /// Draw the label `info`, transformed to clip space by `matrix` in a viewport of `resolution` pixels, hidden
+  /// when its anchor is clipped by the plane `equations`.
+  /// When `id` is provided, only the label box is drawn, with the `id` colour, for picking.
+  /// Depth test is disabled on return.
+  #[allow(clippy::too_many_arguments)]
+  pub fn draw(
+    context: &web_sys::WebGl2RenderingContext,
+    counters: &statistics::Counters,
+    program: &std::rc::Rc<std::cell::RefCell<Option<web_sys::WebGlProgram>>>,
+    atlas: &std::rc::Rc<std::cell::RefCell<Option<glyphatlas::GlyphAtlas>>>,
+    info: &Info,
+    matrix: &nalgebra::Matrix4<f32>,
+    resolution: (i32, i32),
+    id: Option<[f32; 4]>,
+    equations: &[[f32; 4]],
+    ) -> Result<(), Error> {
+    let Info::Label { uid: _, anchor, text, style, material } = *info else {
+      return Err("Expected label".into());
+    };
+    if program.borrow().is_none() {
+      *program.borrow_mut() = Some(Self::webgl_program(context)?);
+    }
+    if atlas.borrow().is_none() {
+      *atlas.borrow_mut() = Some(glyphatlas::GlyphAtlas::new(context)?);
+    }
+    let mut atlas = atlas.borrow_mut();
+    let atlas = atlas.as_mut().ok_or("Unable to retrieve glyph atlas...")?;
+    atlas.prepare(text)?;
+
+    // Text block centred on the offset
+    let scale = style.font_size / glyphatlas::GlyphAtlas::FONT_SIZE;
+    let line_height = glyphatlas::GlyphAtlas::CELL_HEIGHT * scale;
+    let lines = text.split('\n').collect::<Vec<_>>();
+    let text_width = lines.iter()
+      .map(|l| l.chars().filter_map(|c| atlas.glyph(c)).map(|g| g.advance * scale).sum::<f32>())
+      .fold(0.0, f32::max);
+    let width = text_width + 2.0*style.padding;
+    let height = lines.len() as f32 * line_height + 2.0*style.padding;
+    let (left, top) = (style.offset.x - 0.5*width, style.offset.y + 0.5*height);
+    let rect = [left, top - height, left + width, top];
+
+    let mut quads = Quads { offsets: Vec::new(), uvs: Vec::new(), colors: Vec::new() };
+    let solid = [Quads::SOLID; 4];
+    match id {
+      Some(id) => quads.push_rect(rect, [-1.0; 4], id),
+      None => {
+        // Leader line from the anchor to the nearest point of the box
+        let end = nalgebra::Vector2::new(0.0f32.clamp(rect[0], rect[2]), 0.0f32.clamp(rect[1], rect[3]));
+        if style.leader && end.norm() > 0.0 {
+          let normal = 0.5*nalgebra::Vector2::new(-end.y, end.x).normalize();
+          let corners = [-normal, end - normal, end + normal, normal].map(|c| [c.x, c.y]);
+          quads.push(corners, solid, material.line_color().as_array());
+        }
+        if let Some(background) = style.background {
+          quads.push_rect(rect, [-1.0; 4], background.as_array());
+        }
+        let size = glyphatlas::GlyphAtlas::SIZE as f32;
+        let color = material.surface_color().as_array();
+        for (i, line) in lines.iter().enumerate() {
+          let mut pen = left + style.padding;
+          let y1 = top - style.padding - i as f32 * line_height;
+          for glyph in line.chars().filter_map(|c| atlas.glyph(c)) {
+            let x0 = pen - glyphatlas::GlyphAtlas::PADDING * scale;
+            quads.push_rect([x0, y1 - line_height, x0 + glyph.width * scale, y1],
+              [glyph.x / size, (glyph.y + glyphatlas::GlyphAtlas::CELL_HEIGHT) / size, (glyph.x + glyph.width) / size, glyph.y / size],
+              color);
+            pen += glyph.advance * scale;
+          }
+        }
+      },
+    }
+
+    let program = program.borrow();
+    let program = program.as_ref().ok_or("Unable to retrieve program to draw labels...")?;
+    counters.use_program(context, program);
+
+    let u_matrix = context.get_uniform_location(program, "uMatrix");
+    context.uniform_matrix4fv_with_f32_array(u_matrix.as_ref(), false, matrix.as_slice());
+    let u_anchor = context.get_uniform_location(program, "u_anchor");
+    context.uniform3f(u_anchor.as_ref(), anchor.x, anchor.y, anchor.z);
+    let u_resolution = context.get_uniform_location(program, "u_resolution");
+    context.uniform2f(u_resolution.as_ref(), resolution.0 as f32, resolution.1 as f32);
+    clipping::set_uniforms(context, program, equations);
+    context.active_texture(web_sys::WebGl2RenderingContext::TEXTURE0);
+    atlas.bind(context)?;
+    let u_atlas = context.get_uniform_location(program, "u_atlas");
+    context.uniform1i(u_atlas.as_ref(), 0);
+
+    utils::bind_with_size(context, counters, program, "a_offset", &quads.offsets, 2)?;
+    utils::bind_with_size(context, counters, program, "a_uv", &quads.uvs, 2)?;
+    utils::bind_with_size(context, counters, program, "a_color", &quads.colors, 4)?;
+
+    let cull_face = context.is_enabled(web_sys::WebGl2RenderingContext::CULL_FACE);
+    context.disable(web_sys::WebGl2RenderingContext::CULL_FACE);
+    context.disable(web_sys::WebGl2RenderingContext::DEPTH_TEST);
+    if id.is_none() {
+      context.enable(web_sys::WebGl2RenderingContext::BLEND);
+      context.blend_func(web_sys::WebGl2RenderingContext::SRC_ALPHA, web_sys::WebGl2RenderingContext::ONE_MINUS_SRC_ALPHA);
+    }
+    let result = utils::draw(context, counters, web_sys::WebGl2RenderingContext::TRIANGLES, quads.offsets.len() / 2, None);
+    if id.is_none() { context.disable(web_sys::WebGl2RenderingContext::BLEND); }
+    if cull_face { context.enable(web_sys::WebGl2RenderingContext::CULL_FACE); }
+    result
+  }
+
+  const VERTEX_SHADER_SOURCE: &str = r#"
+     #include <clipping_vertex>
+
+     in vec2 a_offset;
+     in vec2 a_uv;
+     in vec4 a_color;
+
+     uniform mat4 uMatrix;
+     uniform vec3 u_anchor;
+     uniform vec2 u_resolution;
+
+     out vec2 v_uv;
+     out vec4 v_color;
+
+     void main()
+     {
+        vec4 anchor = uMatrix*vec4(u_anchor, 1.0);
+        // Anchor snapped to the pixel grid so that text stays sharp
+        vec2 pixel = floor((0.5*anchor.xy/anchor.w + 0.5)*u_resolution + 0.5);
+        vec2 position = 2.0*(pixel + a_offset)/u_resolution - 1.0;
+        // Hidden when the anchor is behind the camera
+        gl_Position = anchor.w > 0.0 ? vec4(position, 0.0, 1.0) : vec4(2.0, 2.0, 2.0, 1.0);
+        v_uv = a_uv;
+        v_color = a_color;
+        v_clipPosition = u_anchor;
+     }
+    "#;
+
+  const FRAGMENT_SHADER_SOURCE: &str = r#"
+     precision mediump float;
+     #include <clipping>
+
+     uniform sampler2D u_atlas;
+
+     in vec2 v_uv;
+     in vec4 v_color;
+
+     out vec4 fragColor;
+
+     void main()
+     {
+       clip();
+       if (v_uv.x < 0.0) {
+         fragColor = v_color;
+       } else {
+         float coverage = texture(u_atlas, v_uv).a;
+         if (coverage == 0.0) { discard; }
+         fragColor = vec4(v_color.rgb, v_color.a*coverage);
+       }
+     }
+    "#;
+}
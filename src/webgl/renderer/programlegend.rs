@@ -0,0 +1,116 @@
+use super::*;
+
+/// Program drawing the background box, gradient bar and tick marks of a `ColorLegend`. The texts are drawn
+/// with the `ProgramLabels`
+pub struct ProgramLegend { }
+
+impl ProgramLegend {
+  /// Value of the quads drawn with a solid colour
+  const SOLID: f32 = -1.0;
+
+  pub fn webgl_program(
+    context: &web_sys::WebGl2RenderingContext,
+    ) -> Result<web_sys::WebGlProgram, Error> {
+    WebGlProgramBuilder::new()
+      .context(context)
+      .vertex_shader_source(Self::VERTEX_SHADER_SOURCE)
+      .fragment_shader_source(Self::FRAGMENT_SHADER_SOURCE)
+      .define_value("LUT_SIZE", &format!("{}.0", Colormap::LUT_SIZE))
+      .build()
+  }
+
+  /// Draw the quads of the `layout` of `legend`, transformed from image pixels to clip space by `matrix`.
+  /// Depth test is disabled on return.
+  pub fn draw(
+    context: &web_sys::WebGl2RenderingContext,
+    counters: &statistics::Counters,
+    program: &std::rc::Rc<std::cell::RefCell<Option<web_sys::WebGlProgram>>>,
+    textures: &mut textures::TextureCache,
+    legend: &ColorLegend,
+    layout: &colorlegend::LegendLayout,
+    matrix: &nalgebra::Matrix4<f32>,
+    ) -> Result<(), Error> {
+    if program.borrow().is_none() {
+      *program.borrow_mut() = Some(Self::webgl_program(context)?);
+    }
+    let program = program.borrow();
+    let program = program.as_ref().ok_or("Unable to retrieve legend program...")?;
+    counters.use_program(context, program);
+
+    let mut positions = Vec::new();
+    let mut values = Vec::new();
+    let mut colors = Vec::new();
+    let mut push = |[x0, y0, x1, y1]: [f32; 4], value: [f32; 4], color: [f32; 4]| {
+      // Corners bottom left, bottom right, top right, top left
+      let corners = [[x0, y0], [x1, y0], [x1, y1], [x0, y1]];
+      for i in [0, 1, 2, 0, 2, 3] {
+        positions.extend(corners[i]);
+        values.push(value[i]);
+        colors.extend(color);
+      }
+    };
+    let text_color = legend.text_color().as_array();
+    if let Some(background) = legend.background() { push(layout.frame, [Self::SOLID; 4], background.as_array()); }
+    let [x0, y0, x1, y1] = layout.bar;
+    push([x0 - 1.0, y0 - 1.0, x1 + 1.0, y1 + 1.0], [Self::SOLID; 4], text_color);
+    let gradient = if x1 - x0 > y1 - y0 { [0.0, 1.0, 1.0, 0.0] } else { [0.0, 0.0, 1.0, 1.0] };
+    push(layout.bar, gradient, text_color);
+    for tick in layout.ticks.iter() { push(*tick, [Self::SOLID; 4], text_color); }
+
+    let u_matrix = context.get_uniform_location(program, "uMatrix");
+    context.uniform_matrix4fv_with_f32_array(u_matrix.as_ref(), false, matrix.as_slice());
+    textures.bind_colormap(context, legend.style().colormap())?;
+    let u_colormap = context.get_uniform_location(program, "u_colormap");
+    context.uniform1i(u_colormap.as_ref(), 0);
+    let u_bands = context.get_uniform_location(program, "u_bands");
+    context.uniform1f(u_bands.as_ref(), legend.style().bands() as f32);
+
+    utils::bind_with_size(context, counters, program, "a_position", &positions, 2)?;
+    utils::bind_with_size(context, counters, program, "a_value", &values, 1)?;
+    utils::bind_with_size(context, counters, program, "a_color", &colors, 4)?;
+
+    let cull_face = context.is_enabled(web_sys::WebGl2RenderingContext::CULL_FACE);
+    context.disable(web_sys::WebGl2RenderingContext::CULL_FACE);
+    context.disable(web_sys::WebGl2RenderingContext::DEPTH_TEST);
+    context.enable(web_sys::WebGl2RenderingContext::BLEND);
+    context.blend_func(web_sys::WebGl2RenderingContext::SRC_ALPHA, web_sys::WebGl2RenderingContext::ONE_MINUS_SRC_ALPHA);
+    let result = utils::draw(context, counters, web_sys::WebGl2RenderingContext::TRIANGLES, positions.len() / 2, None);
+    context.disable(web_sys::WebGl2RenderingContext::BLEND);
+    if cull_face { context.enable(web_sys::WebGl2RenderingContext::CULL_FACE); }
+    result
+  }
+
+  const VERTEX_SHADER_SOURCE: &str = r#"
+     in vec2 a_position;
+     in float a_value;
+     in vec4 a_color;
+
+     uniform mat4 uMatrix;
+
+     out float v_value;
+     out vec4 v_color;
+
+     void main()
+     {
+        gl_Position = uMatrix*vec4(a_position, 0.0, 1.0);
+        v_value = a_value;
+        v_color = a_color;
+     }
+    "#;
+
+  const FRAGMENT_SHADER_SOURCE: &str = r#"
+     precision mediump float;
+
+     #include <colormap>
+
+     in float v_value;
+     in vec4 v_color;
+
+     out vec4 fragColor;
+
+     void main()
+     {
+       fragColor = v_value < 0.0 ? v_color : vec4(colormap(clamp(v_value, 0.0, 1.0)), 1.0);
+     }
+    "#;
+}
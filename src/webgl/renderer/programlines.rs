@@ -14,19 +14,27 @@ impl ProgramLines {
   }
 
   const VERTEX_SHADER_SOURCE: &str = r#"
-     attribute vec4 vPosition;
+     #include <clipping_vertex>
+
+     in vec4 vPosition;
      uniform mat4 uMatrix;
      void main()
      {
         gl_Position = uMatrix*vPosition;
+        v_clipPosition = vPosition.xyz;
      }
     "#;
 
   const FRAGMENT_SHADER_SOURCE: &str = r#"
     precision mediump float;
+     #include <clipping>
+
+     uniform vec4 u_color;
+     out vec4 fragColor;
      void main()
      {
-       gl_FragColor = vec4(0.9, 0.9, 0.9, 1);
+       clip();
+       fragColor = u_color;
      }
     "#;
 
@@ -0,0 +1,85 @@
+use super::*;
+
+/// Full screen pass drawing the outline of the highlight mask onto the current framebuffer
+pub struct ProgramOutline { }
+
+impl ProgramOutline {
+  pub fn webgl_program(
+    context: &web_sys::WebGl2RenderingContext,
+    ) -> Result<web_sys::WebGlProgram, Error> {
+    WebGlProgramBuilder::new()
+      .context(context)
+      .vertex_shader_source(programcomposite::ProgramComposite::VERTEX_SHADER_SOURCE)
+      .fragment_shader_source(Self::FRAGMENT_SHADER_SOURCE)
+      .define_value("MAX_OUTLINE_WIDTH", &Highlight::MAX_OUTLINE_WIDTH.to_string())
+      .build()
+  }
+
+  /// Draw the pixels within `width` pixels of the `mask` texture of size `(width, height)`, in the colour of the mask.
+  /// Depth test is disabled on return.
+  pub fn draw(
+    context: &web_sys::WebGl2RenderingContext,
+    counters: &statistics::Counters,
+    program: &std::rc::Rc<std::cell::RefCell<Option<web_sys::WebGlProgram>>>,
+    mask: &web_sys::WebGlTexture,
+    size: (i32, i32),
+    width: u32,
+    ) -> Result<(), Error> {
+    if program.borrow().is_none() {
+      *program.borrow_mut() = Some(Self::webgl_program(context)?);
+    }
+    let program = program.borrow();
+    let program = program.as_ref().ok_or("Unable to retrieve outline program...")?;
+    counters.use_program(context, program);
+
+    context.active_texture(web_sys::WebGl2RenderingContext::TEXTURE0);
+    context.bind_texture(web_sys::WebGl2RenderingContext::TEXTURE_2D, Some(mask));
+    let u_mask = context.get_uniform_location(program, "u_mask");
+    context.uniform1i(u_mask.as_ref(), 0);
+    let u_texel = context.get_uniform_location(program, "u_texel");
+    context.uniform2f(u_texel.as_ref(), 1.0 / size.0 as f32, 1.0 / size.1 as f32);
+    let u_width = context.get_uniform_location(program, "u_width");
+    context.uniform1i(u_width.as_ref(), width as i32);
+
+    let positions: Vec<f32> = vec!(
+      -1.0, -1.0, 0.0,   1.0, -1.0, 0.0,   1.0, 1.0, 0.0,
+      -1.0, -1.0, 0.0,   1.0,  1.0, 0.0,  -1.0, 1.0, 0.0,
+    );
+    utils::bind(context, counters, program, "a_position", &positions)?;
+
+    context.disable(web_sys::WebGl2RenderingContext::DEPTH_TEST);
+    context.enable(web_sys::WebGl2RenderingContext::BLEND);
+    context.blend_func(web_sys::WebGl2RenderingContext::SRC_ALPHA, web_sys::WebGl2RenderingContext::ONE_MINUS_SRC_ALPHA);
+    context.draw_arrays(web_sys::WebGl2RenderingContext::TRIANGLES, 0, 6);
+    counters.record_draw(web_sys::WebGl2RenderingContext::TRIANGLES, 6, 1);
+    context.disable(web_sys::WebGl2RenderingContext::BLEND);
+    Ok(())
+  }
+
+  const FRAGMENT_SHADER_SOURCE: &str = r#"
+     precision mediump float;
+
+     in vec2 v_uv;
+
+     uniform sampler2D u_mask;
+     uniform vec2 u_texel;
+     uniform int u_width;
+
+     out vec4 fragColor;
+
+     void main()
+     {
+       if (texture(u_mask, v_uv).a > 0.0) { discard; }
+       vec4 color = vec4(0.0);
+       for (int i = -MAX_OUTLINE_WIDTH; i <= MAX_OUTLINE_WIDTH; i++) {
+         for (int j = -MAX_OUTLINE_WIDTH; j <= MAX_OUTLINE_WIDTH; j++) {
+           if (i*i + j*j > u_width*u_width) { continue; }
+           vec4 mask = texture(u_mask, v_uv + vec2(float(i), float(j))*u_texel);
+           if (mask.a > color.a) { color = mask; }
+         }
+       }
+       if (color.a == 0.0) { discard; }
+       fragColor = color;
+     }
+    "#;
+}
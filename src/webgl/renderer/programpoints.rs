@@ -0,0 +1,166 @@
+use super::*;
+
+/// Colouring of the points drawn by the `ProgramPoints`
+pub enum PointColoring<'a> {
+  /// Colours of the points if any, material colour otherwise
+  Colors(Color),
+  /// Scalars of the points mapped over the range through the colormap of the style - bound to texture unit 0 -
+  /// with the given opacity
+  Scalars(&'a ScalarStyle, (f32, f32), f32),
+  /// Single colour, ignoring the colours and scalars of the points
+  Flat(Color),
+  /// Picking id encoded as a colour, from the id of the first point incremented per point. Requires the id program
+  Ids(usize),
+}
+
+/// Program drawing point clouds as sprites, clipped by the clipping planes
+pub struct ProgramPoints { }
+
+impl ProgramPoints {
+  /// Program drawing the points in colour, or the program drawing their picking ids if `ids`
+  pub fn webgl_program(
+    context: &web_sys::WebGl2RenderingContext,
+    ids: bool,
+    ) -> Result<web_sys::WebGlProgram, Error> {
+    let builder = WebGlProgramBuilder::new()
+      .context(context)
+      .vertex_shader_source(Self::VERTEX_SHADER_SOURCE)
+      .fragment_shader_source(Self::FRAGMENT_SHADER_SOURCE)
+      .define_value("LUT_SIZE", &format!("{}.0", Colormap::LUT_SIZE));
+    if ids { builder.define("ID") } else { builder }.build()
+  }
+
+  /// Draw the `n_points` points of `buffers` with `style` and `coloring`, transformed to clip space by `matrix`.
+  /// World sizes are converted to pixels by `pixels_per_unit` at unit distance: the vertical scale of the projection
+  /// times half the height of the image
+  #[allow(clippy::too_many_arguments)]
+  pub fn draw(
+    context: &web_sys::WebGl2RenderingContext,
+    counters: &statistics::Counters,
+    program: &std::rc::Rc<std::cell::RefCell<Option<web_sys::WebGlProgram>>>,
+    buffers: &pointbuffers::PointBuffers,
+    n_points: usize,
+    style: &PointStyle,
+    coloring: PointColoring,
+    matrix: &nalgebra::Matrix4<f32>,
+    pixels_per_unit: f32,
+    equations: &[[f32; 4]],
+    ) -> Result<(), Error> {
+    let ids = matches!(coloring, PointColoring::Ids(_));
+    if program.borrow().is_none() {
+      *program.borrow_mut() = Some(Self::webgl_program(context, ids)?);
+    }
+    let program = program.borrow();
+    let program = program.as_ref().ok_or("Unable to retrieve points program...")?;
+    counters.use_program(context, program);
+
+    let u_matrix = context.get_uniform_location(program, "uMatrix");
+    context.uniform_matrix4fv_with_f32_array(u_matrix.as_ref(), false, matrix.as_slice());
+    let u_size = context.get_uniform_location(program, "u_size");
+    context.uniform1f(u_size.as_ref(), style.size());
+    let u_pixels_per_unit = context.get_uniform_location(program, "u_pixelsPerUnit");
+    context.uniform1f(u_pixels_per_unit.as_ref(), if style.size_units() == PointSizeUnits::World { pixels_per_unit } else { 0.0 });
+    let u_round = context.get_uniform_location(program, "u_round");
+    context.uniform1i(u_round.as_ref(), (style.shape() == PointShape::Round) as i32);
+    clipping::set_uniforms(context, program, equations);
+
+    utils::bind_buffer(context, program, "a_position", Some(&buffers.positions), 3);
+    let (colors, scalars, indices) = match coloring {
+      PointColoring::Colors(color) | PointColoring::Flat(color) => {
+        let u_color = context.get_uniform_location(program, "u_color");
+        context.uniform4f(u_color.as_ref(), color.r(), color.g(), color.b(), color.a());
+        let colors = if let PointColoring::Colors(_) = coloring { buffers.colors.as_ref() } else { None };
+        let u_coloring = context.get_uniform_location(program, "u_coloring");
+        context.uniform1i(u_coloring.as_ref(), colors.is_some() as i32);
+        (colors, None, None)
+      },
+      PointColoring::Scalars(scalar_style, (min, max), opacity) => {
+        let u_coloring = context.get_uniform_location(program, "u_coloring");
+        context.uniform1i(u_coloring.as_ref(), 2);
+        let u_color = context.get_uniform_location(program, "u_color");
+        context.uniform4f(u_color.as_ref(), 1.0, 1.0, 1.0, opacity);
+        let u_colormap = context.get_uniform_location(program, "u_colormap");
+        context.uniform1i(u_colormap.as_ref(), 0);
+        let u_range = context.get_uniform_location(program, "u_range");
+        let (min, max) = ScalarStyle::colormap_range((min, max));
+        context.uniform2f(u_range.as_ref(), min, max);
+        let u_bands = context.get_uniform_location(program, "u_bands");
+        context.uniform1f(u_bands.as_ref(), scalar_style.bands() as f32);
+        (None, buffers.scalars.as_ref(), None)
+      },
+      PointColoring::Ids(first) => {
+        let u_first_id = context.get_uniform_location(program, "u_firstId");
+        context.uniform1ui(u_first_id.as_ref(), first.try_into()?);
+        (None, None, Some(buffers.indices.as_ref().ok_or("Point indices are not uploaded")?))
+      },
+    };
+    utils::bind_buffer(context, program, "a_color", colors, 4);
+    utils::bind_buffer(context, program, "a_scalar", scalars, 1);
+    utils::bind_uint_buffer(context, program, "a_index", indices, 1);
+    utils::draw(context, counters, web_sys::WebGl2RenderingContext::POINTS, n_points, None)
+  }
+
+  const VERTEX_SHADER_SOURCE: &str = r#"
+     #include <clipping_vertex>
+     #include <picking>
+
+     in vec3 a_position;
+     in vec4 a_color;
+     in float a_scalar;
+     #ifdef ID
+     in uint a_index;
+     #endif
+
+     uniform mat4 uMatrix;
+     uniform float u_size;
+     // Pixels per world unit at unit distance, 0 for sizes in pixels
+     uniform float u_pixelsPerUnit;
+     // 0 for the uniform colour, 1 for the point colours, 2 for the scalars
+     uniform int u_coloring;
+     uniform vec4 u_color;
+     uniform vec2 u_range;
+     uniform highp uint u_firstId;
+
+     out vec4 v_color;
+     out float v_value;
+
+     void main()
+     {
+        gl_Position = uMatrix*vec4(a_position, 1.0);
+        v_clipPosition = a_position;
+        gl_PointSize = u_pixelsPerUnit > 0.0 ? max(u_size*u_pixelsPerUnit/gl_Position.w, 1.0) : u_size;
+     #ifdef ID
+        v_color = idColor(u_firstId + a_index);
+        v_value = -1.0;
+     #else
+        v_color = u_coloring == 1 ? a_color : u_color;
+        v_value = u_coloring == 2 ? clamp((a_scalar - u_range.x)/(u_range.y - u_range.x), 0.0, 1.0) : -1.0;
+     #endif
+     }
+    "#;
+
+  const FRAGMENT_SHADER_SOURCE: &str = r#"
+     precision mediump float;
+
+     #include <clipping>
+     #include <colormap>
+
+     uniform bool u_round;
+
+     in vec4 v_color;
+     in float v_value;
+
+     out vec4 fragColor;
+
+     void main()
+     {
+       clip();
+       if (u_round && length(gl_PointCoord - vec2(0.5)) > 0.5) { discard; }
+     #ifdef ID
+       fragColor = v_color;
+     #else
+       fragColor = v_value < 0.0 ? v_color : vec4(colormap(v_value), v_color.a);
+     #endif
+     }
+    "#;
+}
@@ -0,0 +1,85 @@
+use super::*;
+
+/// Depth pre-pass from the light, filling the shadow map
+pub struct ProgramShadowDepth { }
+
+impl ProgramShadowDepth {
+  /// Program drawing instanced geometry if `instanced`, compiled on first use and kept in `cache`
+  pub fn webgl_program(
+    context: &web_sys::WebGl2RenderingContext,
+    cache: &ProgramCache,
+    instanced: bool,
+    ) -> Result<web_sys::WebGlProgram, Error> {
+    let builder = WebGlProgramBuilder::new()
+      .context(context)
+      .vertex_shader_source(Self::VERTEX_SHADER_SOURCE)
+      .fragment_shader_source(Self::FRAGMENT_SHADER_SOURCE)
+      .cache(cache, "shadow_depth");
+    if instanced { builder.define("INSTANCED") } else { builder }.build()
+  }
+
+  const VERTEX_SHADER_SOURCE: &str = r#"
+     #include <clipping_vertex>
+     #include <transforms>
+
+     in vec4 a_position;
+     uniform mat4 uLightMatrix;
+     void main()
+     {
+        vec4 position = worldPosition(a_position);
+        gl_Position = uLightMatrix*position;
+        v_clipPosition = position.xyz;
+     }
+    "#;
+
+  const FRAGMENT_SHADER_SOURCE: &str = r#"
+     precision mediump float;
+     #include <clipping>
+
+     out vec4 fragColor;
+     void main()
+     {
+       clip();
+       fragColor = vec4(1.0);
+     }
+    "#;
+}
+
+/// Ground plane drawn black, with an opacity proportional to the shadowing
+pub struct ProgramShadowGround { }
+
+impl ProgramShadowGround {
+  pub fn webgl_program(
+    context: &web_sys::WebGl2RenderingContext,
+    ) -> Result<web_sys::WebGlProgram, Error> {
+    WebGlProgramBuilder::new()
+      .context(context)
+      .vertex_shader_source(Self::VERTEX_SHADER_SOURCE)
+      .fragment_shader_source(Self::FRAGMENT_SHADER_SOURCE)
+      .build()
+  }
+
+  const VERTEX_SHADER_SOURCE: &str = r#"
+     in vec4 a_position;
+     uniform mat4 uWorldviewProjection;
+     uniform mat4 uLightMatrix;
+     out vec4 vShadowCoord;
+     void main()
+     {
+        gl_Position = uWorldviewProjection*a_position;
+        vShadowCoord = uLightMatrix*a_position;
+     }
+    "#;
+
+  const FRAGMENT_SHADER_SOURCE: &str = r#"
+     precision mediump float;
+     #include <shadows>
+
+     uniform float u_opacity;
+     out vec4 fragColor;
+     void main()
+     {
+       fragColor = vec4(0.0, 0.0, 0.0, u_opacity*(1.0 - shadowVisibility(1.0)));
+     }
+    "#;
+}
@@ -1,52 +1,145 @@
 use super::*;
 
+/// Variant of the program drawing triangles with normals, compiled from the feature flag of the same name
+#[derive(Clone, Copy, PartialEq)]
+pub enum TrianglesVariant {
+  /// Coloured by the texture bound to texture unit 0 at the `a_uv` coordinates
+  Textured,
+  /// Coloured by the `a_scalar` values through the colormap texture bound to texture unit 0
+  Scalar,
+  /// Drawn once per instance, with a per-instance model matrix and colour
+  Instanced,
+}
+
 pub struct ProgramTrianglesWithNormals { }
 
 impl ProgramTrianglesWithNormals {
   pub fn webgl_program(
     context: &web_sys::WebGl2RenderingContext
     ) -> Result<web_sys::WebGlProgram, Error> {
-    Ok(WebGlProgramBuilder::new()
+    WebGlProgramBuilder::new()
+      .context(context)
+      .vertex_shader_source(Self::VERTEX_SHADER_SOURCE)
+      .fragment_shader_source(Self::FRAGMENT_SHADER_SOURCE)
+      .build()
+  }
+
+  /// Program of the `variant`, compiled on first use and kept in `cache`
+  pub fn webgl_program_variant(
+    context: &web_sys::WebGl2RenderingContext,
+    cache: &ProgramCache,
+    variant: TrianglesVariant,
+    ) -> Result<web_sys::WebGlProgram, Error> {
+    let builder = WebGlProgramBuilder::new()
       .context(context)
       .vertex_shader_source(Self::VERTEX_SHADER_SOURCE)
       .fragment_shader_source(Self::FRAGMENT_SHADER_SOURCE)
-      .build()?)
+      .cache(cache, "triangles");
+    match variant {
+      TrianglesVariant::Textured => builder.define("TEXTURED"),
+      TrianglesVariant::Scalar => builder.define("SCALAR").define_value("LUT_SIZE", &format!("{}.0", Colormap::LUT_SIZE)),
+      TrianglesVariant::Instanced => builder.define("INSTANCED"),
+    }.build()
   }
 
   const VERTEX_SHADER_SOURCE: &str = r#"
-     attribute vec4 a_position;
-     attribute vec3 a_normal;
+     #include <clipping_vertex>
+     #include <transforms>
+
+     in vec4 a_position;
+     in vec3 a_normal;
      uniform mat4 uWorldviewInverseTranspose;
 
      uniform mat4 uWorldviewProjection;
+     uniform mat4 uWorldview;
+     uniform mat4 uLightMatrix;
 
-     varying vec3 vNormal;
+     out vec3 vNormal;
+     out vec3 vPosition;
+     out vec4 vShadowCoord;
+     #ifdef INSTANCED
+     in vec4 a_instanceColor;
+     out vec4 vColor;
+     #endif
+     #ifdef TEXTURED
+     in vec2 a_uv;
+     out vec2 vUv;
+     #endif
+     #ifdef SCALAR
+     in float a_scalar;
+     out highp float vScalar;
+     #endif
 
      void main()
      {
-        gl_Position = uWorldviewProjection*a_position;
-        vNormal = mat3(uWorldviewInverseTranspose)*a_normal;
+        #ifdef INSTANCED
+        vColor = a_instanceColor;
+        #endif
+        #ifdef TEXTURED
+        vUv = a_uv;
+        #endif
+        #ifdef SCALAR
+        vScalar = a_scalar;
+        #endif
+        vec4 position = worldPosition(a_position);
+        gl_Position = uWorldviewProjection*position;
+        vNormal = mat3(uWorldviewInverseTranspose)*worldNormal(a_normal);
+        vPosition = (uWorldview*position).xyz;
+        vShadowCoord = uLightMatrix*position;
+        v_clipPosition = position.xyz;
      }
     "#;
 
-
   const FRAGMENT_SHADER_SOURCE: &str = r#"
      precision mediump float;
 
-     varying vec3 vNormal;
+     #include <clipping>
+     #include <lighting>
 
-     uniform vec3 u_reverseLightDirection;
+     in vec3 vNormal;
+     in vec3 vPosition;
+     #ifdef INSTANCED
+     in vec4 vColor;
+     #endif
+     #ifdef TEXTURED
+     in vec2 vUv;
+     uniform sampler2D u_texture;
+     #endif
+     #ifdef SCALAR
+     #include <colormap>
+     in highp float vScalar;
+     uniform highp vec2 u_range;
+     #endif
 
      uniform vec4 u_color;
 
+     // 0: colour, 1: weighted blended accumulation, 2: weighted blended revealage
+     uniform int u_oitPass;
+
+     out vec4 fragColor;
+
      void main()
      {
-       gl_FragColor = u_color;
-       vec3 normal = normalize(vNormal);
-       float light = (dot(normal, u_reverseLightDirection)+2.0)/3.0;
-       gl_FragColor.rgb *= light;
+       clip();
+       #ifdef INSTANCED
+       vec4 color = vColor;
+       #elif defined(TEXTURED)
+       vec4 color = texture(u_texture, vUv);
+       color.a *= u_color.a;
+       #elif defined(SCALAR)
+       vec4 color = vec4(colormap(clamp((vScalar - u_range.x)/(u_range.y - u_range.x), 0.0, 1.0)), u_color.a);
+       #else
+       vec4 color = u_color;
+       #endif
+       vec3 rgb = shade(color.rgb, normalize(vNormal), vPosition);
+       if (u_oitPass == 1) {
+         highp float weight = clamp(pow(min(1.0, color.a*10.0) + 0.01, 3.0)*1e3*pow(1.0 - 0.9*gl_FragCoord.z, 3.0), 1e-2, 3e3);
+         fragColor = vec4(rgb*color.a, color.a)*weight;
+       } else if (u_oitPass == 2) {
+         fragColor = vec4(color.a);
+       } else {
+         fragColor = vec4(rgb, color.a);
+       }
      }
     "#;
-
-
 }
@@ -0,0 +1,243 @@
+use super::*;
+
+/// Line segments drawn as screen-space quads, one instance per segment
+pub struct Segments {
+  /// Index of the first vertex of each segment
+  pub first_vertices: Vec<usize>,
+  starts:    Vec<f32>,
+  ends:      Vec<f32>,
+  distances: Vec<f32>,
+  joined:    Vec<f32>,
+}
+
+impl Segments {
+  /// Segments between pairs of vertices of the flattened `positions`, referenced by `indices` if provided.
+  /// Segment ends shared with another segment are joined, and distances accumulate along consecutive segments
+  pub fn new(positions: &[f32], indices: Option<&Indices>) -> Segments {
+    let pairs: Vec<(usize, usize)> = match indices {
+      Some(indices) => indices.iter().collect::<Vec<_>>().chunks_exact(2).map(|c| (c[0], c[1])).collect(),
+      None => (0..positions.len() / 6).map(|i| (2*i, 2*i + 1)).collect(),
+    };
+    let point = |i: usize| nalgebra::Point3::new(positions[3*i], positions[3*i + 1], positions[3*i + 2]);
+    let key = |i: usize| [positions[3*i].to_bits(), positions[3*i + 1].to_bits(), positions[3*i + 2].to_bits()];
+
+    let mut counts: std::collections::HashMap<[u32; 3], usize> = std::collections::HashMap::new();
+    for (s, e) in pairs.iter() {
+      *counts.entry(key(*s)).or_default() += 1;
+      *counts.entry(key(*e)).or_default() += 1;
+    }
+
+    let mut segments = Segments { first_vertices: Vec::new(), starts: Vec::new(), ends: Vec::new(), distances: Vec::new(), joined: Vec::new() };
+    let mut previous: Option<([u32; 3], f32)> = None;
+    for (s, e) in pairs.into_iter() {
+      let (start, end) = (point(s), point(e));
+      let distance = match previous {
+        Some((k, d)) if k == key(s) => d,
+        _ => 0.0,
+      };
+      previous = Some((key(e), distance + (end - start).norm()));
+      segments.first_vertices.push(s);
+      segments.starts.extend([start.x, start.y, start.z]);
+      segments.ends.extend([end.x, end.y, end.z]);
+      segments.distances.push(distance);
+      segments.joined.extend([(counts[&key(s)] > 1) as u8 as f32, (counts[&key(e)] > 1) as u8 as f32]);
+    }
+    segments
+  }
+
+  /// Number of segments
+  pub fn len(&self) -> usize { self.first_vertices.len() }
+
+  /// Whether there is no segment
+  pub fn is_empty(&self) -> bool { self.first_vertices.is_empty() }
+}
+
+/// Program drawing lines of any width as screen-space quads, with caps, joins and dashes
+pub struct ProgramWideLines { }
+
+impl ProgramWideLines {
+  pub fn webgl_program(
+    context: &web_sys::WebGl2RenderingContext,
+    ) -> Result<web_sys::WebGlProgram, Error> {
+    WebGlProgramBuilder::new()
+      .context(context)
+      .vertex_shader_source(Self::VERTEX_SHADER_SOURCE)
+      .fragment_shader_source(Self::FRAGMENT_SHADER_SOURCE)
+      .define_value("MAX_DASH", &(2*LineStyle::MAX_DASH).to_string())
+      .build()
+  }
+
+  /// Draw the `segments` with the `style`, using a colour per segment (`[r, g, b, a]` flattened), transformed
+  /// to clip space by `matrix` in a viewport of `resolution` pixels and clipped by the plane `equations`
+  #[allow(clippy::too_many_arguments)]
+  pub fn draw(
+    context: &web_sys::WebGl2RenderingContext,
+    counters: &statistics::Counters,
+    program: &std::rc::Rc<std::cell::RefCell<Option<web_sys::WebGlProgram>>>,
+    segments: &Segments,
+    colors: &[f32],
+    style: &LineStyle,
+    matrix: &nalgebra::Matrix4<f32>,
+    resolution: (i32, i32),
+    equations: &[[f32; 4]],
+    ) -> Result<(), Error> {
+    if segments.is_empty() { return Ok(()); }
+    if program.borrow().is_none() {
+      *program.borrow_mut() = Some(Self::webgl_program(context)?);
+    }
+    let program = program.borrow();
+    let program = program.as_ref().ok_or("Unable to retrieve program to draw wide lines...")?;
+    counters.use_program(context, program);
+
+    let u_matrix = context.get_uniform_location(program, "uMatrix");
+    context.uniform_matrix4fv_with_f32_array(u_matrix.as_ref(), false, matrix.as_slice());
+    let u_resolution = context.get_uniform_location(program, "u_resolution");
+    context.uniform2f(u_resolution.as_ref(), resolution.0 as f32, resolution.1 as f32);
+    let u_width = context.get_uniform_location(program, "u_width");
+    context.uniform1f(u_width.as_ref(), style.width());
+    let u_cap = context.get_uniform_location(program, "u_cap");
+    context.uniform1i(u_cap.as_ref(), style.cap() as i32);
+    let u_join = context.get_uniform_location(program, "u_join");
+    context.uniform1i(u_join.as_ref(), style.join() as i32);
+    clipping::set_uniforms(context, program, equations);
+
+    // Odd patterns are repeated so that dashes and gaps alternate
+    let mut dash = style.dash().to_vec();
+    if dash.len() % 2 == 1 { dash.extend_from_within(..); }
+    let u_n_dash = context.get_uniform_location(program, "u_nDash");
+    context.uniform1i(u_n_dash.as_ref(), dash.len() as i32);
+    if !dash.is_empty() {
+      let u_dash = context.get_uniform_location(program, "u_dash");
+      context.uniform1fv_with_f32_array(u_dash.as_ref(), &dash);
+      let u_dash_period = context.get_uniform_location(program, "u_dashPeriod");
+      context.uniform1f(u_dash_period.as_ref(), dash.iter().sum());
+      let u_dash_units = context.get_uniform_location(program, "u_dashUnits");
+      context.uniform1i(u_dash_units.as_ref(), style.dash_units() as i32);
+    }
+
+    // Two triangles per segment, along the segment (x) and across it (y)
+    let corners: Vec<f32> = vec!(
+      0.0, -1.0,   1.0, -1.0,   1.0, 1.0,
+      0.0, -1.0,   1.0,  1.0,   0.0, 1.0,
+    );
+    utils::bind_with_size(context, counters, program, "a_corner", &corners, 2)?;
+    let mut locations = utils::bind_per_instance(context, counters, program, "a_start", &segments.starts, 1, 3)?;
+    locations.append(&mut utils::bind_per_instance(context, counters, program, "a_end", &segments.ends, 1, 3)?);
+    locations.append(&mut utils::bind_per_instance(context, counters, program, "a_distance", &segments.distances, 1, 1)?);
+    locations.append(&mut utils::bind_per_instance(context, counters, program, "a_joined", &segments.joined, 1, 2)?);
+    locations.append(&mut utils::bind_per_instance(context, counters, program, "a_color", colors, 1, 4)?);
+
+    // Quads face either way and must not be pushed back like surfaces
+    let cull_face = context.is_enabled(web_sys::WebGl2RenderingContext::CULL_FACE);
+    let polygon_offset = context.is_enabled(web_sys::WebGl2RenderingContext::POLYGON_OFFSET_FILL);
+    context.disable(web_sys::WebGl2RenderingContext::CULL_FACE);
+    context.disable(web_sys::WebGl2RenderingContext::POLYGON_OFFSET_FILL);
+    let result = utils::draw_instanced(context, counters, web_sys::WebGl2RenderingContext::TRIANGLES, 6, None, segments.len());
+    if cull_face { context.enable(web_sys::WebGl2RenderingContext::CULL_FACE); }
+    if polygon_offset { context.enable(web_sys::WebGl2RenderingContext::POLYGON_OFFSET_FILL); }
+    utils::unbind_per_instance(context, &locations);
+    result
+  }
+
+  const VERTEX_SHADER_SOURCE: &str = r#"
+     #include <clipping_vertex>
+
+     in vec2 a_corner;
+     in vec3 a_start;
+     in vec3 a_end;
+     in float a_distance;
+     in vec2 a_joined;
+     in vec4 a_color;
+
+     uniform mat4 uMatrix;
+     uniform vec2 u_resolution;
+     uniform float u_width;
+
+     // Pixel distances along and across the segment, multiplied by w to be interpolated linearly in screen space
+     out vec3 vPosition;
+     out float vLength;
+     out float vDistance;
+     out float vWorldLength;
+     out vec2 vJoined;
+     out vec4 vColor;
+
+     void main()
+     {
+        vec4 clipStart = uMatrix*vec4(a_start, 1.0);
+        vec4 clipEnd = uMatrix*vec4(a_end, 1.0);
+        vec2 halfResolution = 0.5*u_resolution;
+        vec2 axis = halfResolution*(clipEnd.xy/clipEnd.w - clipStart.xy/clipStart.w);
+        float len = length(axis);
+        vec2 direction = len > 0.0 ? axis/len : vec2(1.0, 0.0);
+        vec2 normal = vec2(-direction.y, direction.x);
+        float halfWidth = 0.5*u_width;
+
+        // Extended by half the width at both ends for caps and joins
+        float extension = (2.0*a_corner.x - 1.0)*halfWidth;
+        vec4 position = mix(clipStart, clipEnd, a_corner.x);
+        position.xy += (normal*a_corner.y*halfWidth + direction*extension)*position.w/halfResolution;
+        gl_Position = position;
+
+        vPosition = vec3(a_corner.x*len + extension, a_corner.y*halfWidth, 1.0)*position.w;
+        vLength = len;
+        vDistance = a_distance;
+        vWorldLength = length(a_end - a_start);
+        vJoined = a_joined;
+        vColor = a_color;
+        v_clipPosition = mix(a_start, a_end, a_corner.x);
+     }
+    "#;
+
+  const FRAGMENT_SHADER_SOURCE: &str = r#"
+     precision highp float;
+     #include <clipping>
+
+     uniform float u_width;
+     uniform int u_cap;
+     uniform int u_join;
+     uniform int u_nDash;
+     uniform float u_dash[MAX_DASH];
+     uniform float u_dashPeriod;
+     uniform int u_dashUnits;
+
+     in vec3 vPosition;
+     in float vLength;
+     in float vDistance;
+     in float vWorldLength;
+     in vec2 vJoined;
+     in vec4 vColor;
+
+     out vec4 fragColor;
+
+     void main()
+     {
+       clip();
+       float along = vPosition.x/vPosition.z;
+       float across = vPosition.y/vPosition.z;
+       float halfWidth = 0.5*u_width;
+
+       // Round caps and joins
+       if (along < 0.0 || along > vLength) {
+         bool joined = along < 0.0 ? vJoined.x > 0.5 : vJoined.y > 0.5;
+         int shape = joined ? u_join : u_cap;
+         float d = along < 0.0 ? -along : along - vLength;
+         if (shape == 1 && d*d + across*across > halfWidth*halfWidth) { discard; }
+       }
+
+       if (u_nDash > 0) {
+         float t = clamp(along, 0.0, vLength);
+         float d = u_dashUnits == 2 ? vDistance + (vLength > 0.0 ? t/vLength : 0.0)*vWorldLength : t;
+         float m = mod(d, u_dashPeriod);
+         bool dash = true;
+         for (int i = 0; i < MAX_DASH; i++) {
+           if (i >= u_nDash || m < u_dash[i]) { break; }
+           m -= u_dash[i];
+           dash = !dash;
+         }
+         if (!dash) { discard; }
+       }
+
+       fragColor = vColor;
+     }
+    "#;
+}
@@ -0,0 +1,27 @@
+use super::*;
+
+/// `RendererTrait` keeping an owned copy of the information drawn, without drawing it
+#[derive(Default)]
+pub struct Recorder {
+  infos: std::cell::RefCell<Vec<OwnedInfo>>,
+}
+
+impl Recorder {
+  /// Create an empty `Recorder`
+  pub fn new() -> Recorder { Recorder::default() }
+
+  /// Retrieve the information recorded
+  pub fn into_infos(self) -> Vec<OwnedInfo> { self.infos.into_inner() }
+}
+
+impl RendererTrait for Recorder {
+  fn init(&self, _context: &web_sys::WebGl2RenderingContext) -> Result<(), Error> {
+    self.infos.borrow_mut().clear();
+    Ok(())
+  }
+
+  fn draw(&self, _context: &web_sys::WebGl2RenderingContext, info: Info) -> Result<(), Error> {
+    self.infos.borrow_mut().push(info.to_owned_info());
+    Ok(())
+  }
+}
@@ -1,19 +1,226 @@
 use super::*;
 
+/// Colouring of triangles per vertex, replacing the material colour
+enum VertexColoring<'a> {
+  /// Texture and flattened texture coordinates
+  Texture(&'a Texture, &'a [f32]),
+  /// Scalar style, values and range mapped onto the colormap
+  Scalars(&'a ScalarStyle, &'a [f32], (f32, f32)),
+}
+
+/// Vertices of lines or triangles
+enum Vertices<'a> {
+  /// Flattened positions and normals - empty for lines - and optional indices, uploaded for the draw
+  Arrays(&'a [f32], &'a [f32], Option<&'a Indices>),
+  /// Cached buffers of merged geometry
+  Buffers(&'a batchbuffers::BatchBuffers),
+}
+
+impl Vertices<'_> {
+  /// Bind the positions to the attribute `position_key` and the normals, if any, to `a_normal`.
+  /// Assumes that the program has already been set
+  fn bind(&self, context: &web_sys::WebGl2RenderingContext, counters: &statistics::Counters, program: &web_sys::WebGlProgram, position_key: &str) -> Result<(), Error> {
+    match self {
+      Vertices::Arrays(positions, normals, _) => {
+        utils::bind(context, counters, program, position_key, positions)?;
+        if !normals.is_empty() { utils::bind(context, counters, program, "a_normal", normals)?; }
+      },
+      Vertices::Buffers(buffers) => buffers.bind(context, program, position_key),
+    }
+    Ok(())
+  }
+
+  /// Issue the draw call for `mode` primitives from the vertices bound, once per instance if `n_instances` is given
+  fn draw(&self, context: &web_sys::WebGl2RenderingContext, counters: &statistics::Counters, mode: u32, n_instances: Option<usize>) -> Result<(), Error> {
+    match (self, n_instances) {
+      (Vertices::Arrays(positions, _, indices), None) => utils::draw(context, counters, mode, positions.len() / 3, *indices),
+      (Vertices::Arrays(positions, _, indices), Some(n)) => utils::draw_instanced(context, counters, mode, positions.len() / 3, *indices, n),
+      (Vertices::Buffers(buffers), None) => buffers.draw(context, counters, mode),
+      (Vertices::Buffers(_), Some(_)) => Err("Merged geometry is not drawn instanced".into()),
+    }
+  }
+}
+
 #[cfg(feature = "wasm")]
 #[wasm_bindgen::prelude::wasm_bindgen]
 /// Struct to managing rendering. Hold consistent copy of `Camera`, WebGL programs etc and provides interface
 /// for rendering lines and triangles
+#[derive(Clone)]
 pub struct Renderer {
   camera: Camera,
   program_lines: std::rc::Rc<std::cell::RefCell<Option<web_sys::WebGlProgram>>>,
   program_triangles_with_normals: std::rc::Rc<std::cell::RefCell<Option<web_sys::WebGlProgram>>>,
+  settings: RendererSettings,
+  program_background: std::rc::Rc<std::cell::RefCell<Option<web_sys::WebGlProgram>>>,
+  background_texture: std::rc::Rc<std::cell::RefCell<Option<web_sys::WebGlTexture>>>,
+  deferred: std::rc::Rc<std::cell::RefCell<Vec<OwnedInfo>>>,
+  oit_targets: std::rc::Rc<std::cell::RefCell<Option<transparency::WeightedBlendedTargets>>>,
+  oit_active: std::rc::Rc<std::cell::Cell<bool>>,
+  program_composite: std::rc::Rc<std::cell::RefCell<Option<web_sys::WebGlProgram>>>,
+  queued: std::rc::Rc<std::cell::RefCell<Vec<OwnedInfo>>>,
+  replaying: std::rc::Rc<std::cell::Cell<bool>>,
+  shadow_map: std::rc::Rc<std::cell::RefCell<Option<shadows::ShadowMap>>>,
+  light_matrix: std::rc::Rc<std::cell::Cell<Option<nalgebra::Matrix4<f32>>>>,
+  program_shadow_ground: std::rc::Rc<std::cell::RefCell<Option<web_sys::WebGlProgram>>>,
+  msaa_target: std::rc::Rc<std::cell::RefCell<Option<antialiasing::MsaaTarget>>>,
+  fxaa_target: std::rc::Rc<std::cell::RefCell<Option<antialiasing::FxaaTarget>>>,
+  antialiasing_active: std::rc::Rc<std::cell::Cell<Antialiasing>>,
+  tile: Option<screenshot::Tile>,
+  output: Option<web_sys::WebGlFramebuffer>,
+  post_targets: std::rc::Rc<std::cell::RefCell<Option<postprocessing::PostTargets>>>,
+  programs: ProgramCache,
+  post_active: std::rc::Rc<std::cell::Cell<bool>>,
+  hovered: Option<String>,
+  selected: std::collections::HashSet<String>,
+  outlines: std::rc::Rc<std::cell::RefCell<Vec<(OwnedInfo, Color)>>>,
+  mask_target: std::rc::Rc<std::cell::RefCell<Option<highlight::MaskTarget>>>,
+  program_mask: std::rc::Rc<std::cell::RefCell<Option<web_sys::WebGlProgram>>>,
+  program_outline: std::rc::Rc<std::cell::RefCell<Option<web_sys::WebGlProgram>>>,
+  program_wide_lines: std::rc::Rc<std::cell::RefCell<Option<web_sys::WebGlProgram>>>,
+  labels: std::rc::Rc<std::cell::RefCell<Vec<OwnedInfo>>>,
+  program_labels: std::rc::Rc<std::cell::RefCell<Option<web_sys::WebGlProgram>>>,
+  glyph_atlas: std::rc::Rc<std::cell::RefCell<Option<glyphatlas::GlyphAtlas>>>,
+  clip_planes: std::rc::Rc<std::cell::RefCell<Vec<[f32; 4]>>>,
+  solids: std::rc::Rc<std::cell::RefCell<Vec<OwnedInfo>>>,
+  textures: std::rc::Rc<std::cell::RefCell<textures::TextureCache>>,
+  program_legend: std::rc::Rc<std::cell::RefCell<Option<web_sys::WebGlProgram>>>,
+  legend_range: std::rc::Rc<std::cell::Cell<Option<(f32, f32)>>>,
+  program_points: std::rc::Rc<std::cell::RefCell<Option<web_sys::WebGlProgram>>>,
+  point_buffers: std::rc::Rc<std::cell::RefCell<pointbuffers::PointBufferCache>>,
+  edges: std::rc::Rc<std::cell::RefCell<edges::EdgeCache>>,
+  bounds: std::rc::Rc<std::cell::RefCell<bounds::BoundsCache>>,
+  batch_buffers: std::rc::Rc<std::cell::RefCell<batchbuffers::BatchBufferCache>>,
+  eye_dome: std::rc::Rc<std::cell::RefCell<Vec<OwnedInfo>>>,
+  eye_dome_target: std::rc::Rc<std::cell::RefCell<Option<eyedome::EyeDomeTarget>>>,
+  program_eye_dome: std::rc::Rc<std::cell::RefCell<Option<web_sys::WebGlProgram>>>,
+  context_state: contextloss::ContextState,
+  statistics: std::rc::Rc<std::cell::RefCell<RenderStatistics>>,
+  counters: statistics::Counters,
+  gpu_timer: std::rc::Rc<std::cell::RefCell<statistics::GpuTimer>>,
 }
 
 #[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
 impl Renderer {
   /// Update `Camera` component in place and returns the `Renderer`
   pub fn with_camera(mut self, camera: Camera) -> Self { self.camera = camera; self }
+
+  /// Update the `Background` in place and returns the `Renderer`
+  pub fn with_background_color(mut self, color: Color) -> Self { self.set_background(Background::Solid(color)); self }
+
+  /// Update the `Lighting` in place and returns the `Renderer`
+  pub fn with_lighting(mut self, lighting: Lighting) -> Self { self.settings.lighting = lighting; self }
+
+  /// Retrieve the `Lighting`
+  pub fn lighting(&self) -> Lighting { self.settings.lighting.clone() }
+
+  /// Update the `Transparency` method in place and returns the `Renderer`
+  pub fn with_transparency(mut self, transparency: Transparency) -> Self { self.settings.transparency = transparency; self }
+
+  /// Update the `DisplayMode` in place and returns the `Renderer`
+  pub fn with_display_mode(mut self, display_mode: DisplayMode) -> Self { self.settings.display_mode = display_mode; self }
+
+  /// Enable shadows in place and returns the `Renderer`
+  pub fn with_shadows(mut self, shadows: Shadows) -> Self { self.settings.shadows = Some(shadows); self }
+
+  /// Disable shadows in place and returns the `Renderer`
+  pub fn without_shadows(mut self) -> Self { self.settings.shadows = None; self }
+
+  /// Enable multisample anti-aliasing with `samples` samples per pixel in place and returns the `Renderer`
+  pub fn with_msaa(mut self, samples: u32) -> Self { self.settings.antialiasing = Antialiasing::Msaa(samples); self }
+
+  /// Enable FXAA anti-aliasing in place and returns the `Renderer`
+  pub fn with_fxaa(mut self) -> Self { self.settings.antialiasing = Antialiasing::Fxaa; self }
+
+  /// Disable anti-aliasing in place and returns the `Renderer`
+  pub fn without_antialiasing(mut self) -> Self { self.settings.antialiasing = Antialiasing::None; self }
+
+  /// Update the `PostProcessing` pipeline in place and returns the `Renderer`
+  pub fn with_post_processing(mut self, post_processing: PostProcessing) -> Self { self.settings.post_processing = post_processing; self }
+
+  /// Disable post-processing in place and returns the `Renderer`
+  pub fn without_post_processing(mut self) -> Self { self.settings.post_processing = PostProcessing::default(); self }
+
+  /// Update the `Highlight` styles in place and returns the `Renderer`
+  pub fn with_highlight(mut self, highlight: Highlight) -> Self { self.settings.highlight = highlight; self }
+
+  /// Set the uid of the hovered element - for example the `Picker` result - in place and returns the `Renderer`
+  pub fn with_hovered(mut self, uid: Option<String>) -> Self { self.hovered = uid; self }
+
+  /// Retrieve the uid of the hovered element
+  pub fn hovered(&self) -> Option<String> { self.hovered.clone() }
+
+  /// Replace the uids of the selected elements in place and returns the `Renderer`
+  pub fn with_selected(mut self, uids: Vec<String>) -> Self { self.selected = uids.into_iter().collect(); self }
+
+  /// Add an element to the selection in place and returns the `Renderer`
+  pub fn with_selected_uid(mut self, uid: String) -> Self { self.selected.insert(uid); self }
+
+  /// Remove an element from the selection in place and returns the `Renderer`
+  pub fn without_selected_uid(mut self, uid: &str) -> Self { self.selected.remove(uid); self }
+
+  /// Retrieve the uids of the selected elements
+  pub fn selected(&self) -> Vec<String> { self.selected.iter().cloned().collect() }
+
+  /// Show or hide the axis triad in place and returns the `Renderer`
+  pub fn with_axis_triad_visible(mut self, visible: bool) -> Self { self.settings.axis_triad.visible = visible; self }
+
+  /// Show the colour legend in place and returns the `Renderer`
+  pub fn with_color_legend(mut self, color_legend: ColorLegend) -> Self { self.settings.color_legend = Some(color_legend); self }
+
+  /// Hide the colour legend in place and returns the `Renderer`
+  pub fn without_color_legend(mut self) -> Self { self.settings.color_legend = None; self }
+
+  /// Retrieve the colour legend, if shown
+  pub fn color_legend(&self) -> Option<ColorLegend> { self.settings.color_legend.clone() }
+
+  /// Show the render statistics of each frame in the `corner` in place and returns the `Renderer`
+  pub fn with_statistics_overlay(mut self, corner: Corner) -> Self { self.settings.statistics_overlay = Some(corner); self }
+
+  /// Hide the render statistics in place and returns the `Renderer`
+  pub fn without_statistics_overlay(mut self) -> Self { self.settings.statistics_overlay = None; self }
+
+  /// Enable or disable the culling of the elements outside the view in place and returns the `Renderer`
+  pub fn with_frustum_culling(mut self, enabled: bool) -> Self { self.settings.frustum_culling = enabled; self }
+
+  /// Statistics of the last frame drawn. The GPU time of the passes lags the frame by a few frames
+  pub fn statistics(&self) -> RenderStatistics { self.statistics.borrow().clone() }
+
+  /// Number of textures held on the GPU. Textures are released at the start of the frame following the drop of
+  /// the last element referencing them
+  pub fn n_textures(&self) -> usize { self.textures.borrow().len() }
+
+  /// Whether the WebGL context was lost at the start of the last frame. The GPU resources are created again on the
+  /// first frame once the context is restored
+  pub fn is_context_lost(&self) -> bool { self.context_state.is_lost() }
+
+  /// Number of point clouds whose buffers are held on the GPU. Buffers are released at the start of the frame
+  /// following the drop of the last copy of the cloud
+  pub fn n_point_buffers(&self) -> usize { self.point_buffers.borrow().len() }
+
+  /// Render `elements` into an offscreen image of `width` x `height` pixels and return it encoded as PNG.
+  /// The image is rendered in tiles when larger than the maximum renderbuffer size. The background is left
+  /// transparent if `transparent` is set. Exposed to JavaScript
+  pub fn screenshot(&self,
+    context: &web_sys::WebGl2RenderingContext,
+    elements: Vec<wasm_bindgen::JsValue>,
+    width: u32,
+    height: u32,
+    transparent: bool,
+    ) -> Result<Vec<u8>, JsError> {
+    let elements = elements.into_iter()
+      .map(|e| DrawableElement::try_from(e).map_err(|e| Error::from(e.to_string())))
+      .collect::<Result<Vec<_>, Error>>()?;
+    // The programs shared with the offscreen renderer must be valid
+    if !self.check_context(context)? { return Err(Error::from("WebGL context is lost").into()); }
+    let offscreen = self.offscreen(width, height, transparent);
+    let pixels = screenshot::capture(context, width, height, |tile, framebuffer| {
+      let renderer = Renderer { tile: Some(tile.clone()), output: Some(framebuffer.clone()), ..offscreen.clone() };
+      renderer.init(context)?;
+      for element in elements.iter() { element.draw(context, &renderer)?; }
+      renderer.end(context)
+    })?;
+    Ok(screenshot::encode_png(width, height, &pixels)?)
+  }
 }
 
 impl Renderer {
@@ -21,91 +228,1263 @@ impl Renderer {
   pub fn new(camera: Camera,
     program_lines: std::rc::Rc<std::cell::RefCell<Option<web_sys::WebGlProgram>>>,
     program_triangles_with_normals: std::rc::Rc<std::cell::RefCell<Option<web_sys::WebGlProgram>>>,
+    settings: RendererSettings,
     ) -> Renderer { 
-    Renderer { camera, program_lines, program_triangles_with_normals, } 
+    Renderer { camera, program_lines, program_triangles_with_normals, 
+      settings,
+      deferred: std::rc::Rc::new(std::cell::RefCell::new(Vec::new())),
+      oit_targets: std::rc::Rc::new(std::cell::RefCell::new(None)),
+      oit_active: std::rc::Rc::new(std::cell::Cell::new(false)),
+      program_composite: std::rc::Rc::new(std::cell::RefCell::new(None)),
+      queued: std::rc::Rc::new(std::cell::RefCell::new(Vec::new())),
+      replaying: std::rc::Rc::new(std::cell::Cell::new(false)),
+      shadow_map: std::rc::Rc::new(std::cell::RefCell::new(None)),
+      light_matrix: std::rc::Rc::new(std::cell::Cell::new(None)),
+      program_shadow_ground: std::rc::Rc::new(std::cell::RefCell::new(None)),
+      msaa_target: std::rc::Rc::new(std::cell::RefCell::new(None)),
+      fxaa_target: std::rc::Rc::new(std::cell::RefCell::new(None)),
+      antialiasing_active: std::rc::Rc::new(std::cell::Cell::new(Antialiasing::None)),
+      program_background: std::rc::Rc::new(std::cell::RefCell::new(None)),
+      background_texture: std::rc::Rc::new(std::cell::RefCell::new(None)),
+      tile: None,
+      output: None,
+      post_targets: std::rc::Rc::new(std::cell::RefCell::new(None)),
+      programs: ProgramCache::default(),
+      post_active: std::rc::Rc::new(std::cell::Cell::new(false)),
+      hovered: None,
+      selected: std::collections::HashSet::new(),
+      outlines: std::rc::Rc::new(std::cell::RefCell::new(Vec::new())),
+      mask_target: std::rc::Rc::new(std::cell::RefCell::new(None)),
+      program_mask: std::rc::Rc::new(std::cell::RefCell::new(None)),
+      program_outline: std::rc::Rc::new(std::cell::RefCell::new(None)),
+      program_wide_lines: std::rc::Rc::new(std::cell::RefCell::new(None)),
+      labels: std::rc::Rc::new(std::cell::RefCell::new(Vec::new())),
+      program_labels: std::rc::Rc::new(std::cell::RefCell::new(None)),
+      glyph_atlas: std::rc::Rc::new(std::cell::RefCell::new(None)),
+      clip_planes: std::rc::Rc::new(std::cell::RefCell::new(Vec::new())),
+      solids: std::rc::Rc::new(std::cell::RefCell::new(Vec::new())),
+      textures: std::rc::Rc::new(std::cell::RefCell::new(textures::TextureCache::default())),
+      program_legend: std::rc::Rc::new(std::cell::RefCell::new(None)),
+      legend_range: std::rc::Rc::new(std::cell::Cell::new(None)),
+      program_points: std::rc::Rc::new(std::cell::RefCell::new(None)),
+      point_buffers: std::rc::Rc::new(std::cell::RefCell::new(pointbuffers::PointBufferCache::default())),
+      edges: std::rc::Rc::new(std::cell::RefCell::new(edges::EdgeCache::default())),
+      bounds: std::rc::Rc::new(std::cell::RefCell::new(bounds::BoundsCache::default())),
+      batch_buffers: std::rc::Rc::new(std::cell::RefCell::new(batchbuffers::BatchBufferCache::default())),
+      eye_dome: std::rc::Rc::new(std::cell::RefCell::new(Vec::new())),
+      eye_dome_target: std::rc::Rc::new(std::cell::RefCell::new(None)),
+      program_eye_dome: std::rc::Rc::new(std::cell::RefCell::new(None)),
+      context_state: contextloss::ContextState::default(),
+      statistics: std::rc::Rc::new(std::cell::RefCell::new(RenderStatistics::default())),
+      counters: statistics::Counters::default(),
+      gpu_timer: std::rc::Rc::new(std::cell::RefCell::new(statistics::GpuTimer::default())),
+    } 
+  }
+
+  /// Check the WebGL context, forgetting the GPU resources of a lost context once restored so that they are created
+  /// again on use. Returns whether the context can be drawn into
+  fn check_context(&self, context: &web_sys::WebGl2RenderingContext) -> Result<bool, Error> {
+    match self.context_state.check(context)? {
+      contextloss::ContextStatus::Lost => return Ok(false),
+      contextloss::ContextStatus::Restored => self.invalidate_gpu_resources(),
+      contextloss::ContextStatus::Valid => (),
+    }
+    Ok(true)
+  }
+
+  /// Forget the programs, textures, buffers and targets of a lost context, in place so that the renderers sharing
+  /// them forget them too
+  fn invalidate_gpu_resources(&self) {
+    for program in [&self.program_lines, &self.program_triangles_with_normals, &self.program_background, &self.program_composite,
+      &self.program_shadow_ground, &self.program_mask, &self.program_outline, &self.program_wide_lines, &self.program_labels,
+      &self.program_legend, &self.program_points, &self.program_eye_dome] {
+      *program.borrow_mut() = None;
+    }
+    self.programs.clear();
+    *self.background_texture.borrow_mut() = None;
+    *self.oit_targets.borrow_mut() = None;
+    *self.shadow_map.borrow_mut() = None;
+    *self.msaa_target.borrow_mut() = None;
+    *self.fxaa_target.borrow_mut() = None;
+    *self.post_targets.borrow_mut() = None;
+    *self.mask_target.borrow_mut() = None;
+    *self.glyph_atlas.borrow_mut() = None;
+    *self.eye_dome_target.borrow_mut() = None;
+    self.textures.borrow_mut().invalidate();
+    self.point_buffers.borrow_mut().invalidate();
+    self.batch_buffers.borrow_mut().invalidate();
+    self.gpu_timer.borrow_mut().invalidate();
+  }
+
+  /// Replace the background. Any cached background image texture is released
+  pub fn set_background(&mut self, background: Background) {
+    self.settings.background = background;
+    self.background_texture = std::rc::Rc::new(std::cell::RefCell::new(None));
+  }
+
+  /// Number of batches whose merged geometry is held on the GPU. Buffers are released at the start of the frame
+  /// following the drop of the last copy of the batch, or its merge after a change of elements
+  pub fn n_batch_buffers(&self) -> usize { self.batch_buffers.borrow().len() }
+
+  /// `Renderer` sharing the programs of this one, with its own offscreen targets, for an image of `width` x `height` pixels
+  fn offscreen(&self, width: u32, height: u32, transparent: bool) -> Renderer {
+    let camera = self.camera.clone().width(width as f32).height(height as f32);
+    let mut renderer = Renderer::new(camera, self.program_lines.clone(), self.program_triangles_with_normals.clone(), self.settings.clone());
+    renderer.program_background = self.program_background.clone();
+    renderer.program_composite = self.program_composite.clone();
+    renderer.program_shadow_ground = self.program_shadow_ground.clone();
+    renderer.programs = self.programs.clone();
+    renderer.hovered = self.hovered.clone();
+    renderer.selected = self.selected.clone();
+    renderer.program_mask = self.program_mask.clone();
+    renderer.program_outline = self.program_outline.clone();
+    renderer.program_wide_lines = self.program_wide_lines.clone();
+    renderer.program_labels = self.program_labels.clone();
+    renderer.glyph_atlas = self.glyph_atlas.clone();
+    renderer.textures = self.textures.clone();
+    renderer.program_legend = self.program_legend.clone();
+    renderer.program_points = self.program_points.clone();
+    renderer.point_buffers = self.point_buffers.clone();
+    renderer.edges = self.edges.clone();
+    renderer.bounds = self.bounds.clone();
+    renderer.batch_buffers = self.batch_buffers.clone();
+    renderer.program_eye_dome = self.program_eye_dome.clone();
+    if transparent { 
+      renderer.set_background(Background::Transparent);
+    } else {
+      renderer.background_texture = self.background_texture.clone();
+    }
+    renderer
+  }
+
+  /// Size of the frame drawn: the tile size when rendering a screenshot, the camera size otherwise
+  fn size(&self) -> (i32, i32) {
+    match &self.tile {
+      Some(tile) => (tile.width, tile.height),
+      None => (self.camera.get_width() as i32, self.camera.get_height() as i32),
+    }
+  }
+
+  /// World to clip space matrix, restricted to the tile when rendering a screenshot
+  fn matrix(&self) -> Result<nalgebra::Matrix4<f32>, Error> {
+    let matrix = nalgebra::Matrix4::from_column_slice(self.camera.as_matrix()?.as_slice());
+    Ok(match &self.tile {
+      Some(tile) => tile.matrix * matrix,
+      None => matrix,
+    })
+  }
+
+  /// Camera to clip space matrix, restricted to the tile when rendering a screenshot
+  fn projection(&self) -> Result<nalgebra::Matrix4<f32>, Error> {
+    let projection = nalgebra::Matrix4::from_column_slice(self.camera.as_projection_matrix()?.as_slice());
+    Ok(match &self.tile {
+      Some(tile) => tile.matrix * projection,
+      None => projection,
+    })
+  }
+
+  /// Camera eye position, retrieved from the view matrix
+  fn eye(&self) -> Result<nalgebra::Point3<f32>, Error> {
+    let view = nalgebra::Matrix4::from_column_slice(self.camera.as_view_matrix()?.as_slice());
+    let inverse = view.try_inverse().ok_or("Unable to inverse view matrix")?;
+    Ok(inverse.transform_point(&nalgebra::Point3::origin()))
+  }
+
+  /// Bind the offscreen targets used for order-independent transparency, creating them if required.
+  /// Returns whether order-independent transparency is used for the frame
+  fn bind_oit_targets(&self, context: &web_sys::WebGl2RenderingContext) -> Result<bool, Error> {
+    if self.settings.transparency != Transparency::WeightedBlended { return Ok(false); }
+    let (width, height) = self.size();
+    let valid = self.oit_targets.borrow().as_ref().map(|t| t.has_size(width, height)).unwrap_or(false);
+    if !valid {
+      *self.oit_targets.borrow_mut() = transparency::WeightedBlendedTargets::new(context, width, height)?;
+    }
+    match self.oit_targets.borrow().as_ref() {
+      Some(targets) => { targets.bind(context); Ok(true) },
+      None => Ok(false),
+    }
+  }
+
+  /// Draw the transparent elements deferred to the end of the frame
+  fn draw_deferred(&self, context: &web_sys::WebGl2RenderingContext) -> Result<(), Error> {
+    let deferred = std::mem::take(&mut *self.deferred.borrow_mut());
+    let (oit, mut sorted): (Vec<OwnedInfo>, Vec<OwnedInfo>) = deferred.into_iter()
+      .partition(|i| self.oit_active.get() && i.as_info().is_triangles());
+
+    // Sort back-to-front
+    let eye = self.eye()?;
+    sorted.sort_by(|a, b| {
+      let da = (a.as_info().centroid() - eye).norm_squared();
+      let db = (b.as_info().centroid() - eye).norm_squared();
+      db.total_cmp(&da)
+    });
+    let draw_sorted = || -> Result<(), Error> {
+      if sorted.is_empty() { return Ok(()); }
+      context.enable(web_sys::WebGl2RenderingContext::DEPTH_TEST);
+      context.enable(web_sys::WebGl2RenderingContext::BLEND);
+      context.blend_func(web_sys::WebGl2RenderingContext::SRC_ALPHA, web_sys::WebGl2RenderingContext::ONE_MINUS_SRC_ALPHA);
+      context.depth_mask(false);
+      for info in sorted.iter() { self.draw_info(context, &info.as_info(), transparency::OitPass::Color)?; }
+      context.depth_mask(true);
+      context.disable(web_sys::WebGl2RenderingContext::BLEND);
+      Ok(())
+    };
+
+    if self.oit_active.get() {
+      let targets = self.oit_targets.borrow();
+      let targets = targets.as_ref().ok_or("Unable to retrieve order-independent transparency targets...")?;
+      targets.resolve(context, &self.counters, &self.program_composite,
+        |pass| {
+          context.enable(web_sys::WebGl2RenderingContext::DEPTH_TEST);
+          for info in oit.iter() { self.draw_info(context, &info.as_info(), pass)?; }
+          Ok(())
+        },
+        draw_sorted,
+        if self.settings.antialiasing == Antialiasing::None { programcomposite::CompositeMode::Copy } else { programcomposite::CompositeMode::Fxaa },
+        self.frame_output().as_ref())?;
+    } else {
+      draw_sorted()?;
+    }
+    Ok(())
   }
 }
 
 impl RendererTrait for Renderer {
-  /// Initialise render
+  /// Initialise render. Nothing is drawn in the frame while the context is lost
   fn init(&self, context: &web_sys::WebGl2RenderingContext) -> Result<(), Error> {
-    context.viewport(0, 0, self.camera.get_width() as i32, self.camera.get_height() as i32);
-    context.clear_color(6.0/255.0, 78.0/255.0, 59.0/255.0, 1.0);
-    context.clear(web_sys::WebGl2RenderingContext::COLOR_BUFFER_BIT | web_sys::WebGl2RenderingContext::DEPTH_BUFFER_BIT);
+    if !self.check_context(context)? { return Ok(()); }
+    self.counters.begin_frame();
+    self.legend_range.set(None);
+    self.time_pass(context, "opaque");
+    let (width, height) = self.size();
+    context.viewport(0, 0, width, height);
+    self.deferred.borrow_mut().clear();
+    self.queued.borrow_mut().clear();
+    self.outlines.borrow_mut().clear();
+    self.labels.borrow_mut().clear();
+    self.solids.borrow_mut().clear();
+    self.eye_dome.borrow_mut().clear();
+    *self.clip_planes.borrow_mut() = self.camera.clipping().equations();
+    self.textures.borrow_mut().release_unused(context);
+    self.point_buffers.borrow_mut().release_unused(context);
+    self.edges.borrow_mut().release_unused();
+    self.bounds.borrow_mut().release_unused();
+    self.batch_buffers.borrow_mut().release_unused(context);
+    self.light_matrix.set(None);
+    self.post_active.set(self.prepare_post_targets(context)?);
+    self.oit_active.set(self.bind_oit_targets(context)?);
+    let antialiasing = if self.oit_active.get() { Antialiasing::None } else { self.bind_antialiasing_target(context)? };
+    self.antialiasing_active.set(antialiasing);
+    self.bind_frame_target(context)?;
+    self.settings.background.draw(context, &self.counters, &self.program_background, &self.background_texture)?;
     context.enable(web_sys::WebGl2RenderingContext::CULL_FACE);
     context.enable(web_sys::WebGl2RenderingContext::DEPTH_TEST);
+    // Push filled triangles back so that edges drawn over them do not z-fight
+    context.enable(web_sys::WebGl2RenderingContext::POLYGON_OFFSET_FILL);
+    context.polygon_offset(1.0, 1.0);
     Ok(())
   }
 
-  /// Draw information provided
+  /// Draw information provided according to the display mode and highlight styles, unless culled outside the view.
+  /// Transparent elements are deferred to `end`
   fn draw(&self, 
+    context: &web_sys::WebGl2RenderingContext,
+    info: Info
+    ) -> Result<(), Error> {
+    if self.context_state.is_lost() { return Ok(()); }
+    if self.settings.shadows.is_some() && !self.replaying.get() {
+      // Drawn once the shadow map is rendered
+      self.queued.borrow_mut().push(info.to_owned_info());
+      return Ok(());
+    }
+    if self.legend_range.get().is_none() { self.legend_range.set(Renderer::auto_scalar_range(&info)); }
+    if self.settings.frustum_culling && self.is_outside_view(&info)? {
+      self.counters.record_culled();
+      return Ok(());
+    }
+    match self.highlight_split(&info) {
+      Some(split) => {
+        self.outlines.borrow_mut().extend(split.outlines);
+        for part in split.parts.iter() { self.draw_displayed(context, part.as_info())?; }
+        Ok(())
+      },
+      None => self.draw_displayed(context, info),
+    }
+  }
+
+  /// Post render: draw the elements queued for shadows, cap the clipped solids, shade the point clouds with eye-dome lighting,
+  /// then draw the transparent elements and the labels, resolve
+  /// anti-aliasing, outline the highlighted elements, apply post-processing and draw the colour legend, the axis triad
+  /// and the render statistics over the result. The axis triad and the statistics are not drawn in screenshots
+  fn end(&self, context: &web_sys::WebGl2RenderingContext) -> Result<(), Error> {
+    if self.context_state.is_lost() { return Ok(()); }
+    if self.settings.shadows.is_some() { self.draw_queued(context)?; }
+    self.draw_caps(context)?;
+    self.time_pass(context, "eye_dome");
+    self.draw_eye_dome(context)?;
+    self.time_pass(context, "transparent");
+    self.draw_deferred(context)?;
+    self.time_pass(context, "labels");
+    self.draw_labels(context)?;
+    self.time_pass(context, "resolve");
+    self.resolve_antialiasing(context)?;
+    self.time_pass(context, "outlines");
+    self.draw_outlines(context)?;
+    if self.post_active.get() {
+      self.time_pass(context, "post_processing");
+      self.post_process(context)?;
+    }
+    self.time_pass(context, "overlays");
+    if let Some(legend) = &self.settings.color_legend { self.draw_color_legend(context, legend)?; }
+    if self.settings.axis_triad.visible && self.tile.is_none() { self.draw_axis_triad(context)?; }
+    self.end_statistics(context)
+  }
+}
+
+impl Renderer {
+  /// Draw information provided according to the display mode
+  fn draw_displayed(&self, 
     context: &web_sys::WebGl2RenderingContext,
     info: Info
     ) -> Result<(), Error> {
     match info {
-      Info::Lines { uid: _, vertices } => {
-        if self.program_lines.borrow().is_none() {
-          *self.program_lines.borrow_mut() = Some(programlines::ProgramLines::webgl_program(context)?);
+      Info::Label { .. } => {
+        // Drawn over the scene
+        self.labels.borrow_mut().push(info.to_owned_info());
+        Ok(())
+      },
+      Info::TrianglesWithNormals { .. } | Info::IndexedTrianglesWithNormals { .. } | Info::InstancedTrianglesWithNormals { .. }
+      | Info::TexturedTriangles { .. } | Info::ScalarTriangles { .. } | Info::Batched { .. } if info.is_triangles() => {
+        let (uid, material) = (info.uid(), info.material());
+        let mode = material.display_mode().unwrap_or(self.settings.display_mode);
+        let edges = self.edges.borrow_mut().edges(uid, &info, mode, self.settings.crease_angle);
+        if mode.shows_surfaces() {
+          self.draw_or_defer(context, info)?;
+        } else if mode == DisplayMode::HiddenLine {
+          // Surfaces only fill the depth buffer
+          context.color_mask(false, false, false, false);
+          self.draw_info(context, &info, transparency::OitPass::Color)?;
+          context.color_mask(true, true, true, true);
+        }
+        if let Some(edges) = edges {
+          let edges = Info::Lines { uid, vertices: edges.as_ref(), material };
+          if mode == DisplayMode::HiddenLine {
+            // Drawn once all surfaces have filled the depth buffer
+            self.deferred.borrow_mut().push(edges.to_owned_info());
+          } else {
+            self.draw_or_defer(context, edges)?;
+          }
+        }
+        Ok(())
+      },
+      _ => self.draw_or_defer(context, info),
+    }
+  }
+
+  /// Draw information provided, or defer it to `end` if transparent or shaded by eye-dome lighting. Opaque triangles
+  /// are kept to cap their sections when clipped
+  fn draw_or_defer(&self, 
+    context: &web_sys::WebGl2RenderingContext,
+    info: Info
+    ) -> Result<(), Error> {
+    if info.is_transparent() {
+      self.deferred.borrow_mut().push(info.to_owned_info());
+      Ok(())
+    } else if matches!(info, Info::Points { style, .. } if style.eye_dome_lighting() > 0.0) {
+      self.eye_dome.borrow_mut().push(info.to_owned_info());
+      Ok(())
+    } else {
+      if info.is_triangles() && self.camera.clipping().caps() && !self.clip_planes.borrow().is_empty() {
+        self.solids.borrow_mut().push(info.to_owned_info());
+      }
+      self.draw_info(context, &info, transparency::OitPass::Color)
+    }
+  }
+
+  /// Draw information provided using the fragment output of the given pass
+  fn draw_info(&self, 
+    context: &web_sys::WebGl2RenderingContext,
+    info: &Info,
+    pass: transparency::OitPass,
+    ) -> Result<(), Error> {
+    match *info {
+      Info::Batched { key, ref info, .. } => {
+        // Wide lines are drawn from the positions of their segments
+        if matches!(**info, Info::IndexedLines { material, .. } if !material.line_style().is_thin()) { return self.draw_info(context, info, pass); }
+        let mut batch_buffers = self.batch_buffers.borrow_mut();
+        let buffers = batch_buffers.buffers(context, &self.counters, key, info, None)?;
+        match **info {
+          Info::IndexedLines { material, .. } => self.draw_lines(context, Vertices::Buffers(buffers), material),
+          Info::IndexedTrianglesWithNormals { material, .. } => 
+            self.draw_triangles(context, Vertices::Buffers(buffers), None, None, material, pass),
+          _ => self.draw_info(context, info, pass),
         }
-        let program = self.program_lines.borrow();
-        let program = program.as_ref().ok_or("Unable to retrieve program to draw lines...")?;
-        context.use_program(Some(program));
+      },
+
+      Info::Label { .. } => Ok(()),
 
-        // Bind vertices
+      Info::Lines { uid: _, vertices, material } => {
         let positions = vertices.iter()
         .fold(Vec::new(), |mut r, (s, e)| {r.push(s); r.push(e); r});
-        let positions = utils::point3_to_vecf32(&positions);
-        utils::bind(context, program, "vPosition", &positions)?;
+        self.draw_lines(context, Vertices::Arrays(&utils::point3_to_vecf32(&positions), &[], None), material)
+      },
+
+      Info::IndexedLines { uid: _, positions, indices, material } => {
+        let positions = positions.iter().collect::<Vec<_>>();
+        self.draw_lines(context, Vertices::Arrays(&utils::point3_to_vecf32(&positions), &[], Some(indices)), material)
+      },
 
-        // Bind camera
-        let u_matrix = context.get_uniform_location(program, "uMatrix");
-        context.uniform_matrix4fv_with_f32_array(u_matrix.as_ref(), false, self.camera.as_matrix()?.as_slice());
+      Info::TrianglesWithNormals { uid: _, vertices, normals, material } => {
+        let positions = vertices.iter()
+        .fold(Vec::new(), |mut r, (p1, p2, p3)| {r.push(p1); r.push(p2); r.push(p3); r});
+        let normals = normals.iter()
+        .fold(Vec::new(), |mut r, (n1, n2, n3)| {r.push(n1); r.push(n2); r.push(n3); r});
+        self.draw_triangles(context, Vertices::Arrays(&utils::point3_to_vecf32(&positions), &utils::vector3_to_vecf32(&normals), None), None, None, material, pass)
+      },
 
-        let n: i32 = (2*vertices.len()).try_into()?;
-        context.draw_arrays(web_sys::WebGl2RenderingContext::LINES, 0, n);
+      Info::IndexedTrianglesWithNormals { uid: _, positions, normals, indices, material } => {
+        let positions = positions.iter().collect::<Vec<_>>();
+        let normals = normals.iter().collect::<Vec<_>>();
+        self.draw_triangles(context, Vertices::Arrays(&utils::point3_to_vecf32(&positions), &utils::vector3_to_vecf32(&normals), Some(indices)), None, None, material, pass)
       },
 
-      Info::TrianglesWithNormals { uid: _, vertices, normals } => {
+      Info::InstancedTrianglesWithNormals { uid: _, positions, normals, indices, instances, material } => {
+        let positions = positions.iter().collect::<Vec<_>>();
+        let normals = normals.iter().collect::<Vec<_>>();
+        self.draw_triangles(context, Vertices::Arrays(&utils::point3_to_vecf32(&positions), &utils::vector3_to_vecf32(&normals), Some(indices)), Some(instances), None, material, pass)
+      },
+
+      Info::TexturedTriangles { uid: _, positions, normals, uvs, indices, texture, material } => {
+        let positions = positions.iter().collect::<Vec<_>>();
+        let normals = normals.iter().collect::<Vec<_>>();
+        let uvs = uvs.iter().flat_map(|uv| [uv.x, uv.y]).collect::<Vec<f32>>();
+        self.draw_triangles(context, Vertices::Arrays(&utils::point3_to_vecf32(&positions), &utils::vector3_to_vecf32(&normals), Some(indices)), None, Some(VertexColoring::Texture(texture, &uvs)), material, pass)
+      },
+
+      Info::ScalarTriangles { uid: _, positions, normals, scalars, indices, style, material } => {
+        let positions = positions.iter().collect::<Vec<_>>();
+        let normals = normals.iter().collect::<Vec<_>>();
+        let range = style.resolved_range(scalars);
+        let coloring = VertexColoring::Scalars(style, scalars, range);
+        self.draw_triangles(context, Vertices::Arrays(&utils::point3_to_vecf32(&positions), &utils::vector3_to_vecf32(&normals), Some(indices)), None, Some(coloring), material, pass)
+      },
+
+      Info::Points { uid: _, points, style, scalar_style, material } => {
+        let coloring = if points.scalars().is_empty() {
+          programpoints::PointColoring::Colors(material.surface_color())
+        } else {
+          let range = scalar_style.range().unwrap_or(points.scalar_range());
+          self.textures.borrow_mut().bind_colormap(context, scalar_style.colormap())?;
+          programpoints::PointColoring::Scalars(scalar_style, range, material.surface_color().a())
+        };
+        self.draw_points(context, points, style, coloring)
+      },
+
+      Info::Custom { uid: _, program, positions, attributes, indices, uniforms, material } => {
+        let color = if program.primitive() == Primitive::Lines { material.line_color() } else { material.surface_color() };
+        self.draw_custom(context, program, positions, attributes, indices, uniforms, &color, false)
+      },
+    }
+  }
+
+  /// Draw geometry with a custom program in `color`, or with its picking variant writing `color` if `picking`
+  #[allow(clippy::too_many_arguments)]
+  fn draw_custom(&self,
+    context: &web_sys::WebGl2RenderingContext,
+    custom: &CustomProgram,
+    positions: &[nalgebra::Point3<f32>],
+    attributes: &[(String, Vec<f32>)],
+    indices: Option<&Indices>,
+    uniforms: &[(String, Uniform)],
+    color: &Color,
+    picking: bool,
+    ) -> Result<(), Error> {
+    let program = &custom.webgl_program(context, &self.programs, picking)?;
+    self.counters.use_program(context, program);
+
+    let view = self.camera.as_view_matrix()?;
+    custom.set_camera_uniforms(context, program, &view, self.matrix()?.as_slice(), &self.camera.as_transpose_inverse_view_matrix()?, self.size());
+    clipping::set_uniforms(context, program, &self.clip_planes.borrow());
+    if picking {
+      let u_pick_id = context.get_uniform_location(program, "u_pickId");
+      context.uniform4f(u_pick_id.as_ref(), color.r(), color.g(), color.b(), 1.0);
+    } else {
+      let u_color = context.get_uniform_location(program, "u_color");
+      context.uniform4f(u_color.as_ref(), color.r(), color.g(), color.b(), color.a());
+      self.settings.lighting.set_uniforms(context, program, &nalgebra::Matrix4::from_column_slice(view.as_slice()))?;
+      self.set_shadow_uniforms(context, program);
+    }
+    custom.draw(context, &self.counters, program, &utils::point3_to_vecf32(&positions.iter().collect::<Vec<_>>()), attributes, indices, uniforms)
+  }
+
+  /// Draw the points from their cached GPU buffers
+  fn draw_points(&self,
+    context: &web_sys::WebGl2RenderingContext,
+    points: &std::rc::Rc<pointcloud::PointData>,
+    style: &PointStyle,
+    coloring: programpoints::PointColoring,
+    ) -> Result<(), Error> {
+    if points.is_empty() { return Ok(()); }
+    let pixels_per_unit = 0.5 * self.size().1 as f32 * self.projection()?[(1, 1)];
+    let mut point_buffers = self.point_buffers.borrow_mut();
+    let buffers = point_buffers.buffers(context, &self.counters, points, false)?;
+    programpoints::ProgramPoints::draw(context, &self.counters, &self.program_points, buffers, points.len(), style, coloring, &self.matrix()?,
+      pixels_per_unit, &self.clip_planes.borrow())
+  }
+
+  /// Draw lines from flattened positions, optionally indexed
+  fn draw_lines(&self,
+    context: &web_sys::WebGl2RenderingContext,
+    vertices: Vertices,
+    material: &Material,
+    ) -> Result<(), Error> {
+    if !material.lines_visible() { return Ok(()); }
+    if !material.line_style().is_thin() {
+      let Vertices::Arrays(positions, _, indices) = vertices else { return Err("Wide lines require vertex arrays".into()) };
+      return self.draw_wide_lines(context, positions, indices, material.line_style(), &material.line_color());
+    }
+    if self.program_lines.borrow().is_none() {
+      *self.program_lines.borrow_mut() = Some(programlines::ProgramLines::webgl_program(context)?);
+    }
+    let program = self.program_lines.borrow();
+    let program = program.as_ref().ok_or("Unable to retrieve program to draw lines...")?;
+    self.counters.use_program(context, program);
+
+    // Bind vertices
+    vertices.bind(context, &self.counters, program, "vPosition")?;
+
+    // Bind camera
+    let u_matrix = context.get_uniform_location(program, "uMatrix");
+    context.uniform_matrix4fv_with_f32_array(u_matrix.as_ref(), false, self.matrix()?.as_slice());
+
+    // Set color
+    let color = material.line_color();
+    let u_color = context.get_uniform_location(program, "u_color");
+    context.uniform4f(u_color.as_ref(), color.r(), color.g(), color.b(), color.a());
+    clipping::set_uniforms(context, program, &self.clip_planes.borrow());
+
+    vertices.draw(context, &self.counters, web_sys::WebGl2RenderingContext::LINES, None)
+  }
+
+  /// Draw lines from flattened positions, optionally indexed, as screen-space quads
+  fn draw_wide_lines(&self,
+    context: &web_sys::WebGl2RenderingContext,
+    positions: &[f32],
+    indices: Option<&Indices>,
+    style: &LineStyle,
+    color: &Color,
+    ) -> Result<(), Error> {
+    let segments = programwidelines::Segments::new(positions, indices);
+    let colors = std::iter::repeat_n(color.as_array(), segments.len()).flatten().collect::<Vec<f32>>();
+    programwidelines::ProgramWideLines::draw(context, &self.counters, &self.program_wide_lines, &segments, &colors, style, &self.matrix()?, self.size(), &self.clip_planes.borrow())
+  }
+
+  /// Draw triangles, optionally once per instance and optionally coloured per vertex. Textured triangles are not drawn
+  /// while their image is loading
+  fn draw_triangles(&self,
+    context: &web_sys::WebGl2RenderingContext,
+    vertices: Vertices,
+    instances: Option<&Instances>,
+    coloring: Option<VertexColoring>,
+    material: &Material,
+    pass: transparency::OitPass,
+    ) -> Result<(), Error> {
+    let variant = match coloring {
+      Some(VertexColoring::Texture(..)) => Some(programtriangleswithnormals::TrianglesVariant::Textured),
+      Some(VertexColoring::Scalars(..)) => Some(programtriangleswithnormals::TrianglesVariant::Scalar),
+      _ if instances.is_some() => Some(programtriangleswithnormals::TrianglesVariant::Instanced),
+      _ => None,
+    };
+    let program = match variant {
+      Some(variant) => programtriangleswithnormals::ProgramTrianglesWithNormals::webgl_program_variant(context, &self.programs, variant)?,
+      None => {
         if self.program_triangles_with_normals.borrow().is_none() {
           *self.program_triangles_with_normals.borrow_mut() = Some(programtriangleswithnormals::ProgramTrianglesWithNormals::webgl_program(context)?);
         }
-        let program = self.program_triangles_with_normals.borrow();
-        let program = program.as_ref().ok_or("Unable to retrieve program to draw lines...")?;
-        context.use_program(Some(program));
+        self.program_triangles_with_normals.borrow().clone().ok_or("Unable to retrieve program to draw triangles...")?
+      },
+    };
+    let program = &program;
+    self.counters.use_program(context, program);
 
-        // Bind vertices
-        let positions = vertices.iter()
-        .fold(Vec::new(), |mut r, (p1, p2, p3)| {r.push(p1); r.push(p2); r.push(p3); r});
-        let positions = utils::point3_to_vecf32(&positions);
-        utils::bind(context, program, "a_position", &positions)?;
+    // Bind vertices and normals
+    vertices.bind(context, &self.counters, program, "a_position")?;
+    
+    // Set color
+    let color = material.surface_color();
+    let u_color = context.get_uniform_location(program, "u_color");
+    context.uniform4f(u_color.as_ref(), color.r(), color.g(), color.b(), color.a());
+    let u_shininess = context.get_uniform_location(program, "u_shininess");
+    context.uniform1f(u_shininess.as_ref(), material.shininess());
+    let u_specular = context.get_uniform_location(program, "u_specular");
+    context.uniform1f(u_specular.as_ref(), material.specular());
+    let u_oit_pass = context.get_uniform_location(program, "u_oitPass");
+    context.uniform1i(u_oit_pass.as_ref(), pass as i32);
+    clipping::set_uniforms(context, program, &self.clip_planes.borrow());
 
-        // Bind normals
-        let normals = normals.iter()
-        .fold(Vec::new(), |mut r, (n1, n2, n3)| {r.push(n1); r.push(n2); r.push(n3); r});
-        let normals = utils::vector3_to_vecf32(&normals);
-        utils::bind(context, program, "a_normal", &normals)?;
-        
-        // Set color
-        let u_color = context.get_uniform_location(program, "u_color");
-        context.uniform4f(u_color.as_ref(), 0.3, 0.3, 0.3, 1.0);
-
-        // Set camera
-        let u_worldview_projection = context.get_uniform_location(program, "uWorldviewProjection");
-        context.uniform_matrix4fv_with_f32_array(u_worldview_projection.as_ref(), false, self.camera.as_matrix()?.as_slice());
-        let u_worldview = context.get_uniform_location(program, "uWorldviewInverseTranspose");
-        context.uniform_matrix4fv_with_f32_array(u_worldview.as_ref(), false, self.camera.as_transpose_inverse_view_matrix()?.as_slice());
-
-        // Set lighting
-        let u_reverse_light_direction = context.get_uniform_location(program, "u_reverseLightDirection");
-        context.uniform3f(u_reverse_light_direction.as_ref(), 1.0, 1.0, 1.0);
-
-        let n: i32 = (3*vertices.len()).try_into()?;
-        context.draw_arrays(web_sys::WebGl2RenderingContext::TRIANGLES, 0, n);
+    // Set camera
+    let view = self.camera.as_view_matrix()?;
+    let u_worldview = context.get_uniform_location(program, "uWorldview");
+    context.uniform_matrix4fv_with_f32_array(u_worldview.as_ref(), false, view.as_slice());
+    let u_worldview_projection = context.get_uniform_location(program, "uWorldviewProjection");
+    context.uniform_matrix4fv_with_f32_array(u_worldview_projection.as_ref(), false, self.matrix()?.as_slice());
+    let u_worldview_inverse_transpose = context.get_uniform_location(program, "uWorldviewInverseTranspose");
+    context.uniform_matrix4fv_with_f32_array(u_worldview_inverse_transpose.as_ref(), false, self.camera.as_transpose_inverse_view_matrix()?.as_slice());
+
+    // Set lighting
+    self.settings.lighting.set_uniforms(context, program, &nalgebra::Matrix4::from_column_slice(view.as_slice()))?;
+    self.set_shadow_uniforms(context, program);
+
+    match coloring {
+      Some(VertexColoring::Texture(texture, uvs)) => {
+        if !self.textures.borrow_mut().bind(context, texture)? { return Ok(()); }
+        let u_texture = context.get_uniform_location(program, "u_texture");
+        context.uniform1i(u_texture.as_ref(), 0);
+        utils::bind_with_size(context, &self.counters, program, "a_uv", uvs, 2)?;
+      },
+      Some(VertexColoring::Scalars(style, scalars, (min, max))) => {
+        self.textures.borrow_mut().bind_colormap(context, style.colormap())?;
+        let u_colormap = context.get_uniform_location(program, "u_colormap");
+        context.uniform1i(u_colormap.as_ref(), 0);
+        let u_range = context.get_uniform_location(program, "u_range");
+        let (min, max) = ScalarStyle::colormap_range((min, max));
+        context.uniform2f(u_range.as_ref(), min, max);
+        let u_bands = context.get_uniform_location(program, "u_bands");
+        context.uniform1f(u_bands.as_ref(), style.bands() as f32);
+        utils::bind_with_size(context, &self.counters, program, "a_scalar", scalars, 1)?;
+      },
+      None => (),
+    }
+
+    match instances {
+      Some(instances) => {
+        let mut locations = utils::bind_per_instance(context, &self.counters, program, "a_instanceMatrix", &utils::matrices_to_vecf32(&instances.matrices), 4, 4)?;
+        locations.append(&mut utils::bind_per_instance(context, &self.counters, program, "a_instanceNormalMatrix", &utils::normal_matrices_to_vecf32(&instances.matrices), 3, 3)?);
+        locations.append(&mut utils::bind_per_instance(context, &self.counters, program, "a_instanceColor", &utils::colors_to_vecf32(&instances.colors), 1, 4)?);
+        vertices.draw(context, &self.counters, web_sys::WebGl2RenderingContext::TRIANGLES, Some(instances.len()))?;
+        utils::unbind_per_instance(context, &locations);
+        Ok(())
+      },
+      None => vertices.draw(context, &self.counters, web_sys::WebGl2RenderingContext::TRIANGLES, None),
+    }
+  }
+}
+
+impl Renderer {
+  /// Render the shadow map from the elements queued, draw them and draw the ground plane
+  fn draw_queued(&self, context: &web_sys::WebGl2RenderingContext) -> Result<(), Error> {
+    let queued = std::mem::take(&mut *self.queued.borrow_mut());
+    let shadows = self.settings.shadows.as_ref().ok_or("Shadows are not enabled")?;
+    let bounds = queued.iter()
+      .filter_map(|i| i.as_info().bounding_box())
+      .reduce(|(min1, max1), (min2, max2)| (min1.inf(&min2), max1.sup(&max2)));
+
+    if let (Some(bounds), Some((_, direction))) = (bounds, self.settings.lighting.shadow_light()) {
+      let valid = self.shadow_map.borrow().as_ref().map(|m| m.has_resolution(context, shadows.resolution())).unwrap_or(false);
+      if !valid {
+        *self.shadow_map.borrow_mut() = Some(shadows::ShadowMap::new(context, shadows.resolution())?);
+      }
+      let light_matrix = Shadows::light_matrix(&direction, &bounds);
+      {
+        let shadow_map = self.shadow_map.borrow();
+        let shadow_map = shadow_map.as_ref().ok_or("Unable to retrieve shadow map...")?;
+        self.time_pass(context, "shadows");
+        shadow_map.render(context, || {
+          for info in queued.iter() { self.draw_shadow_depth(context, &info.as_info(), &light_matrix)?; }
+          Ok(())
+        })?;
+        self.time_pass(context, "opaque");
+      }
+      self.light_matrix.set(Some(light_matrix));
+
+      self.bind_frame_target(context)?;
+      let (width, height) = self.size();
+      context.viewport(0, 0, width, height);
+    }
+
+    self.replaying.set(true);
+    let r = queued.iter().try_for_each(|info| self.draw(context, info.as_info()));
+    self.replaying.set(false);
+    r?;
+
+    if let (Some(bounds), Some(light_matrix)) = (bounds, self.light_matrix.get()) {
+      if let Some((positions, _, indices)) = shadows.ground_plane(&bounds) {
+        self.draw_ground_plane(context, &positions, &indices, &light_matrix, shadows.ground_opacity())?;
+      }
+    }
+    Ok(())
+  }
+
+  /// Draw opaque triangles into the shadow map
+  fn draw_shadow_depth(&self,
+    context: &web_sys::WebGl2RenderingContext,
+    info: &Info,
+    light_matrix: &nalgebra::Matrix4<f32>,
+    ) -> Result<(), Error> {
+    if info.is_transparent() || !info.is_triangles() { return Ok(()); }
+    let mut batch_buffers = self.batch_buffers.borrow_mut();
+    let positions;
+    let (vertices, instances) = match info {
+      Info::Batched { key, info, .. } => (Vertices::Buffers(batch_buffers.buffers(context, &self.counters, key, info, None)?), None),
+      Info::TrianglesWithNormals { vertices, .. } => {
+        positions = utils::point3_to_vecf32(&vertices.iter().flat_map(|(p1, p2, p3)| [p1, p2, p3]).collect());
+        (Vertices::Arrays(&positions, &[], None), None)
+      },
+      Info::IndexedTrianglesWithNormals { positions: p, indices, .. } | Info::TexturedTriangles { positions: p, indices, .. }
+      | Info::ScalarTriangles { positions: p, indices, .. } => {
+        positions = utils::point3_to_vecf32(&p.iter().collect());
+        (Vertices::Arrays(&positions, &[], Some(*indices)), None)
+      },
+      Info::InstancedTrianglesWithNormals { positions: p, indices, instances, .. } => {
+        positions = utils::point3_to_vecf32(&p.iter().collect());
+        (Vertices::Arrays(&positions, &[], Some(*indices)), Some(*instances))
+      },
+      Info::Lines { .. } | Info::IndexedLines { .. } | Info::Label { .. } | Info::Points { .. } | Info::Custom { .. } => return Ok(()),
+    };
+
+    let program = &programshadow::ProgramShadowDepth::webgl_program(context, &self.programs, instances.is_some())?;
+    self.counters.use_program(context, program);
+
+    let u_light_matrix = context.get_uniform_location(program, "uLightMatrix");
+    context.uniform_matrix4fv_with_f32_array(u_light_matrix.as_ref(), false, light_matrix.as_slice());
+    clipping::set_uniforms(context, program, &self.clip_planes.borrow());
+    vertices.bind(context, &self.counters, program, "a_position")?;
+    match instances {
+      Some(instances) => {
+        let locations = utils::bind_per_instance(context, &self.counters, program, "a_instanceMatrix", &utils::matrices_to_vecf32(&instances.matrices), 4, 4)?;
+        vertices.draw(context, &self.counters, web_sys::WebGl2RenderingContext::TRIANGLES, Some(instances.len()))?;
+        utils::unbind_per_instance(context, &locations);
+        Ok(())
       },
+      None => vertices.draw(context, &self.counters, web_sys::WebGl2RenderingContext::TRIANGLES, None),
+    }
+  }
+
+  /// Draw the plane receiving shadows, blended over the opaque elements
+  fn draw_ground_plane(&self,
+    context: &web_sys::WebGl2RenderingContext,
+    positions: &[nalgebra::Point3<f32>],
+    indices: &Indices,
+    light_matrix: &nalgebra::Matrix4<f32>,
+    opacity: f32,
+    ) -> Result<(), Error> {
+    if self.program_shadow_ground.borrow().is_none() {
+      *self.program_shadow_ground.borrow_mut() = Some(programshadow::ProgramShadowGround::webgl_program(context)?);
+    }
+    let program = self.program_shadow_ground.borrow();
+    let program = program.as_ref().ok_or("Unable to retrieve program to draw ground plane...")?;
+    self.counters.use_program(context, program);
+
+    let shadows = self.settings.shadows.as_ref().ok_or("Shadows are not enabled")?;
+    let shadow_map = self.shadow_map.borrow();
+    shadows.set_uniforms(context, program, light_matrix, shadow_map.as_ref().map(|m| m.texture()));
+    let u_worldview_projection = context.get_uniform_location(program, "uWorldviewProjection");
+    context.uniform_matrix4fv_with_f32_array(u_worldview_projection.as_ref(), false, self.matrix()?.as_slice());
+    let u_opacity = context.get_uniform_location(program, "u_opacity");
+    context.uniform1f(u_opacity.as_ref(), opacity);
+
+    let positions = positions.iter().collect::<Vec<_>>();
+    utils::bind(context, &self.counters, program, "a_position", &utils::point3_to_vecf32(&positions))?;
+
+    // Visible from both sides, without hiding elements drawn later
+    context.disable(web_sys::WebGl2RenderingContext::CULL_FACE);
+    context.enable(web_sys::WebGl2RenderingContext::BLEND);
+    context.blend_func(web_sys::WebGl2RenderingContext::SRC_ALPHA, web_sys::WebGl2RenderingContext::ONE_MINUS_SRC_ALPHA);
+    context.depth_mask(false);
+    let r = utils::draw(context, &self.counters, web_sys::WebGl2RenderingContext::TRIANGLES, positions.len(), Some(indices));
+    context.depth_mask(true);
+    context.disable(web_sys::WebGl2RenderingContext::BLEND);
+    context.enable(web_sys::WebGl2RenderingContext::CULL_FACE);
+    r
+  }
+
+  /// Set the shadow uniforms of the triangle programs. Shadows are disabled when the shadow map was not rendered for the frame
+  fn set_shadow_uniforms(&self, context: &web_sys::WebGl2RenderingContext, program: &web_sys::WebGlProgram) {
+    let shadow_map = self.shadow_map.borrow();
+    let shadow = self.settings.shadows.as_ref()
+      .zip(self.light_matrix.get())
+      .zip(self.settings.lighting.shadow_light())
+      .zip(shadow_map.as_ref());
+    let u_shadow_light = context.get_uniform_location(program, "u_shadowLight");
+    match shadow {
+      Some((((shadows, light_matrix), (index, _)), shadow_map)) => {
+        shadows.set_uniforms(context, program, &light_matrix, Some(shadow_map.texture()));
+        context.uniform1i(u_shadow_light.as_ref(), index);
+      },
+      None => {
+        Shadows::default().set_uniforms(context, program, &nalgebra::Matrix4::identity(), None);
+        context.uniform1i(u_shadow_light.as_ref(), -1);
+      },
+    }
+  }
+}
+
+impl Renderer {
+  /// Bind the anti-aliasing target, creating it if required. Returns the anti-aliasing used for the frame
+  fn bind_antialiasing_target(&self, context: &web_sys::WebGl2RenderingContext) -> Result<Antialiasing, Error> {
+    let (width, height) = self.size();
+    match self.settings.antialiasing {
+      Antialiasing::None => Ok(Antialiasing::None),
+      Antialiasing::Msaa(samples) => {
+        let valid = self.msaa_target.borrow().as_ref().map(|t| t.has_size(context, width, height, samples)).unwrap_or(false);
+        if !valid {
+          *self.msaa_target.borrow_mut() = antialiasing::MsaaTarget::new(context, width, height, samples)?;
+        }
+        match self.msaa_target.borrow().as_ref() {
+          Some(target) => { target.bind(context); Ok(Antialiasing::Msaa(samples)) },
+          None => Ok(Antialiasing::None),
+        }
+      },
+      Antialiasing::Fxaa => {
+        let valid = self.fxaa_target.borrow().as_ref().map(|t| t.has_size(width, height)).unwrap_or(false);
+        if !valid {
+          *self.fxaa_target.borrow_mut() = Some(antialiasing::FxaaTarget::new(context, width, height)?);
+        }
+        self.fxaa_target.borrow().as_ref().ok_or("Unable to retrieve FXAA target...")?.bind(context);
+        Ok(Antialiasing::Fxaa)
+      },
+    }
+  }
+
+  /// Bind the framebuffer that the frame is drawn into
+  fn bind_frame_target(&self, context: &web_sys::WebGl2RenderingContext) -> Result<(), Error> {
+    if self.oit_active.get() {
+      self.oit_targets.borrow().as_ref().ok_or("Unable to retrieve order-independent transparency targets...")?.bind(context);
+      return Ok(());
+    }
+    match self.antialiasing_active.get() {
+      Antialiasing::None => context.bind_framebuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER, self.frame_output().as_ref()),
+      Antialiasing::Msaa(_) => self.msaa_target.borrow().as_ref().ok_or("Unable to retrieve MSAA target...")?.bind(context),
+      Antialiasing::Fxaa => self.fxaa_target.borrow().as_ref().ok_or("Unable to retrieve FXAA target...")?.bind(context),
+    }
+    Ok(())
+  }
+
+  /// Highlight colour of the element `uid`, if hovered or selected
+  fn highlight_color(&self, uid: &str) -> Option<Color> {
+    if self.hovered.as_deref() == Some(uid) {
+      Some(self.settings.highlight.hovered_color())
+    } else if self.selected.contains(uid) {
+      Some(self.settings.highlight.selected_color())
+    } else {
+      None
+    }
+  }
+
+  /// Split the information according to the highlight colour of its elements, if any is highlighted
+  fn highlight_split(&self, info: &Info) -> Option<highlight::Split> {
+    if self.hovered.is_none() && self.selected.is_empty() { return None; }
+    highlight::split(info, self.settings.highlight.tint(), |uid| self.highlight_color(uid))
+  }
+
+  /// Draw the labels over the scene, in the order drawn
+  fn draw_labels(&self, context: &web_sys::WebGl2RenderingContext) -> Result<(), Error> {
+    let labels = std::mem::take(&mut *self.labels.borrow_mut());
+    if labels.is_empty() { return Ok(()); }
+    let matrix = self.matrix()?;
+    for info in labels.iter() {
+      programlabels::ProgramLabels::draw(context, &self.counters, &self.program_labels, &self.glyph_atlas, &info.as_info(), &matrix, self.size(), None, &self.clip_planes.borrow())?;
+    }
+    context.enable(web_sys::WebGl2RenderingContext::DEPTH_TEST);
+    Ok(())
+  }
+
+  /// Draw the colour legend in its corner of the output framebuffer, placed in the full image when rendering a
+  /// screenshot. Not drawn when the style has no range
+  fn draw_color_legend(&self, context: &web_sys::WebGl2RenderingContext, legend: &ColorLegend) -> Result<(), Error> {
+    // Nothing to show without a range nor element coloured over the range of its values
+    let Some((min, max)) = legend.style().range().or(self.legend_range.get()) else { return Ok(()); };
+    let (min, max) = ScalarStyle::colormap_range((min, max));
+    let image = (self.camera.get_width() as i32, self.camera.get_height() as i32);
+    let pixels = nalgebra::Matrix4::new_orthographic(0.0, image.0 as f32, 0.0, image.1 as f32, -1.0, 1.0);
+    let matrix = match &self.tile {
+      Some(tile) => tile.matrix * pixels,
+      None => pixels,
     };
 
+    if self.glyph_atlas.borrow().is_none() {
+      *self.glyph_atlas.borrow_mut() = Some(glyphatlas::GlyphAtlas::new(context)?);
+    }
+    let layout = {
+      let mut atlas = self.glyph_atlas.borrow_mut();
+      let atlas = atlas.as_mut().ok_or("Unable to retrieve glyph atlas...")?;
+      let layout = legend.layout((min, max), image, |_| 0.0);
+      atlas.prepare(&layout.texts.iter().map(|(_, text)| text.as_str()).collect::<String>())?;
+      legend.layout((min, max), image, |text| atlas.text_width(text, legend.font_size()))
+    };
+
+    context.bind_framebuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER, self.output.as_ref());
+    programlegend::ProgramLegend::draw(context, &self.counters, &self.program_legend, &mut self.textures.borrow_mut(), legend, &layout, &matrix)?;
+    let uid = String::new();
+    let style = LabelStyle { font_size: legend.font_size(), padding: 0.0, leader: false, ..LabelStyle::default() };
+    let material = material::materialbuilder::MaterialBuilder::new().color(legend.text_color()).build()?;
+    for ([x, y], text) in layout.texts.iter() {
+      let anchor = nalgebra::Point3::new(*x, *y, 0.0);
+      let info = Info::Label { uid: &uid, anchor: &anchor, text, style: &style, material: &material };
+      programlabels::ProgramLabels::draw(context, &self.counters, &self.program_labels, &self.glyph_atlas, &info, &matrix, self.size(), None, &[])?;
+    }
+    context.enable(web_sys::WebGl2RenderingContext::DEPTH_TEST);
+    Ok(())
+  }
+
+  /// Range of the values colouring the information, if coloured by a scalar style without range
+  fn auto_scalar_range(info: &Info) -> Option<(f32, f32)> {
+    match *info {
+      Info::ScalarTriangles { scalars, style, .. } if style.range().is_none() => Some(style.resolved_range(scalars)),
+      Info::Points { points, scalar_style, .. } if scalar_style.range().is_none() && !points.scalars().is_empty() => Some(points.scalar_range()),
+      _ => None,
+    }
+  }
+
+  /// Draw the axis triad in its corner viewport of the output framebuffer
+  fn draw_axis_triad(&self, context: &web_sys::WebGl2RenderingContext) -> Result<(), Error> {
+    let triad = &self.settings.axis_triad;
+    let (x, y, width, height) = triad.viewport(self.size());
+    context.bind_framebuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER, self.output.as_ref());
+    context.viewport(x, y, width, height);
+    context.enable(web_sys::WebGl2RenderingContext::SCISSOR_TEST);
+    context.scissor(x, y, width, height);
+    context.clear(web_sys::WebGl2RenderingContext::DEPTH_BUFFER_BIT);
+    context.disable(web_sys::WebGl2RenderingContext::SCISSOR_TEST);
+    context.enable(web_sys::WebGl2RenderingContext::DEPTH_TEST);
+    context.disable(web_sys::WebGl2RenderingContext::CULL_FACE);
+
+    let view = nalgebra::Matrix4::from_column_slice(self.camera.as_view_matrix()?.as_slice());
+    let matrix = AxisTriad::matrix(&view);
+    let axes = AxisTriad::AXES.map(|(axis, color, name)| (nalgebra::Vector3::from(axis), Color::rgb(color[0], color[1], color[2]), name));
+
+    // Shafts
+    let positions = axes.iter().flat_map(|(axis, _, _)| [0.0, 0.0, 0.0, AxisTriad::SHAFT*axis.x, AxisTriad::SHAFT*axis.y, AxisTriad::SHAFT*axis.z]).collect::<Vec<f32>>();
+    let colors = axes.iter().flat_map(|(_, color, _)| color.as_array()).collect::<Vec<f32>>();
+    let style = LineStyle::new().with_width(3.0)?.with_cap(LineCap::Round).with_join(LineJoin::Round);
+    programwidelines::ProgramWideLines::draw(context, &self.counters, &self.program_wide_lines, &programwidelines::Segments::new(&positions, None), &colors, &style, &matrix, (width, height), &[])?;
+
+    // Heads, in flat colours
+    if self.program_mask.borrow().is_none() {
+      *self.program_mask.borrow_mut() = Some(picker::ProgramPicker::webgl_program(context)?);
+    }
+    {
+      let program = self.program_mask.borrow();
+      let program = program.as_ref().ok_or("Unable to retrieve axis triad program...")?;
+      self.counters.use_program(context, program);
+      let u_matrix = context.get_uniform_location(program, "uMatrix");
+      context.uniform_matrix4fv_with_f32_array(u_matrix.as_ref(), false, matrix.as_slice());
+      clipping::set_uniforms(context, program, &[]);
+      let u_id = context.get_uniform_location(program, "u_id");
+      for (axis, color, _) in axes.iter() {
+        context.uniform4f(u_id.as_ref(), color.r(), color.g(), color.b(), 1.0);
+        let head = AxisTriad::head(axis);
+        utils::bind(context, &self.counters, program, "a_position", &head)?;
+        utils::draw(context, &self.counters, web_sys::WebGl2RenderingContext::TRIANGLES, head.len() / 3, None)?;
+      }
+    }
+
+    // Names beyond the heads
+    let uid = String::new();
+    let style = LabelStyle { font_size: (triad.size as f32 / 7.0).max(9.0), padding: 0.0, leader: false, ..LabelStyle::default() };
+    for (axis, color, name) in axes.iter() {
+      let anchor = nalgebra::Point3::from(1.18 * axis);
+      let material = material::materialbuilder::MaterialBuilder::new().color(*color).build()?;
+      let info = Info::Label { uid: &uid, anchor: &anchor, text: &name.to_string(), style: &style, material: &material };
+      programlabels::ProgramLabels::draw(context, &self.counters, &self.program_labels, &self.glyph_atlas, &info, &matrix, (width, height), None, &[])?;
+    }
+
+    let (width, height) = self.size();
+    context.viewport(0, 0, width, height);
+    context.enable(web_sys::WebGl2RenderingContext::CULL_FACE);
+    context.enable(web_sys::WebGl2RenderingContext::DEPTH_TEST);
+    Ok(())
+  }
+
+  /// Cap the sections of the opaque solids cut by each clipping plane: the parity of the solid faces behind each
+  /// pixel is counted in the stencil buffer, and the plane is drawn where odd, clipped by the other planes
+  fn draw_caps(&self, context: &web_sys::WebGl2RenderingContext) -> Result<(), Error> {
+    let solids = std::mem::take(&mut *self.solids.borrow_mut());
+    let bounds = solids.iter()
+      .filter_map(|i| i.as_info().bounding_box())
+      .reduce(|(min1, max1), (min2, max2)| (min1.inf(&min2), max1.sup(&max2)));
+    let Some((min, max)) = bounds else { return Ok(()); };
+    let center = nalgebra::center(&min, &max);
+    let radius = 0.5 * (max - min).norm();
+    let material = material::materialbuilder::MaterialBuilder::new().color(self.camera.clipping().cap_color()).build()?;
+    let equations = self.clip_planes.borrow().clone();
+
+    context.enable(web_sys::WebGl2RenderingContext::STENCIL_TEST);
+    context.disable(web_sys::WebGl2RenderingContext::CULL_FACE);
+    context.clear_stencil(0);
+    let mut result = Ok(());
+    for (i, equation) in equations.iter().enumerate() {
+      // Parity of the faces clipped by this plane only
+      context.clear(web_sys::WebGl2RenderingContext::STENCIL_BUFFER_BIT);
+      *self.clip_planes.borrow_mut() = vec![*equation];
+      context.color_mask(false, false, false, false);
+      context.depth_mask(false);
+      context.disable(web_sys::WebGl2RenderingContext::DEPTH_TEST);
+      context.stencil_func(web_sys::WebGl2RenderingContext::ALWAYS, 0, 0xff);
+      context.stencil_op(web_sys::WebGl2RenderingContext::KEEP, web_sys::WebGl2RenderingContext::KEEP, web_sys::WebGl2RenderingContext::INVERT);
+      result = solids.iter().try_for_each(|info| self.draw_mask(context, &info.as_info(), &material.surface_color()));
+      context.color_mask(true, true, true, true);
+      context.depth_mask(true);
+      context.enable(web_sys::WebGl2RenderingContext::DEPTH_TEST);
+      if result.is_err() { break; }
+
+      // Square of the plane covering the solids, facing the side cut away
+      let normal = -nalgebra::Vector3::new(equation[0], equation[1], equation[2]);
+      let origin = center + (equation[3] - normal.dot(&center.coords)) * normal;
+      let u = normal.cross(&if normal.x.abs() < 0.9 { nalgebra::Vector3::x() } else { nalgebra::Vector3::y() }).normalize() * radius;
+      let v = normal.cross(&u);
+      let corners = [origin - u - v, origin + u - v, origin + u + v, origin - u + v];
+      let positions = [0, 1, 2, 0, 2, 3].iter().flat_map(|k| [corners[*k].x, corners[*k].y, corners[*k].z]).collect::<Vec<f32>>();
+      let normals = std::iter::repeat_n([normal.x, normal.y, normal.z], 6).flatten().collect::<Vec<f32>>();
+      *self.clip_planes.borrow_mut() = equations.iter().enumerate().filter(|(j, _)| *j != i).map(|(_, e)| *e).collect();
+      context.stencil_func(web_sys::WebGl2RenderingContext::NOTEQUAL, 0, 0xff);
+      context.stencil_op(web_sys::WebGl2RenderingContext::KEEP, web_sys::WebGl2RenderingContext::KEEP, web_sys::WebGl2RenderingContext::KEEP);
+      result = self.draw_triangles(context, Vertices::Arrays(&positions, &normals, None), None, None, &material, transparency::OitPass::Color);
+      if result.is_err() { break; }
+    }
+    context.disable(web_sys::WebGl2RenderingContext::STENCIL_TEST);
+    context.enable(web_sys::WebGl2RenderingContext::CULL_FACE);
+    *self.clip_planes.borrow_mut() = equations;
+    result
+  }
+
+  /// Draw the point clouds with eye-dome lighting into their own target, then shade them onto the frame with the
+  /// largest strength of their styles
+  fn draw_eye_dome(&self, context: &web_sys::WebGl2RenderingContext) -> Result<(), Error> {
+    let clouds = std::mem::take(&mut *self.eye_dome.borrow_mut());
+    if clouds.is_empty() { return Ok(()); }
+    let (width, height) = self.size();
+    let valid = self.eye_dome_target.borrow().as_ref().map(|t| t.has_size(width, height)).unwrap_or(false);
+    if !valid {
+      *self.eye_dome_target.borrow_mut() = Some(eyedome::EyeDomeTarget::new(context, width, height)?);
+    }
+    let target = self.eye_dome_target.borrow();
+    let target = target.as_ref().ok_or("Unable to retrieve eye-dome lighting target...")?;
+
+    target.bind(context);
+    context.enable(web_sys::WebGl2RenderingContext::DEPTH_TEST);
+    let mut strength: f32 = 0.0;
+    for info in clouds.iter() {
+      let info = info.as_info();
+      if let Info::Points { style, .. } = info { strength = strength.max(style.eye_dome_lighting()); }
+      self.draw_info(context, &info, transparency::OitPass::Color)?;
+    }
+
+    self.bind_frame_target(context)?;
+    eyedome::ProgramEyeDome::draw(context, &self.counters, &self.program_eye_dome, target, strength, &self.projection()?)
+  }
+
+  /// Draw the outline of the highlighted elements into the frame, through other geometry
+  fn draw_outlines(&self, context: &web_sys::WebGl2RenderingContext) -> Result<(), Error> {
+    let outlines = self.outlines.borrow();
+    if outlines.is_empty() || self.settings.highlight.outline_width() == 0 { return Ok(()); }
+    let (width, height) = self.size();
+    let valid = self.mask_target.borrow().as_ref().map(|t| t.has_size(width, height)).unwrap_or(false);
+    if !valid {
+      *self.mask_target.borrow_mut() = Some(highlight::MaskTarget::new(context, width, height)?);
+    }
+    let mask_target = self.mask_target.borrow();
+    let mask_target = mask_target.as_ref().ok_or("Unable to retrieve highlight mask target...")?;
+
+    mask_target.bind(context);
+    context.viewport(0, 0, width, height);
+    context.disable(web_sys::WebGl2RenderingContext::DEPTH_TEST);
+    context.disable(web_sys::WebGl2RenderingContext::CULL_FACE);
+    for (info, color) in outlines.iter() { self.draw_mask(context, &info.as_info(), color)?; }
+    context.enable(web_sys::WebGl2RenderingContext::CULL_FACE);
+
+    context.bind_framebuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER, self.frame_output().as_ref());
+    programoutline::ProgramOutline::draw(context, &self.counters, &self.program_outline, mask_target.texture(), (width, height), self.settings.highlight.outline_width())?;
+    context.enable(web_sys::WebGl2RenderingContext::DEPTH_TEST);
     Ok(())
   }
+
+  /// Draw the information in the highlight `color` into the mask
+  fn draw_mask(&self, 
+    context: &web_sys::WebGl2RenderingContext,
+    info: &Info,
+    color: &Color,
+    ) -> Result<(), Error> {
+    if let Info::InstancedTrianglesWithNormals { positions, indices, instances, .. } = *info {
+      let program = &picker::ProgramPicker::webgl_program_instanced(context, &self.programs)?;
+      self.counters.use_program(context, program);
+      let u_matrix = context.get_uniform_location(program, "uMatrix");
+      context.uniform_matrix4fv_with_f32_array(u_matrix.as_ref(), false, self.matrix()?.as_slice());
+      clipping::set_uniforms(context, program, &self.clip_planes.borrow());
+
+      let colors = instances.colors.iter().map(|c| c.with_alpha(1.0)).collect::<Vec<_>>();
+      let positions = positions.iter().collect::<Vec<_>>();
+      utils::bind(context, &self.counters, program, "a_position", &utils::point3_to_vecf32(&positions))?;
+      let mut locations = utils::bind_per_instance(context, &self.counters, program, "a_instanceMatrix", &utils::matrices_to_vecf32(&instances.matrices), 4, 4)?;
+      locations.append(&mut utils::bind_per_instance(context, &self.counters, program, "a_instanceId", &utils::colors_to_vecf32(&colors), 1, 4)?);
+      utils::draw_instanced(context, &self.counters, web_sys::WebGl2RenderingContext::TRIANGLES, positions.len(), Some(indices), instances.len())?;
+      utils::unbind_per_instance(context, &locations);
+      return Ok(());
+    }
+    if let Info::Batched { ref info, .. } = *info { return self.draw_mask(context, info, color); }
+    if let Info::Custom { program, positions, attributes, indices, uniforms, .. } = *info {
+      return self.draw_custom(context, program, positions, attributes, indices, uniforms, color, true);
+    }
+    if let Info::Label { .. } = *info {
+      return programlabels::ProgramLabels::draw(context, &self.counters, &self.program_labels, &self.glyph_atlas, info, &self.matrix()?, self.size(),
+        Some(color.with_alpha(1.0).as_array()), &self.clip_planes.borrow());
+    }
+
+    if self.program_mask.borrow().is_none() {
+      *self.program_mask.borrow_mut() = Some(picker::ProgramPicker::webgl_program(context)?);
+    }
+    let program = self.program_mask.borrow();
+    let program = program.as_ref().ok_or("Unable to retrieve mask program...")?;
+    self.counters.use_program(context, program);
+    let u_matrix = context.get_uniform_location(program, "uMatrix");
+    context.uniform_matrix4fv_with_f32_array(u_matrix.as_ref(), false, self.matrix()?.as_slice());
+    let u_id = context.get_uniform_location(program, "u_id");
+    context.uniform4f(u_id.as_ref(), color.r(), color.g(), color.b(), 1.0);
+    clipping::set_uniforms(context, program, &self.clip_planes.borrow());
+
+    match *info {
+      Info::Lines { vertices, material, .. } => {
+        if !material.lines_visible() { return Ok(()); }
+        let positions = utils::point3_to_vecf32(&vertices.iter().fold(Vec::new(), |mut r, (s, e)| {r.push(s); r.push(e); r}));
+        if !material.line_style().is_thin() { return self.draw_wide_lines(context, &positions, None, material.line_style(), &color.with_alpha(1.0)); }
+        utils::bind(context, &self.counters, program, "a_position", &positions)?;
+        utils::draw(context, &self.counters, web_sys::WebGl2RenderingContext::LINES, positions.len() / 3, None)
+      },
+      Info::IndexedLines { positions, indices, material, .. } => {
+        if !material.lines_visible() { return Ok(()); }
+        let positions = utils::point3_to_vecf32(&positions.iter().collect::<Vec<_>>());
+        if !material.line_style().is_thin() { return self.draw_wide_lines(context, &positions, Some(indices), material.line_style(), &color.with_alpha(1.0)); }
+        utils::bind(context, &self.counters, program, "a_position", &positions)?;
+        utils::draw(context, &self.counters, web_sys::WebGl2RenderingContext::LINES, positions.len() / 3, Some(indices))
+      },
+      Info::TrianglesWithNormals { vertices, .. } => {
+        let positions = vertices.iter().fold(Vec::new(), |mut r, (p1, p2, p3)| {r.push(p1); r.push(p2); r.push(p3); r});
+        utils::bind(context, &self.counters, program, "a_position", &utils::point3_to_vecf32(&positions))?;
+        utils::draw(context, &self.counters, web_sys::WebGl2RenderingContext::TRIANGLES, positions.len(), None)
+      },
+      Info::IndexedTrianglesWithNormals { positions, indices, .. } | Info::TexturedTriangles { positions, indices, .. }
+      | Info::ScalarTriangles { positions, indices, .. } => {
+        let positions = positions.iter().collect::<Vec<_>>();
+        utils::bind(context, &self.counters, program, "a_position", &utils::point3_to_vecf32(&positions))?;
+        utils::draw(context, &self.counters, web_sys::WebGl2RenderingContext::TRIANGLES, positions.len(), Some(indices))
+      },
+      Info::Points { points, style, .. } => self.draw_points(context, points, style, programpoints::PointColoring::Flat(color.with_alpha(1.0))),
+      Info::InstancedTrianglesWithNormals { .. } | Info::Batched { .. } | Info::Label { .. } | Info::Custom { .. } => Ok(()),
+    }
+  }
+
+  /// Framebuffer the resolved frame is drawn into: the post-processing scene target if post-processing is active,
+  /// the output framebuffer otherwise
+  fn frame_output(&self) -> Option<web_sys::WebGlFramebuffer> {
+    if self.post_active.get() {
+      self.post_targets.borrow().as_ref().map(|t| t.framebuffer().clone())
+    } else {
+      self.output.clone()
+    }
+  }
+
+  /// Create the post-processing targets if required, resized to the frame.
+  /// Returns whether post-processing is applied to the frame
+  fn prepare_post_targets(&self, context: &web_sys::WebGl2RenderingContext) -> Result<bool, Error> {
+    if self.settings.post_processing.is_empty() { return Ok(false); }
+    let (width, height) = self.size();
+    let valid = self.post_targets.borrow().as_ref().map(|t| t.has_size(width, height)).unwrap_or(false);
+    if !valid {
+      *self.post_targets.borrow_mut() = Some(postprocessing::PostTargets::new(context, width, height)?);
+    }
+    Ok(true)
+  }
+
+  /// Apply the post-processing passes to the frame, drawing the result into the output framebuffer
+  fn post_process(&self, context: &web_sys::WebGl2RenderingContext) -> Result<(), Error> {
+    let targets = self.post_targets.borrow();
+    let targets = targets.as_ref().ok_or("Unable to retrieve post-processing targets...")?;
+    // The depth of the scene is in the framebuffer it was drawn into when resolved by another pass
+    if self.oit_active.get() {
+      targets.copy_depth(context, self.oit_targets.borrow().as_ref().ok_or("Unable to retrieve order-independent transparency targets...")?.framebuffer());
+    } else {
+      match self.antialiasing_active.get() {
+        Antialiasing::None => (),
+        Antialiasing::Msaa(_) => targets.copy_depth(context, self.msaa_target.borrow().as_ref().ok_or("Unable to retrieve MSAA target...")?.framebuffer()),
+        Antialiasing::Fxaa => targets.copy_depth(context, self.fxaa_target.borrow().as_ref().ok_or("Unable to retrieve FXAA target...")?.framebuffer()),
+      }
+    }
+    targets.run(context, &self.counters, &self.programs, self.settings.post_processing.passes(), self.output.as_ref())
+  }
+
+  /// Draw the anti-aliased frame into the output framebuffer
+  fn resolve_antialiasing(&self, context: &web_sys::WebGl2RenderingContext) -> Result<(), Error> {
+    match self.antialiasing_active.get() {
+      Antialiasing::None => Ok(()),
+      Antialiasing::Msaa(_) => {
+        self.msaa_target.borrow().as_ref().ok_or("Unable to retrieve MSAA target...")?.resolve(context, self.frame_output().as_ref());
+        Ok(())
+      },
+      Antialiasing::Fxaa => self.fxaa_target.borrow().as_ref().ok_or("Unable to retrieve FXAA target...")?.resolve(context, &self.counters, &self.program_composite, self.frame_output().as_ref()),
+    }
+  }
 }
 
+impl Renderer {
+  /// Time the following draw calls on the GPU as the `pass`, until the next pass. Screenshots are not timed
+  fn time_pass(&self, context: &web_sys::WebGl2RenderingContext, pass: &str) {
+    if self.tile.is_none() { self.gpu_timer.borrow_mut().begin(context, pass); }
+  }
+
+  /// Keep the statistics of the frame, with the GPU time of the latest frame measured, and draw them if shown
+  fn end_statistics(&self, context: &web_sys::WebGl2RenderingContext) -> Result<(), Error> {
+    // The overlay is not counted
+    let statistics = self.counters.frame().with_gpu_times(self.gpu_timer.borrow().times());
+    if let (Some(corner), None) = (self.settings.statistics_overlay, &self.tile) { self.draw_statistics(context, &statistics, corner)?; }
+    if self.tile.is_none() { self.gpu_timer.borrow_mut().end_frame(context); }
+    *self.statistics.borrow_mut() = statistics;
+    Ok(())
+  }
+
+  /// Draw the statistics as text over the `corner` of the output framebuffer
+  fn draw_statistics(&self, context: &web_sys::WebGl2RenderingContext, statistics: &RenderStatistics, corner: Corner) -> Result<(), Error> {
+    let image = self.size();
+    let matrix = nalgebra::Matrix4::new_orthographic(0.0, image.0 as f32, 0.0, image.1 as f32, -1.0, 1.0);
+    let text = statistics.summary();
+    let style = LabelStyle { font_size: 12.0, padding: 6.0, background: Some(Color::new(0.0, 0.0, 0.0, 0.6)), leader: false, ..LabelStyle::default() };
 
+    if self.glyph_atlas.borrow().is_none() {
+      *self.glyph_atlas.borrow_mut() = Some(glyphatlas::GlyphAtlas::new(context)?);
+    }
+    let (width, height) = {
+      let mut atlas = self.glyph_atlas.borrow_mut();
+      let atlas = atlas.as_mut().ok_or("Unable to retrieve glyph atlas...")?;
+      atlas.prepare(&text)?;
+      let width = text.lines().map(|l| atlas.text_width(l, style.font_size)).fold(0.0, f32::max) + 2.0*style.padding;
+      let line_height = glyphatlas::GlyphAtlas::CELL_HEIGHT * style.font_size / glyphatlas::GlyphAtlas::FONT_SIZE;
+      (width, text.lines().count() as f32 * line_height + 2.0*style.padding)
+    };
+    let (x, y) = corner.origin(image, width.ceil() as i32, height.ceil() as i32, ColorLegend::MARGIN);
+    let anchor = nalgebra::Point3::new(x as f32 + 0.5*width, y as f32 + 0.5*height, 0.0);
+
+    context.bind_framebuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER, self.output.as_ref());
+    let uid = String::new();
+    let material = material::materialbuilder::MaterialBuilder::new().color(Color::new(1.0, 1.0, 1.0, 1.0)).build()?;
+    let info = Info::Label { uid: &uid, anchor: &anchor, text: &text, style: &style, material: &material };
+    programlabels::ProgramLabels::draw(context, &self.counters, &self.program_labels, &self.glyph_atlas, &info, &matrix, image, None, &[])?;
+    context.enable(web_sys::WebGl2RenderingContext::DEPTH_TEST);
+    Ok(())
+  }
+
+  /// Whether the bounding box of the information is entirely outside the view frustum. Labels and custom geometry,
+  /// which may be drawn away from their positions, and instanced geometry are never outside
+  fn is_outside_view(&self, info: &Info) -> Result<bool, Error> {
+    if matches!(info, Info::Label { .. } | Info::Custom { .. } | Info::InstancedTrianglesWithNormals { .. }) { return Ok(false); }
+    let Some((min, max)) = self.bounds.borrow_mut().bounds(info) else { return Ok(false); };
+    let matrix = self.matrix()?;
+    let corners = (0..8)
+      .map(|i| matrix * nalgebra::Vector4::new(
+        if i & 1 == 0 { min.x } else { max.x },
+        if i & 2 == 0 { min.y } else { max.y },
+        if i & 4 == 0 { min.z } else { max.z },
+        1.0))
+      .collect::<Vec<_>>();
+    // Outside if all corners are on the outer side of one of the clip space planes
+    Ok((0..3).any(|axis| corners.iter().all(|c| c[axis] < -c.w) || corners.iter().all(|c| c[axis] > c.w)))
+  }
+}
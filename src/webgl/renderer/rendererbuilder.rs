@@ -8,6 +8,7 @@ pub struct RendererBuilder {
   camera: Option<Camera>,
   program_lines: Option<web_sys::WebGlProgram>,
   program_triangles_with_normals: Option<web_sys::WebGlProgram>,
+  settings: RendererSettings,
 }
 
 #[cfg(feature = "wasm")]
@@ -29,14 +30,88 @@ impl RendererBuilder {
    
   /// Specify a `Camera` object
   pub fn camera(mut self, camera: Camera) -> RendererBuilder { self.camera = Some(camera); self }
+
+  /// Specify a solid background colour
+  pub fn background_color(mut self, color: Color) -> RendererBuilder { self.settings.background = Background::Solid(color); self }
+
+  /// Specify a vertical gradient background, from `bottom` to `top` colours
+  pub fn background_gradient(mut self, top: Color, bottom: Color) -> RendererBuilder { 
+    self.settings.background = Background::VerticalGradient { top, bottom }; self 
+  }
+
+  /// Specify a background image as RGBA bytes of size `width` x `height`, ordered from the top row
+  pub fn background_image(mut self, width: u32, height: u32, data: Vec<u8>) -> RendererBuilder { 
+    self.settings.background = Background::Image { width, height, data }; self 
+  }
+
+  /// Specify a transparent background, for compositing the canvas over HTML content
+  pub fn transparent_background(mut self) -> RendererBuilder { self.settings.background = Background::Transparent; self }
   
+  /// Specify the `Lighting`. Default lighting is used if not specified
+  pub fn lighting(mut self, lighting: Lighting) -> RendererBuilder { self.settings.lighting = lighting; self }
+
+  /// Specify the method used to draw transparent elements. Default to `Transparency::Sorted`
+  pub fn transparency(mut self, transparency: Transparency) -> RendererBuilder { self.settings.transparency = transparency; self }
+
+  /// Specify how triangles are displayed. Default to `DisplayMode::Shaded`. Can be overriden per element by the `Material`
+  pub fn display_mode(mut self, display_mode: DisplayMode) -> RendererBuilder { self.settings.display_mode = display_mode; self }
+
+  /// Specify the crease angle, in degrees, above which the edge between two faces is a feature edge. Default to 30 degrees
+  pub fn crease_angle(mut self, crease_angle_deg: f32) -> RendererBuilder { self.settings.crease_angle = crease_angle_deg.to_radians(); self }
+
+  /// Enable shadows cast by the first directional light of the `Lighting`
+  pub fn shadows(mut self, shadows: Shadows) -> RendererBuilder { self.settings.shadows = Some(shadows); self }
+
+  /// Specify the width and height, in texels, of the shadow map. Enables shadows with default settings if required
+  pub fn shadow_map_resolution(mut self, resolution: u32) -> Result<RendererBuilder, JsError> { 
+    self.settings.shadows = Some(self.settings.shadows.unwrap_or_default().with_resolution(resolution)?); 
+    Ok(self)
+  }
+
+  /// Render into a multisampled renderbuffer with `samples` samples per pixel, limited to the maximum supported
+  pub fn msaa(mut self, samples: u32) -> RendererBuilder { self.settings.antialiasing = Antialiasing::Msaa(samples); self }
+
+  /// Apply a fast approximate anti-aliasing (FXAA) post-pass
+  pub fn fxaa(mut self) -> RendererBuilder { self.settings.antialiasing = Antialiasing::Fxaa; self }
+
+  /// Apply the passes of the `PostProcessing` pipeline to the rendered image
+  pub fn post_processing(mut self, post_processing: PostProcessing) -> RendererBuilder { self.settings.post_processing = post_processing; self }
+
+  /// Specify the styles of the hovered and selected elements. Default styles are used if not specified
+  pub fn highlight(mut self, highlight: Highlight) -> RendererBuilder { self.settings.highlight = highlight; self }
+
+  /// Specify whether the axis triad is shown. Hidden by default
+  pub fn axis_triad_visible(mut self, visible: bool) -> RendererBuilder { self.settings.axis_triad.visible = visible; self }
+
+  /// Specify the corner the axis triad is shown in. Default to `Corner::BottomLeft`
+  pub fn axis_triad_corner(mut self, corner: Corner) -> RendererBuilder { self.settings.axis_triad.corner = corner; self }
+
+  /// Specify the width and height, in pixels, of the axis triad viewport. Default to 96 pixels
+  pub fn axis_triad_size(mut self, size: u32) -> RendererBuilder { self.settings.axis_triad.size = size; self }
+
+  /// Show a colour legend. Not shown by default
+  pub fn color_legend(mut self, color_legend: ColorLegend) -> RendererBuilder { self.settings.color_legend = Some(color_legend); self }
+
+  /// Show the render statistics of each frame in the `corner`. Not shown by default
+  pub fn statistics_overlay(mut self, corner: Corner) -> RendererBuilder { self.settings.statistics_overlay = Some(corner); self }
+
+  /// Specify whether elements outside the view are skipped. Enabled by default
+  pub fn frustum_culling(mut self, enabled: bool) -> RendererBuilder { self.settings.frustum_culling = enabled; self }
+
   /// Build a `Renderer` object
   pub fn build(self) -> Result<Renderer, JsError> {
+    if let Background::Image { width, height, data } = &self.settings.background {
+      let expected = 4 * (*width as usize) * (*height as usize);
+      if data.len() != expected {
+        return Err(format!("Background image of size {width}x{height} requires {expected} bytes, got {}", data.len()).into());
+      }
+    }
     Ok(
       Renderer::new(
         self.camera.ok_or("Camera is not specified")?,
         std::rc::Rc::new(std::cell::RefCell::new(self.program_lines)),
         std::rc::Rc::new(std::cell::RefCell::new(self.program_triangles_with_normals)),
+        self.settings,
       )
     )
   }
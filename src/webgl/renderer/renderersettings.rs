@@ -0,0 +1,49 @@
+use super::*;
+
+/// Settings of a `Renderer`, specified using the `RendererBuilder`
+#[derive(Clone)]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct RendererSettings {
+  pub background:   Background,
+  pub lighting:     Lighting,
+  pub transparency: Transparency,
+  pub display_mode: DisplayMode,
+  /// Minimum angle, in radians, between adjacent faces for their shared edge to be a feature edge
+  pub crease_angle: f32,
+  /// Shadows cast by the first directional light, if enabled
+  pub shadows:      Option<Shadows>,
+  pub antialiasing: Antialiasing,
+  /// Full screen passes applied to the rendered image
+  pub post_processing: PostProcessing,
+  /// Styles of the hovered and selected elements
+  pub highlight:    Highlight,
+  /// Orientation overlay of the world axes
+  pub axis_triad:   AxisTriad,
+  /// Colour legend of a scalar style, if shown
+  pub color_legend: Option<ColorLegend>,
+  /// Corner the render statistics are drawn in, if shown
+  pub statistics_overlay: Option<Corner>,
+  /// Whether elements outside the view are skipped, disabled by default
+  pub frustum_culling: bool,
+}
+
+impl Default for RendererSettings {
+  fn default() -> RendererSettings {
+    RendererSettings {
+      background:   Background::default(),
+      lighting:     Lighting::default(),
+      transparency: Transparency::default(),
+      display_mode: DisplayMode::default(),
+      crease_angle: 30f32.to_radians(),
+      shadows:      None,
+      antialiasing: Antialiasing::default(),
+      post_processing: PostProcessing::default(),
+      highlight:    Highlight::default(),
+      axis_triad:   AxisTriad::default(),
+      color_legend: None,
+      statistics_overlay: None,
+      frustum_culling: false,
+    }
+  }
+}
@@ -0,0 +1,146 @@
+use super::*;
+
+/// Region of a screenshot drawn in one pass. `matrix` maps the clip space of the full image onto the tile
+#[derive(Clone)]
+pub struct Tile {
+  pub width:  i32,
+  pub height: i32,
+  pub matrix: nalgebra::Matrix4<f32>,
+}
+
+impl Tile {
+  /// Tile of `width` x `height` pixels whose bottom-left corner is at pixel (`x`, `y`) of an image of
+  /// `image_width` x `image_height` pixels
+  fn new(x: i32, y: i32, width: i32, height: i32, image_width: i32, image_height: i32) -> Tile {
+    let sx = image_width as f32 / width as f32;
+    let sy = image_height as f32 / height as f32;
+    // Centre of the tile in normalised device coordinates
+    let cx = (2 * x + width) as f32 / image_width as f32 - 1.0;
+    let cy = (2 * y + height) as f32 / image_height as f32 - 1.0;
+    let matrix = nalgebra::Matrix4::new(
+      sx,  0.0, 0.0, -sx * cx,
+      0.0, sy,  0.0, -sy * cy,
+      0.0, 0.0, 1.0, 0.0,
+      0.0, 0.0, 0.0, 1.0,
+    );
+    Tile { width, height, matrix }
+  }
+}
+
+/// Offscreen colour and depth renderbuffers a tile is drawn into
+struct Target {
+  framebuffer: web_sys::WebGlFramebuffer,
+  color:       web_sys::WebGlRenderbuffer,
+  depth:       web_sys::WebGlRenderbuffer,
+}
+
+impl Target {
+  fn new(context: &web_sys::WebGl2RenderingContext, width: i32, height: i32) -> Result<Target, Error> {
+    let color = Self::renderbuffer(context, width, height, web_sys::WebGl2RenderingContext::RGBA8)?;
+    let depth = Self::renderbuffer(context, width, height, web_sys::WebGl2RenderingContext::DEPTH24_STENCIL8)?;
+    let framebuffer = context.create_framebuffer().ok_or("Unable to create frame buffer")?;
+    context.bind_framebuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER, Some(&framebuffer));
+    context.framebuffer_renderbuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER,
+      web_sys::WebGl2RenderingContext::COLOR_ATTACHMENT0,
+      web_sys::WebGl2RenderingContext::RENDERBUFFER,
+      Some(&color)
+    );
+    context.framebuffer_renderbuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER,
+      web_sys::WebGl2RenderingContext::DEPTH_STENCIL_ATTACHMENT,
+      web_sys::WebGl2RenderingContext::RENDERBUFFER,
+      Some(&depth)
+    );
+    let status = context.check_framebuffer_status(web_sys::WebGl2RenderingContext::FRAMEBUFFER);
+    if status != web_sys::WebGl2RenderingContext::FRAMEBUFFER_COMPLETE {
+      context.bind_framebuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER, None);
+      return Err(format!("Screenshot frame buffer incomplete: {}", status).into());
+    }
+    Ok(Target { framebuffer, color, depth })
+  }
+
+  fn renderbuffer(context: &web_sys::WebGl2RenderingContext, width: i32, height: i32, internal_format: u32) -> Result<web_sys::WebGlRenderbuffer, Error> {
+    let renderbuffer = context.create_renderbuffer().ok_or("Unable to create render buffer")?;
+    context.bind_renderbuffer(web_sys::WebGl2RenderingContext::RENDERBUFFER, Some(&renderbuffer));
+    context.renderbuffer_storage(web_sys::WebGl2RenderingContext::RENDERBUFFER, internal_format, width, height);
+    Ok(renderbuffer)
+  }
+
+  /// Read the RGBA pixels of the target, from the bottom row
+  fn read(&self, context: &web_sys::WebGl2RenderingContext, width: i32, height: i32) -> Result<Vec<u8>, Error> {
+    let mut pixels = vec![0u8; rgba_size(width, height)?];
+    context.bind_framebuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER, Some(&self.framebuffer));
+    context.read_pixels_with_opt_u8_array(0, 0, width, height,
+      web_sys::WebGl2RenderingContext::RGBA, web_sys::WebGl2RenderingContext::UNSIGNED_BYTE, Some(&mut pixels))?;
+    Ok(pixels)
+  }
+}
+
+/// Number of bytes of the RGBA pixels of an image of `width` x `height` pixels
+fn rgba_size(width: i32, height: i32) -> Result<usize, Error> {
+  usize::try_from(width)?.checked_mul(usize::try_from(height)?).and_then(|n| n.checked_mul(4))
+    .ok_or(format!("Image of {width}x{height} pixels exceeds the addressable memory").into())
+}
+
+/// Largest tile size supported by the context: the smallest of the maximum renderbuffer size, texture size
+/// (used by the transparency and anti-aliasing targets) and viewport dimensions
+fn max_tile_size(context: &web_sys::WebGl2RenderingContext) -> i32 {
+  let parameter = |p| context.get_parameter(p).ok().and_then(|v| v.as_f64()).unwrap_or(4096.0) as i32;
+  let viewport = context.get_parameter(web_sys::WebGl2RenderingContext::MAX_VIEWPORT_DIMS).ok()
+    .map(|v| js_sys::Int32Array::from(v).to_vec())
+    .and_then(|v| v.into_iter().min())
+    .unwrap_or(4096);
+  parameter(web_sys::WebGl2RenderingContext::MAX_RENDERBUFFER_SIZE)
+    .min(parameter(web_sys::WebGl2RenderingContext::MAX_TEXTURE_SIZE))
+    .min(viewport)
+    .max(1)
+}
+
+/// Draw an image of `width` x `height` pixels tile by tile using `draw`, which renders a tile into the
+/// framebuffer provided. Returns the RGBA pixels of the image, from the top row
+pub fn capture<F>(context: &web_sys::WebGl2RenderingContext, width: u32, height: u32, draw: F) -> Result<Vec<u8>, Error>
+where F: Fn(&Tile, &web_sys::WebGlFramebuffer) -> Result<(), Error> {
+  if width == 0 || height == 0 { return Err("Screenshot size must be non-zero".into()); }
+  let (width, height) = match (i32::try_from(width), i32::try_from(height)) {
+    (Ok(width), Ok(height)) => (width, height),
+    _ => return Err(format!("Screenshot size {width}x{height} exceeds {} pixels", i32::MAX).into()),
+  };
+  let image_size = rgba_size(width, height)?;
+  let tile_size = max_tile_size(context);
+  let (tile_width, tile_height) = (width.min(tile_size), height.min(tile_size));
+  let target = Target::new(context, tile_width, tile_height)?;
+
+  let mut image = vec![0u8; image_size];
+  let result = (0..height).step_by(tile_height as usize)
+    .flat_map(|y| (0..width).step_by(tile_width as usize).map(move |x| (x, y)))
+    .try_for_each(|(x, y)| {
+      let (w, h) = (tile_width.min(width - x), tile_height.min(height - y));
+      draw(&Tile::new(x, y, w, h, width, height), &target.framebuffer)?;
+      let pixels = target.read(context, w, h)?;
+      for row in 0..h {
+        // Pixels are read from the bottom row
+        let start = 4 * ((height - 1 - y - row) as usize * width as usize + x as usize);
+        let source = 4 * row as usize * w as usize;
+        image[start..start + 4 * w as usize].copy_from_slice(&pixels[source..source + 4 * w as usize]);
+      }
+      Ok(())
+    });
+  context.bind_framebuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER, None);
+  context.delete_framebuffer(Some(&target.framebuffer));
+  context.delete_renderbuffer(Some(&target.color));
+  context.delete_renderbuffer(Some(&target.depth));
+  result.map(|_| image)
+}
+
+/// Encode RGBA pixels, from the top row, to PNG
+pub fn encode_png(width: u32, height: u32, pixels: &[u8]) -> Result<Vec<u8>, Error> {
+  let mut bytes = Vec::new();
+  {
+    let mut encoder = png::Encoder::new(&mut bytes, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|e| Error::from(e.to_string()))?;
+    writer.write_image_data(pixels).map_err(|e| Error::from(e.to_string()))?;
+    writer.finish().map_err(|e| Error::from(e.to_string()))?;
+  }
+  Ok(bytes)
+}
@@ -0,0 +1,213 @@
+use super::*;
+
+/// Positions, normals and indices of triangles
+type Mesh = (Vec<nalgebra::Point3<f32>>, Vec<nalgebra::Vector3<f32>>, Indices);
+
+/// Plane drawn below the scene that only receives shadows
+#[derive(Clone, Debug)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct GroundPlane {
+  /// Normal of the plane, pointing towards the scene
+  normal:    nalgebra::Vector3<f32>,
+  /// Position of the plane along the normal. Defaults to the bottom of the scene
+  elevation: Option<f32>,
+  /// Opacity of fully shadowed areas
+  opacity:   f32,
+}
+
+/// Shadows cast by the first directional light of the `Lighting`, using shadow mapping
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[derive(Clone, Debug)]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Shadows {
+  /// Width and height, in texels, of the shadow map
+  resolution:   u32,
+  /// Radius, in texels, of the percentage-closer filtering kernel
+  pcf_radius:   u32,
+  /// Depth bias, in light depth units, applied to avoid shadow acne
+  bias:         f32,
+  ground_plane: Option<GroundPlane>,
+}
+
+impl Default for Shadows {
+  fn default() -> Shadows {
+    Shadows { resolution: 2048, pcf_radius: 1, bias: 0.002, ground_plane: None, }
+  }
+}
+
+impl Shadows {
+  /// Maximum radius of the percentage-closer filtering kernel supported by the shader
+  pub const MAX_PCF_RADIUS: u32 = 3;
+
+  /// Shadow map resolution
+  pub fn resolution(&self) -> u32 { self.resolution }
+
+  /// Set the shadow uniforms of `program`, shared by the triangle and ground plane programs.
+  /// The shadow map texture is bound to texture unit 1. Assumes that the program has already been set.
+  pub fn set_uniforms(&self,
+    context: &web_sys::WebGl2RenderingContext,
+    program: &web_sys::WebGlProgram,
+    light_matrix: &nalgebra::Matrix4<f32>,
+    texture: Option<&web_sys::WebGlTexture>,
+    ) {
+    context.active_texture(web_sys::WebGl2RenderingContext::TEXTURE1);
+    context.bind_texture(web_sys::WebGl2RenderingContext::TEXTURE_2D, texture);
+    context.active_texture(web_sys::WebGl2RenderingContext::TEXTURE0);
+    let u_shadow_map = context.get_uniform_location(program, "u_shadowMap");
+    context.uniform1i(u_shadow_map.as_ref(), 1);
+    let u_light_matrix = context.get_uniform_location(program, "uLightMatrix");
+    context.uniform_matrix4fv_with_f32_array(u_light_matrix.as_ref(), false, light_matrix.as_slice());
+    let u_shadow_texel = context.get_uniform_location(program, "u_shadowTexel");
+    context.uniform1f(u_shadow_texel.as_ref(), 1.0 / self.resolution as f32);
+    let u_shadow_bias = context.get_uniform_location(program, "u_shadowBias");
+    context.uniform1f(u_shadow_bias.as_ref(), self.bias);
+    let u_pcf_radius = context.get_uniform_location(program, "u_pcfRadius");
+    context.uniform1f(u_pcf_radius.as_ref(), self.pcf_radius as f32);
+  }
+
+  /// Orthographic view-projection matrix of the light coming from `direction`, enclosing the bounding box
+  pub fn light_matrix(direction: &nalgebra::Vector3<f32>, bounds: &(nalgebra::Point3<f32>, nalgebra::Point3<f32>)) -> nalgebra::Matrix4<f32> {
+    let center = nalgebra::center(&bounds.0, &bounds.1);
+    let radius = (0.5 * (bounds.1 - bounds.0).norm()).max(1e-3);
+    let direction = direction.normalize();
+    let eye = center + 2.0 * radius * direction;
+    let up = if direction.z.abs() < 0.9 { nalgebra::Vector3::z() } else { nalgebra::Vector3::x() };
+    let view = nalgebra::Isometry3::look_at_rh(&eye, &center, &up).to_homogeneous();
+    let projection = nalgebra::Orthographic3::new(-radius, radius, -radius, radius, radius, 3.0 * radius).to_homogeneous();
+    projection * view
+  }
+
+  /// Ground plane quad, as positions, normals and indices, below the bounding box
+  pub fn ground_plane(&self, bounds: &(nalgebra::Point3<f32>, nalgebra::Point3<f32>)) -> Option<Mesh> {
+    let ground = self.ground_plane.as_ref()?;
+    let normal = ground.normal.try_normalize(1e-6)?;
+    let center = nalgebra::center(&bounds.0, &bounds.1);
+    let radius = (0.5 * (bounds.1 - bounds.0).norm()).max(1e-3);
+    let corners = [bounds.0.x, bounds.1.x].into_iter()
+      .flat_map(|x| [bounds.0.y, bounds.1.y].into_iter().flat_map(move |y| [bounds.0.z, bounds.1.z].into_iter().map(move |z| nalgebra::Vector3::new(x, y, z))))
+      .collect::<Vec<_>>();
+    let elevation = ground.elevation
+      .unwrap_or_else(|| corners.iter().map(|c| c.dot(&normal)).fold(f32::INFINITY, f32::min));
+    let origin = center - (center.coords.dot(&normal) - elevation) * normal;
+    let t = normal.cross(&if normal.z.abs() < 0.9 { nalgebra::Vector3::z() } else { nalgebra::Vector3::x() }).normalize();
+    let c = normal.cross(&t);
+    let positions = [(-1.0f32, -1.0f32), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)].into_iter()
+      .map(|(i, j)| origin + 2.0 * radius * (i * t + j * c))
+      .collect::<Vec<_>>();
+    Some((positions, vec![normal; 4], Indices::new(vec![0, 1, 2, 0, 2, 3])))
+  }
+
+  /// Opacity of fully shadowed areas of the ground plane
+  pub fn ground_opacity(&self) -> f32 { self.ground_plane.as_ref().map(|g| g.opacity).unwrap_or(0.0) }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+impl Shadows {
+  /// Create the default shadow settings: 2048x2048 shadow map, 3x3 filtering kernel and no ground plane
+  pub fn new() -> Shadows { Shadows::default() }
+
+  /// Set the width and height, in texels, of the shadow map
+  pub fn with_resolution(mut self, resolution: u32) -> Result<Shadows, JsError> {
+    if resolution == 0 { return Err("Shadow map resolution must be positive".into()); }
+    self.resolution = resolution; Ok(self)
+  }
+
+  /// Set the radius, in texels, of the percentage-closer filtering kernel. 0 disables filtering
+  pub fn with_pcf_radius(mut self, pcf_radius: u32) -> Result<Shadows, JsError> {
+    if pcf_radius > Self::MAX_PCF_RADIUS { return Err(format!("A maximum filtering radius of {} is supported", Self::MAX_PCF_RADIUS).into()); }
+    self.pcf_radius = pcf_radius; Ok(self)
+  }
+
+  /// Set the depth bias applied to avoid shadow acne
+  pub fn with_bias(mut self, bias: f32) -> Shadows { self.bias = bias; self }
+
+  /// Add a ground plane with `normal` `[x, y, z]` pointing towards the scene that only receives shadows, 
+  /// drawn black with `opacity` where fully shadowed. It is placed below the scene unless `elevation` - the 
+  /// position along the normal - is specified
+  pub fn with_ground_plane(mut self, normal: &[f32], elevation: Option<f32>, opacity: f32) -> Result<Shadows, JsError> {
+    if normal.len() != 3 { return Err("Normal must be specified as [x, y, z]".into()); }
+    self.ground_plane = Some(GroundPlane { normal: nalgebra::Vector3::from_row_slice(normal), elevation, opacity }); 
+    Ok(self)
+  }
+
+  /// Remove the ground plane
+  pub fn without_ground_plane(mut self) -> Shadows { self.ground_plane = None; self }
+
+  /// Expose clone functionality to JavaScript
+  pub fn clone(&self) -> Shadows { Clone::clone(self) }
+
+  /// Convert shadow settings to json
+  pub fn to_json(&self) -> Result<String, JsError> {
+    Ok(serde_json::to_string(&self).map_err(|e| format!("{e}"))?)
+  }
+
+  /// Create shadow settings from json
+  pub fn from_json(json: &str) -> Result<Shadows, JsError> {
+    Ok(serde_json::from_str(json).map_err(|e| format!("{e}"))?)
+  }
+}
+
+/// Depth texture rendered from the light
+pub struct ShadowMap {
+  resolution:  i32,
+  framebuffer: web_sys::WebGlFramebuffer,
+  texture:     web_sys::WebGlTexture,
+}
+
+impl ShadowMap {
+  /// Create the shadow map, with a resolution limited to the maximum texture size
+  pub fn new(context: &web_sys::WebGl2RenderingContext, resolution: u32) -> Result<ShadowMap, Error> {
+    let max_size = context.get_parameter(web_sys::WebGl2RenderingContext::MAX_TEXTURE_SIZE)?.as_f64().unwrap_or(2048.0) as u32;
+    let resolution: i32 = resolution.min(max_size).try_into()?;
+
+    let texture = context.create_texture().ok_or("Unable to create texture")?;
+    context.bind_texture(web_sys::WebGl2RenderingContext::TEXTURE_2D, Some(&texture));
+    context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_MIN_FILTER, web_sys::WebGl2RenderingContext::NEAREST.try_into()?);
+    context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_MAG_FILTER, web_sys::WebGl2RenderingContext::NEAREST.try_into()?);
+    context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_WRAP_S, web_sys::WebGl2RenderingContext::CLAMP_TO_EDGE.try_into()?);
+    context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_WRAP_T, web_sys::WebGl2RenderingContext::CLAMP_TO_EDGE.try_into()?);
+    context.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+      web_sys::WebGl2RenderingContext::TEXTURE_2D, 0, web_sys::WebGl2RenderingContext::DEPTH_COMPONENT24.try_into()?, resolution, resolution, 0, 
+      web_sys::WebGl2RenderingContext::DEPTH_COMPONENT, web_sys::WebGl2RenderingContext::UNSIGNED_INT, None,
+    )?;
+
+    let framebuffer = context.create_framebuffer().ok_or("Unable to create frame buffer")?;
+    context.bind_framebuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER, Some(&framebuffer));
+    context.framebuffer_texture_2d(web_sys::WebGl2RenderingContext::FRAMEBUFFER,
+      web_sys::WebGl2RenderingContext::DEPTH_ATTACHMENT,
+      web_sys::WebGl2RenderingContext::TEXTURE_2D,
+      Some(&texture),
+      0,       // level
+    );
+    let status = context.check_framebuffer_status(web_sys::WebGl2RenderingContext::FRAMEBUFFER);
+    context.bind_framebuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER, None);
+    if status != web_sys::WebGl2RenderingContext::FRAMEBUFFER_COMPLETE { return Err("Shadow map frame buffer is incomplete".into()); }
+    Ok(ShadowMap { resolution, framebuffer, texture })
+  }
+
+  /// Whether the shadow map was created for the resolution provided
+  pub fn has_resolution(&self, context: &web_sys::WebGl2RenderingContext, resolution: u32) -> bool { 
+    let max_size = context.get_parameter(web_sys::WebGl2RenderingContext::MAX_TEXTURE_SIZE).ok()
+      .and_then(|v| v.as_f64()).unwrap_or(2048.0) as u32;
+    self.resolution as u32 == resolution.min(max_size)
+  }
+
+  /// Depth texture
+  pub fn texture(&self) -> &web_sys::WebGlTexture { &self.texture }
+
+  /// Bind and clear the shadow map. `draw` is called to draw the shadow casters, with front faces culled.
+  /// The framebuffer and viewport are left to be restored by the caller
+  pub fn render<F>(&self, context: &web_sys::WebGl2RenderingContext, draw: F) -> Result<(), Error>
+  where F: FnOnce() -> Result<(), Error> {
+    context.bind_framebuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER, Some(&self.framebuffer));
+    context.viewport(0, 0, self.resolution, self.resolution);
+    context.enable(web_sys::WebGl2RenderingContext::DEPTH_TEST);
+    context.depth_mask(true);
+    context.clear(web_sys::WebGl2RenderingContext::DEPTH_BUFFER_BIT);
+    context.cull_face(web_sys::WebGl2RenderingContext::FRONT);
+    let r = draw();
+    context.cull_face(web_sys::WebGl2RenderingContext::BACK);
+    r
+  }
+}
@@ -0,0 +1,275 @@
+use super::*;
+
+/// Statistics of a frame drawn by a `Renderer`: draw calls issued, vertices and primitives drawn, bytes uploaded to
+/// buffers, program switches and elements culled outside the view. The GPU time of the passes is reported where
+/// the `EXT_disjoint_timer_query_webgl2` extension is available, with a latency of a few frames
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct RenderStatistics {
+  draw_calls:       usize,
+  vertices:         usize,
+  triangles:        usize,
+  lines:            usize,
+  points:           usize,
+  buffer_bytes:     usize,
+  program_switches: usize,
+  culled:           usize,
+  /// GPU time of the passes, in milliseconds, in the order drawn
+  gpu_times:        Vec<(String, f64)>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+impl RenderStatistics {
+  /// Number of draw calls
+  pub fn draw_calls(&self) -> usize { self.draw_calls }
+
+  /// Number of vertices processed, over all instances
+  pub fn vertices(&self) -> usize { self.vertices }
+
+  /// Number of triangles drawn
+  pub fn triangles(&self) -> usize { self.triangles }
+
+  /// Number of line segments drawn
+  pub fn lines(&self) -> usize { self.lines }
+
+  /// Number of points drawn
+  pub fn points(&self) -> usize { self.points }
+
+  /// Number of bytes uploaded to vertex and index buffers
+  pub fn buffer_bytes(&self) -> usize { self.buffer_bytes }
+
+  /// Number of changes of the program in use
+  pub fn program_switches(&self) -> usize { self.program_switches }
+
+  /// Number of elements not drawn as outside the view
+  pub fn culled_elements(&self) -> usize { self.culled }
+
+  /// Names of the passes timed on the GPU, empty if GPU timing is not available
+  pub fn gpu_passes(&self) -> Vec<String> { self.gpu_times.iter().map(|(pass, _)| pass.clone()).collect() }
+
+  /// GPU time of the pass, in milliseconds, if timed
+  pub fn gpu_time(&self, pass: &str) -> Option<f64> { self.gpu_times.iter().find(|(p, _)| p == pass).map(|(_, t)| *t) }
+
+  /// GPU time of the frame, in milliseconds, if timed
+  pub fn gpu_total(&self) -> Option<f64> {
+    if self.gpu_times.is_empty() { None } else { Some(self.gpu_times.iter().map(|(_, t)| t).sum()) }
+  }
+
+  /// Statistics as text, one per line
+  pub fn summary(&self) -> String {
+    let mut lines = vec![
+      format!("Draw calls: {}", self.draw_calls),
+      format!("Vertices: {}", self.vertices),
+      format!("Triangles: {}", self.triangles),
+      format!("Lines: {}", self.lines),
+      format!("Points: {}", self.points),
+      format!("Buffer uploads: {:.1} kB", self.buffer_bytes as f64 / 1024.0),
+      format!("Program switches: {}", self.program_switches),
+      format!("Culled elements: {}", self.culled),
+    ];
+    if let Some(total) = self.gpu_total() {
+      lines.push(format!("GPU: {:.2} ms", total));
+      lines.extend(self.gpu_times.iter().map(|(pass, t)| format!("  {}: {:.2} ms", pass, t)));
+    }
+    lines.join("\n")
+  }
+
+  /// Expose clone functionality to JavaScript
+  pub fn clone(&self) -> RenderStatistics { Clone::clone(self) }
+
+  /// Convert the statistics to json
+  pub fn to_json(&self) -> Result<String, JsError> {
+    Ok(serde_json::to_string(&self).map_err(|e| format!("{e}"))?)
+  }
+}
+
+/// Counts of the frame being drawn by a renderer, passed down to the functions issuing its draw calls and uploads.
+/// Clones share the counts
+#[derive(Clone, Default)]
+pub struct Counters {
+  frame:   std::rc::Rc<std::cell::RefCell<RenderStatistics>>,
+  /// Program in use, to count the program switches
+  program: std::rc::Rc<std::cell::RefCell<Option<web_sys::WebGlProgram>>>,
+}
+
+impl Counters {
+  /// Start counting a frame
+  pub fn begin_frame(&self) {
+    *self.frame.borrow_mut() = RenderStatistics::default();
+    *self.program.borrow_mut() = None;
+  }
+
+  /// Counts of the frame since `begin_frame`
+  pub fn frame(&self) -> RenderStatistics { self.frame.borrow().clone() }
+
+  /// Count a draw call of `n_vertices` vertices assembled into `mode` primitives, drawn `n_instances` times
+  pub fn record_draw(&self, mode: u32, n_vertices: usize, n_instances: usize) {
+    let mut f = self.frame.borrow_mut();
+    f.draw_calls += 1;
+    f.vertices += n_vertices * n_instances;
+    match mode {
+      web_sys::WebGl2RenderingContext::TRIANGLES => f.triangles += n_vertices / 3 * n_instances,
+      web_sys::WebGl2RenderingContext::TRIANGLE_STRIP | web_sys::WebGl2RenderingContext::TRIANGLE_FAN =>
+        f.triangles += n_vertices.saturating_sub(2) * n_instances,
+      web_sys::WebGl2RenderingContext::LINES => f.lines += n_vertices / 2 * n_instances,
+      web_sys::WebGl2RenderingContext::LINE_STRIP => f.lines += n_vertices.saturating_sub(1) * n_instances,
+      web_sys::WebGl2RenderingContext::LINE_LOOP => f.lines += n_vertices * n_instances,
+      web_sys::WebGl2RenderingContext::POINTS => f.points += n_vertices * n_instances,
+      _ => (),
+    }
+  }
+
+  /// Count `bytes` uploaded to a buffer
+  pub fn record_upload(&self, bytes: usize) { self.frame.borrow_mut().buffer_bytes += bytes; }
+
+  /// Count an element culled outside the view
+  pub fn record_culled(&self) { self.frame.borrow_mut().culled += 1; }
+
+  /// Use `program` for the following draw calls, counting a switch if it differs from the program in use
+  pub fn use_program(&self, context: &web_sys::WebGl2RenderingContext, program: &web_sys::WebGlProgram) {
+    context.use_program(Some(program));
+    let mut p = self.program.borrow_mut();
+    if p.as_ref() != Some(program) {
+      *p = Some(program.clone());
+      self.frame.borrow_mut().program_switches += 1;
+    }
+  }
+}
+
+/// Time of the passes of the frames on the GPU, from `EXT_disjoint_timer_query_webgl2` queries. The queries of a
+/// frame are sequential - a pass ends when the next begins - and their results are collected once available, the
+/// frames during which the GPU timer was disjoint being discarded
+#[derive(Default)]
+pub struct GpuTimer {
+  /// Whether the extension is available, once checked
+  available: Option<bool>,
+  active:    Option<(String, web_sys::WebGlQuery)>,
+  frame:     Vec<(String, web_sys::WebGlQuery)>,
+  pending:   std::collections::VecDeque<Vec<(String, web_sys::WebGlQuery)>>,
+  times:     Vec<(String, f64)>,
+}
+
+impl GpuTimer {
+  /// `TIME_ELAPSED_EXT` query target of the extension
+  const TIME_ELAPSED: u32 = 0x88BF;
+  /// `GPU_DISJOINT_EXT` parameter of the extension
+  const GPU_DISJOINT: u32 = 0x8FBB;
+  /// Maximum number of frames awaiting their results, older frames being discarded
+  const MAX_PENDING: usize = 8;
+
+  /// Whether the GPU time can be measured
+  fn is_available(&mut self, context: &web_sys::WebGl2RenderingContext) -> bool {
+    *self.available.get_or_insert_with(|| matches!(context.get_extension("EXT_disjoint_timer_query_webgl2"), Ok(Some(_))))
+  }
+
+  /// End the pass being timed, if any, and start timing `pass`
+  pub fn begin(&mut self, context: &web_sys::WebGl2RenderingContext, pass: &str) {
+    self.end(context);
+    if !self.is_available(context) { return; }
+    if let Some(query) = context.create_query() {
+      context.begin_query(Self::TIME_ELAPSED, &query);
+      self.active = Some((pass.to_string(), query));
+    }
+  }
+
+  /// End the pass being timed, if any
+  pub fn end(&mut self, context: &web_sys::WebGl2RenderingContext) {
+    if let Some((pass, query)) = self.active.take() {
+      context.end_query(Self::TIME_ELAPSED);
+      self.frame.push((pass, query));
+    }
+  }
+
+  /// End the frame and collect the results of the frames available
+  pub fn end_frame(&mut self, context: &web_sys::WebGl2RenderingContext) {
+    self.end(context);
+    if !self.frame.is_empty() { self.pending.push_back(std::mem::take(&mut self.frame)); }
+    while self.pending.len() > Self::MAX_PENDING {
+      for (_, query) in self.pending.pop_front().unwrap_or_default() { context.delete_query(Some(&query)); }
+    }
+    while let Some(queries) = self.pending.front() {
+      let available = queries.iter()
+        .all(|(_, query)| context.get_query_parameter(query, web_sys::WebGl2RenderingContext::QUERY_RESULT_AVAILABLE).as_bool() == Some(true));
+      if !available { break; }
+      let queries = self.pending.pop_front().unwrap_or_default();
+      let disjoint = context.get_parameter(Self::GPU_DISJOINT).ok().and_then(|v| v.as_bool()).unwrap_or(false);
+      if !disjoint {
+        self.times.clear();
+        for (pass, query) in queries.iter() {
+          let ms = context.get_query_parameter(query, web_sys::WebGl2RenderingContext::QUERY_RESULT).as_f64().unwrap_or(0.0) * 1e-6;
+          match self.times.iter_mut().find(|(p, _)| p == pass) {
+            Some((_, t)) => *t += ms,
+            None => self.times.push((pass.clone(), ms)),
+          }
+        }
+      }
+      for (_, query) in queries { context.delete_query(Some(&query)); }
+    }
+  }
+
+  /// GPU time of the passes of the latest frame measured, in milliseconds
+  pub fn times(&self) -> &[(String, f64)] { &self.times }
+
+  /// Forget the queries of a lost context
+  pub fn invalidate(&mut self) { *self = GpuTimer::default(); }
+}
+
+impl RenderStatistics {
+  /// Replace the GPU time of the passes
+  pub fn with_gpu_times(mut self, gpu_times: &[(String, f64)]) -> RenderStatistics { self.gpu_times = gpu_times.to_vec(); self }
+}
+
+#[cfg(all(test, feature = "wasm"))]
+mod tests {
+  use super::*;
+  use web_sys::WebGl2RenderingContext as GL;
+
+  #[test]
+  fn draws_are_counted_by_primitive() {
+    let counters = Counters::default();
+    counters.record_draw(GL::TRIANGLES, 6, 3);
+    counters.record_draw(GL::TRIANGLE_STRIP, 4, 1);
+    counters.record_draw(GL::LINES, 4, 1);
+    counters.record_draw(GL::LINE_STRIP, 4, 1);
+    counters.record_draw(GL::POINTS, 5, 2);
+    let frame = counters.frame();
+    assert_eq!(frame.draw_calls(), 5);
+    assert_eq!(frame.vertices(), 18 + 4 + 4 + 4 + 10);
+    assert_eq!(frame.triangles(), 6 + 2);
+    assert_eq!(frame.lines(), 2 + 3);
+    assert_eq!(frame.points(), 10);
+  }
+
+  #[test]
+  fn begin_frame_resets_the_counts() {
+    let counters = Counters::default();
+    counters.record_draw(GL::TRIANGLES, 3, 1);
+    counters.record_upload(1024);
+    counters.record_culled();
+    assert_eq!((counters.frame().buffer_bytes(), counters.frame().culled_elements()), (1024, 1));
+    counters.begin_frame();
+    assert_eq!(counters.frame(), RenderStatistics::default());
+  }
+
+  #[test]
+  fn clones_share_the_counts() {
+    let counters = Counters::default();
+    let clone = counters.clone();
+    let other = Counters::default();
+    clone.record_upload(16);
+    assert_eq!(counters.frame().buffer_bytes(), 16);
+    assert_eq!(other.frame().buffer_bytes(), 0);
+  }
+
+  #[test]
+  fn gpu_times_are_summed() {
+    let frame = RenderStatistics::default();
+    assert_eq!(frame.gpu_total(), None);
+    assert!(!frame.summary().contains("GPU"));
+    let frame = frame.with_gpu_times(&[("scene".to_string(), 1.5), ("post".to_string(), 0.25)]);
+    assert_eq!(frame.gpu_total(), Some(1.75));
+    assert_eq!(frame.gpu_time("post"), Some(0.25));
+    assert!(frame.summary().ends_with("GPU: 1.75 ms\n  scene: 1.50 ms\n  post: 0.25 ms"));
+  }
+}
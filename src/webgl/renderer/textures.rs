@@ -0,0 +1,111 @@
+use super::*;
+
+/// GPU textures of the `Texture` objects drawn, shared by the elements referencing the same texture, and colormap
+/// lookup textures. A GPU texture is deleted once all copies of its `Texture` are dropped
+#[derive(Default)]
+pub struct TextureCache {
+  textures:  std::collections::HashMap<String, (std::rc::Weak<texture::TextureData>, web_sys::WebGlTexture)>,
+  colormaps: std::collections::HashMap<Colormap, web_sys::WebGlTexture>,
+}
+
+impl TextureCache {
+  /// Number of GPU textures
+  pub fn len(&self) -> usize { self.textures.len() }
+
+  /// Bind the GPU texture of `texture` to texture unit 0, uploading it on first use.
+  /// Returns `false` when the texture cannot be drawn yet because its image is loading
+  pub fn bind(&mut self, context: &web_sys::WebGl2RenderingContext, texture: &Texture) -> Result<bool, Error> {
+    if !self.textures.contains_key(texture.uid()) {
+      let Some(gl_texture) = Self::upload(context, texture)? else { return Ok(false); };
+      self.textures.insert(texture.uid().to_string(), (texture.downgrade(), gl_texture));
+    }
+    context.active_texture(web_sys::WebGl2RenderingContext::TEXTURE0);
+    context.bind_texture(web_sys::WebGl2RenderingContext::TEXTURE_2D, self.textures.get(texture.uid()).map(|(_, t)| t));
+    Ok(true)
+  }
+
+  /// Bind the lookup texture of `colormap` to texture unit 0, creating it on first use
+  pub fn bind_colormap(&mut self, context: &web_sys::WebGl2RenderingContext, colormap: Colormap) -> Result<(), Error> {
+    if let std::collections::hash_map::Entry::Vacant(entry) = self.colormaps.entry(colormap) {
+      let lut = context.create_texture().ok_or("Unable to create texture")?;
+      context.bind_texture(web_sys::WebGl2RenderingContext::TEXTURE_2D, Some(&lut));
+      context.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+        web_sys::WebGl2RenderingContext::TEXTURE_2D,         // target
+        0,                                                   // level
+        web_sys::WebGl2RenderingContext::RGBA8.try_into()?,  // internal format
+        Colormap::LUT_SIZE.try_into()?,                      // width
+        1,                                                   // height
+        0,                                                   // border
+        web_sys::WebGl2RenderingContext::RGBA,               // format
+        web_sys::WebGl2RenderingContext::UNSIGNED_BYTE,      // type
+        Some(&colormap.lut()),                               // data
+      )?;
+      context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_MIN_FILTER, web_sys::WebGl2RenderingContext::LINEAR.try_into()?);
+      context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_MAG_FILTER, web_sys::WebGl2RenderingContext::LINEAR.try_into()?);
+      context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_WRAP_S, web_sys::WebGl2RenderingContext::CLAMP_TO_EDGE.try_into()?);
+      context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_WRAP_T, web_sys::WebGl2RenderingContext::CLAMP_TO_EDGE.try_into()?);
+      entry.insert(lut);
+    }
+    context.active_texture(web_sys::WebGl2RenderingContext::TEXTURE0);
+    context.bind_texture(web_sys::WebGl2RenderingContext::TEXTURE_2D, self.colormaps.get(&colormap));
+    Ok(())
+  }
+
+  /// Delete the GPU textures no longer referenced
+  pub fn release_unused(&mut self, context: &web_sys::WebGl2RenderingContext) {
+    self.textures.retain(|_, (data, gl_texture)| {
+      let used = data.strong_count() > 0;
+      if !used { context.delete_texture(Some(gl_texture)); }
+      used
+    });
+  }
+
+  /// Forget the GPU textures of a lost context, without deleting them. They are uploaded again on next use
+  pub fn invalidate(&mut self) {
+    self.textures.clear();
+    self.colormaps.clear();
+  }
+
+  /// Create the GPU texture, with the first row of pixels at `v = 1`. `None` while the image is loading
+  fn upload(context: &web_sys::WebGl2RenderingContext, texture: &Texture) -> Result<Option<web_sys::WebGlTexture>, Error> {
+    if let texture::TextureSource::Image(image) = texture.source() {
+      if !image.complete() || image.natural_width() == 0 { return Ok(None); }
+    }
+    let gl_texture = context.create_texture().ok_or("Unable to create texture")?;
+    context.bind_texture(web_sys::WebGl2RenderingContext::TEXTURE_2D, Some(&gl_texture));
+    context.pixel_storei(web_sys::WebGl2RenderingContext::UNPACK_FLIP_Y_WEBGL, 1);
+    let result = match texture.source() {
+      texture::TextureSource::Rgba { width, height, data } =>
+        context.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+          web_sys::WebGl2RenderingContext::TEXTURE_2D,         // target
+          0,                                                   // level
+          web_sys::WebGl2RenderingContext::RGBA8.try_into()?,  // internal format
+          (*width).try_into()?,                                // width
+          (*height).try_into()?,                               // height
+          0,                                                   // border
+          web_sys::WebGl2RenderingContext::RGBA,               // format
+          web_sys::WebGl2RenderingContext::UNSIGNED_BYTE,      // type
+          Some(data),                                          // data
+        ),
+      texture::TextureSource::Image(image) =>
+        context.tex_image_2d_with_u32_and_u32_and_html_image_element(
+          web_sys::WebGl2RenderingContext::TEXTURE_2D,         // target
+          0,                                                   // level
+          web_sys::WebGl2RenderingContext::RGBA8.try_into()?,  // internal format
+          web_sys::WebGl2RenderingContext::RGBA,               // format
+          web_sys::WebGl2RenderingContext::UNSIGNED_BYTE,      // type
+          image,
+        ),
+    };
+    context.pixel_storei(web_sys::WebGl2RenderingContext::UNPACK_FLIP_Y_WEBGL, 0);
+    result?;
+
+    let (wrap_u, wrap_v) = texture.wrap();
+    context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_MIN_FILTER, texture.filter().gl_filter(texture.mipmap()).try_into()?);
+    context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_MAG_FILTER, texture.filter().gl_filter(false).try_into()?);
+    context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_WRAP_S, wrap_u.gl_wrap().try_into()?);
+    context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_WRAP_T, wrap_v.gl_wrap().try_into()?);
+    if texture.mipmap() { context.generate_mipmap(web_sys::WebGl2RenderingContext::TEXTURE_2D); }
+    Ok(Some(gl_texture))
+  }
+}
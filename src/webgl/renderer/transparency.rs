@@ -0,0 +1,152 @@
+use super::*;
+
+/// Method used to draw elements whose material is not opaque
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub enum Transparency {
+  /// Transparent elements are drawn after opaque elements, sorted back-to-front by camera distance
+  #[default]
+  Sorted,
+  /// Weighted blended order-independent transparency (McGuire and Bavoil, 2013). Falls back to `Sorted`
+  /// when float render targets (`EXT_color_buffer_float`) are not available.
+  WeightedBlended,
+}
+
+/// Pass of the fragment shader output, matching `u_oitPass` in the triangle program
+#[derive(Clone, Copy, PartialEq)]
+pub enum OitPass {
+  /// Regular output
+  Color = 0,
+  /// Weighted premultiplied colour accumulation
+  Accumulation = 1,
+  /// Revealage (product of `1 - alpha`)
+  Revealage = 2,
+}
+
+/// Offscreen targets used by the weighted blended order-independent transparency
+pub struct WeightedBlendedTargets {
+  width:       i32,
+  height:      i32,
+  framebuffer: web_sys::WebGlFramebuffer,
+  color:       web_sys::WebGlTexture,
+  accum:       web_sys::WebGlTexture,
+  revealage:   web_sys::WebGlTexture,
+  _depth:      web_sys::WebGlRenderbuffer,
+}
+
+impl WeightedBlendedTargets {
+  /// Create the targets, or return `None` if float render targets are not supported
+  pub fn new(context: &web_sys::WebGl2RenderingContext, width: i32, height: i32) -> Result<Option<WeightedBlendedTargets>, Error> {
+    if context.get_extension("EXT_color_buffer_float")?.is_none() { return Ok(None); }
+
+    let color = Self::texture(context, width, height, web_sys::WebGl2RenderingContext::RGBA8, web_sys::WebGl2RenderingContext::RGBA, web_sys::WebGl2RenderingContext::UNSIGNED_BYTE)?;
+    // Linear filtering for the FXAA pass
+    context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_MIN_FILTER, web_sys::WebGl2RenderingContext::LINEAR.try_into()?);
+    context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_MAG_FILTER, web_sys::WebGl2RenderingContext::LINEAR.try_into()?);
+    let accum = Self::texture(context, width, height, web_sys::WebGl2RenderingContext::RGBA16F, web_sys::WebGl2RenderingContext::RGBA, web_sys::WebGl2RenderingContext::HALF_FLOAT)?;
+    let revealage = Self::texture(context, width, height, web_sys::WebGl2RenderingContext::R8, web_sys::WebGl2RenderingContext::RED, web_sys::WebGl2RenderingContext::UNSIGNED_BYTE)?;
+
+    let depth = context.create_renderbuffer().ok_or("Unable to create render buffer")?;
+    context.bind_renderbuffer(web_sys::WebGl2RenderingContext::RENDERBUFFER, Some(&depth));
+    context.renderbuffer_storage(web_sys::WebGl2RenderingContext::RENDERBUFFER, DEPTH_FORMAT, width, height);
+
+    let framebuffer = context.create_framebuffer().ok_or("Unable to create frame buffer")?;
+    context.bind_framebuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER, Some(&framebuffer));
+    context.framebuffer_renderbuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER,
+      web_sys::WebGl2RenderingContext::DEPTH_STENCIL_ATTACHMENT,
+      web_sys::WebGl2RenderingContext::RENDERBUFFER,
+      Some(&depth)
+    );
+    let targets = WeightedBlendedTargets { width, height, framebuffer, color, accum, revealage, _depth: depth };
+    targets.attach(context, &targets.color);
+    let status = context.check_framebuffer_status(web_sys::WebGl2RenderingContext::FRAMEBUFFER);
+    context.bind_framebuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER, None);
+    if status != web_sys::WebGl2RenderingContext::FRAMEBUFFER_COMPLETE { return Ok(None); }
+    Ok(Some(targets))
+  }
+
+  /// Whether the targets match the size provided
+  pub fn has_size(&self, width: i32, height: i32) -> bool { self.width == width && self.height == height }
+
+  /// Bind the framebuffer with the opaque colour target attached
+  pub fn bind(&self, context: &web_sys::WebGl2RenderingContext) {
+    context.bind_framebuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER, Some(&self.framebuffer));
+    self.attach(context, &self.color);
+  }
+
+  /// Framebuffer drawn into
+  pub fn framebuffer(&self) -> &web_sys::WebGlFramebuffer { &self.framebuffer }
+
+  /// Draw the transparent elements using `draw` for each of the accumulation and revealage passes,
+  /// composite them over the opaque colour target and copy the result to the `output` framebuffer - the default 
+  /// framebuffer if `None` - using `mode` (`CompositeMode::Copy` or `CompositeMode::Fxaa`).
+  /// `draw_sorted` is called before the copy to draw elements that do not support order-independent transparency.
+  #[allow(clippy::too_many_arguments)]
+  pub fn resolve<F, G>(&self,
+    context: &web_sys::WebGl2RenderingContext,
+    counters: &statistics::Counters,
+    program: &std::rc::Rc<std::cell::RefCell<Option<web_sys::WebGlProgram>>>,
+    draw: F,
+    draw_sorted: G,
+    mode: programcomposite::CompositeMode,
+    output: Option<&web_sys::WebGlFramebuffer>,
+    ) -> Result<(), Error>
+  where F: Fn(OitPass) -> Result<(), Error>,
+        G: FnOnce() -> Result<(), Error> {
+    context.bind_framebuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER, Some(&self.framebuffer));
+    context.enable(web_sys::WebGl2RenderingContext::BLEND);
+    context.depth_mask(false);
+
+    // Accumulation
+    self.attach(context, &self.accum);
+    context.clear_color(0.0, 0.0, 0.0, 0.0);
+    context.clear(web_sys::WebGl2RenderingContext::COLOR_BUFFER_BIT);
+    context.blend_func(web_sys::WebGl2RenderingContext::ONE, web_sys::WebGl2RenderingContext::ONE);
+    draw(OitPass::Accumulation)?;
+
+    // Revealage
+    self.attach(context, &self.revealage);
+    context.clear_color(1.0, 1.0, 1.0, 1.0);
+    context.clear(web_sys::WebGl2RenderingContext::COLOR_BUFFER_BIT);
+    context.blend_func(web_sys::WebGl2RenderingContext::ZERO, web_sys::WebGl2RenderingContext::ONE_MINUS_SRC_COLOR);
+    draw(OitPass::Revealage)?;
+
+    // Composite over the opaque colour
+    self.attach(context, &self.color);
+    context.blend_func(web_sys::WebGl2RenderingContext::SRC_ALPHA, web_sys::WebGl2RenderingContext::ONE_MINUS_SRC_ALPHA);
+    programcomposite::ProgramComposite::draw(context, counters, program, programcomposite::CompositeMode::WeightedBlended, &self.accum, Some(&self.revealage), (self.width, self.height))?;
+    context.depth_mask(true);
+    context.disable(web_sys::WebGl2RenderingContext::BLEND);
+
+    draw_sorted()?;
+
+    // Copy to the output framebuffer
+    context.bind_framebuffer(web_sys::WebGl2RenderingContext::FRAMEBUFFER, output);
+    programcomposite::ProgramComposite::draw(context, counters, program, mode, &self.color, None, (self.width, self.height))?;
+    context.enable(web_sys::WebGl2RenderingContext::DEPTH_TEST);
+    Ok(())
+  }
+
+  fn attach(&self, context: &web_sys::WebGl2RenderingContext, texture: &web_sys::WebGlTexture) {
+    context.framebuffer_texture_2d(web_sys::WebGl2RenderingContext::FRAMEBUFFER,
+      web_sys::WebGl2RenderingContext::COLOR_ATTACHMENT0,
+      web_sys::WebGl2RenderingContext::TEXTURE_2D,
+      Some(texture),
+      0,       // level
+    );
+  }
+
+  fn texture(context: &web_sys::WebGl2RenderingContext, width: i32, height: i32, internal_format: u32, format: u32, type_: u32) -> Result<web_sys::WebGlTexture, Error> {
+    let texture = context.create_texture().ok_or("Unable to create texture")?;
+    context.bind_texture(web_sys::WebGl2RenderingContext::TEXTURE_2D, Some(&texture));
+    context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_MIN_FILTER, web_sys::WebGl2RenderingContext::NEAREST.try_into()?);
+    context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_MAG_FILTER, web_sys::WebGl2RenderingContext::NEAREST.try_into()?);
+    context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_WRAP_S, web_sys::WebGl2RenderingContext::CLAMP_TO_EDGE.try_into()?);
+    context.tex_parameteri(web_sys::WebGl2RenderingContext::TEXTURE_2D, web_sys::WebGl2RenderingContext::TEXTURE_WRAP_T, web_sys::WebGl2RenderingContext::CLAMP_TO_EDGE.try_into()?);
+    context.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+      web_sys::WebGl2RenderingContext::TEXTURE_2D, 0, internal_format.try_into()?, width, height, 0, format, type_, None,
+    )?;
+    Ok(texture)
+  }
+}
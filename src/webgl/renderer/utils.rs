@@ -7,9 +7,23 @@ use super::*;
 /// into the array...
 pub fn bind(
     context: &web_sys::WebGl2RenderingContext,
+    counters: &statistics::Counters,
     program: &web_sys::WebGlProgram,
     key: &str,
-    array: &Vec<f32>
+    array: &[f32]
+  ) -> Result<(), Error> {
+    bind_with_size(context, counters, program, key, array, 3)
+}
+
+/// Bind the values of an array to the context using the key provided, with `size` components per vertex.
+/// Assumes that the program has already been set.
+pub fn bind_with_size(
+    context: &web_sys::WebGl2RenderingContext,
+    counters: &statistics::Counters,
+    program: &web_sys::WebGlProgram,
+    key: &str,
+    array: &[f32],
+    size: i32,
   ) -> Result<(), Error> {
     let buffer = context.create_buffer().ok_or("Unable to create buffer")?;
     context.bind_buffer(web_sys::WebGl2RenderingContext::ARRAY_BUFFER, Some(&buffer));
@@ -23,19 +37,62 @@ pub fn bind(
     }
     */
     let view = js_sys::Float32Array::new_with_length(array.len().try_into()?);
-    view.copy_from(array.as_slice());
+    view.copy_from(array);
     context.buffer_data_with_array_buffer_view(
         web_sys::WebGl2RenderingContext::ARRAY_BUFFER,
         &view,
         web_sys::WebGl2RenderingContext::STATIC_DRAW);
+    counters.record_upload(4*array.len());
 
     let position = context.get_attrib_location(program, key);
-    context.vertex_attrib_pointer_with_i32(position.try_into()?, 3, web_sys::WebGl2RenderingContext::FLOAT, false, 0, 0);
+    context.vertex_attrib_pointer_with_i32(position.try_into()?, size, web_sys::WebGl2RenderingContext::FLOAT, false, 0, 0);
     context.enable_vertex_attrib_array(position as u32);
     context.bind_buffer(web_sys::WebGl2RenderingContext::ARRAY_BUFFER, Some(&buffer));
     Ok(())
 }
 
+/// Bind an uploaded `buffer` to the attribute `key`, with `size` components per vertex, or disable the attribute
+/// array if `None` so that the attribute takes its constant value. Assumes that the program has already been set.
+pub fn bind_buffer(
+    context: &web_sys::WebGl2RenderingContext,
+    program: &web_sys::WebGlProgram,
+    key: &str,
+    buffer: Option<&web_sys::WebGlBuffer>,
+    size: i32,
+  ) {
+    let location = context.get_attrib_location(program, key);
+    if location < 0 { return; }
+    match buffer {
+      Some(buffer) => {
+        context.bind_buffer(web_sys::WebGl2RenderingContext::ARRAY_BUFFER, Some(buffer));
+        context.vertex_attrib_pointer_with_i32(location as u32, size, web_sys::WebGl2RenderingContext::FLOAT, false, 0, 0);
+        context.enable_vertex_attrib_array(location as u32);
+      },
+      None => context.disable_vertex_attrib_array(location as u32),
+    }
+}
+
+/// Bind the unsigned integer `buffer` to the attribute `key`, or disable the attribute if `buffer` is `None`.
+/// Assumes that the program has already been set
+pub fn bind_uint_buffer(
+    context: &web_sys::WebGl2RenderingContext,
+    program: &web_sys::WebGlProgram,
+    key: &str,
+    buffer: Option<&web_sys::WebGlBuffer>,
+    size: i32,
+  ) {
+    let location = context.get_attrib_location(program, key);
+    if location < 0 { return; }
+    match buffer {
+      Some(buffer) => {
+        context.bind_buffer(web_sys::WebGl2RenderingContext::ARRAY_BUFFER, Some(buffer));
+        context.vertex_attrib_i_pointer_with_i32(location as u32, size, web_sys::WebGl2RenderingContext::UNSIGNED_INT, 0, 0);
+        context.enable_vertex_attrib_array(location as u32);
+      },
+      None => context.disable_vertex_attrib_array(location as u32),
+    }
+}
+
 // Convert an array of `nalgebra::Point3<f32>` into a `Vec<f32>`
 pub fn point3_to_vecf32(array: &Vec<&nalgebra::Point3<f32>>) -> Vec<f32> {
     array.iter()
@@ -49,3 +106,178 @@ pub fn vector3_to_vecf32(array: &Vec<&nalgebra::Vector3<f32>>) -> Vec<f32> {
     .map(|p| vec!(p.x, p.y, p.z))
     .fold(Vec::new(), |mut r, mut a| {r.append(&mut a); r})
 }
+
+/// Create a static array buffer holding `array`
+pub fn upload(context: &web_sys::WebGl2RenderingContext, counters: &statistics::Counters, array: &[f32]) -> Result<web_sys::WebGlBuffer, Error> {
+    let buffer = context.create_buffer().ok_or("Unable to create buffer")?;
+    context.bind_buffer(web_sys::WebGl2RenderingContext::ARRAY_BUFFER, Some(&buffer));
+    let view = js_sys::Float32Array::new_with_length(array.len().try_into()?);
+    view.copy_from(array);
+    context.buffer_data_with_array_buffer_view(
+        web_sys::WebGl2RenderingContext::ARRAY_BUFFER,
+        &view,
+        web_sys::WebGl2RenderingContext::STATIC_DRAW);
+    counters.record_upload(4*array.len());
+    Ok(buffer)
+}
+
+/// Create a static vertex buffer holding the unsigned integers `array`
+pub fn upload_uint(context: &web_sys::WebGl2RenderingContext, counters: &statistics::Counters, array: &[u32]) -> Result<web_sys::WebGlBuffer, Error> {
+    let buffer = context.create_buffer().ok_or("Unable to create buffer")?;
+    context.bind_buffer(web_sys::WebGl2RenderingContext::ARRAY_BUFFER, Some(&buffer));
+    let view = js_sys::Uint32Array::new_with_length(array.len().try_into()?);
+    view.copy_from(array);
+    context.buffer_data_with_array_buffer_view(
+        web_sys::WebGl2RenderingContext::ARRAY_BUFFER,
+        &view,
+        web_sys::WebGl2RenderingContext::STATIC_DRAW);
+    counters.record_upload(4*array.len());
+    Ok(buffer)
+}
+
+/// Upload an index buffer and bind it to the context
+pub fn bind_indices(
+    context: &web_sys::WebGl2RenderingContext,
+    counters: &statistics::Counters,
+    indices: &Indices,
+  ) -> Result<(), Error> {
+    upload_indices(context, counters, indices)?;
+    Ok(())
+}
+
+/// Create a static index buffer holding `indices`, left bound to the context
+pub fn upload_indices(
+    context: &web_sys::WebGl2RenderingContext,
+    counters: &statistics::Counters,
+    indices: &Indices,
+  ) -> Result<web_sys::WebGlBuffer, Error> {
+    let buffer = context.create_buffer().ok_or("Unable to create buffer")?;
+    context.bind_buffer(web_sys::WebGl2RenderingContext::ELEMENT_ARRAY_BUFFER, Some(&buffer));
+    match indices {
+      Indices::U16(indices) => {
+        let view = js_sys::Uint16Array::new_with_length(indices.len().try_into()?);
+        view.copy_from(indices.as_slice());
+        context.buffer_data_with_array_buffer_view(web_sys::WebGl2RenderingContext::ELEMENT_ARRAY_BUFFER, &view, web_sys::WebGl2RenderingContext::STATIC_DRAW);
+        counters.record_upload(2*indices.len());
+      },
+      Indices::U32(indices) => {
+        let view = js_sys::Uint32Array::new_with_length(indices.len().try_into()?);
+        view.copy_from(indices.as_slice());
+        context.buffer_data_with_array_buffer_view(web_sys::WebGl2RenderingContext::ELEMENT_ARRAY_BUFFER, &view, web_sys::WebGl2RenderingContext::STATIC_DRAW);
+        counters.record_upload(4*indices.len());
+      },
+    }
+    Ok(buffer)
+}
+
+/// Issue the draw call for `mode` primitives: `drawElements` if indices are provided, `drawArrays` of `n_vertices` otherwise
+pub fn draw(
+    context: &web_sys::WebGl2RenderingContext,
+    counters: &statistics::Counters,
+    mode: u32,
+    n_vertices: usize,
+    indices: Option<&Indices>,
+  ) -> Result<(), Error> {
+    match indices {
+      Some(indices) => {
+        bind_indices(context, counters, indices)?;
+        context.draw_elements_with_i32(mode, indices.len().try_into()?, indices.gl_type(), 0);
+        counters.record_draw(mode, indices.len(), 1);
+      },
+      None => {
+        context.draw_arrays(mode, 0, n_vertices.try_into()?);
+        counters.record_draw(mode, n_vertices, 1);
+      },
+    }
+    Ok(())
+}
+
+/// Bind per-instance values to the attribute `key`, advancing once per instance. Each instance holds `columns`
+/// consecutive columns of `rows` components, assigned to consecutive attribute locations (eg 4 columns of 4 rows for a `mat4`).
+/// Returns the attribute locations, to be released with `unbind_per_instance` once drawn.
+pub fn bind_per_instance(
+    context: &web_sys::WebGl2RenderingContext,
+    counters: &statistics::Counters,
+    program: &web_sys::WebGlProgram,
+    key: &str,
+    array: &[f32],
+    columns: u32,
+    rows: i32,
+  ) -> Result<Vec<u32>, Error> {
+    let location = context.get_attrib_location(program, key);
+    if location < 0 { return Ok(Vec::new()); }
+    let location = location as u32;
+
+    let buffer = context.create_buffer().ok_or("Unable to create buffer")?;
+    context.bind_buffer(web_sys::WebGl2RenderingContext::ARRAY_BUFFER, Some(&buffer));
+    let view = js_sys::Float32Array::new_with_length(array.len().try_into()?);
+    view.copy_from(array);
+    context.buffer_data_with_array_buffer_view(
+        web_sys::WebGl2RenderingContext::ARRAY_BUFFER,
+        &view,
+        web_sys::WebGl2RenderingContext::STATIC_DRAW);
+    counters.record_upload(4*array.len());
+
+    let stride = 4*rows*columns as i32;
+    (0..columns).map(|column| {
+      let l = location + column;
+      context.vertex_attrib_pointer_with_i32(l, rows, web_sys::WebGl2RenderingContext::FLOAT, false, stride, 4*rows*column as i32);
+      context.enable_vertex_attrib_array(l);
+      context.vertex_attrib_divisor(l, 1);
+      Ok(l)
+    }).collect()
+}
+
+/// Restore attribute locations bound by `bind_per_instance` to per-vertex arrays
+pub fn unbind_per_instance(
+    context: &web_sys::WebGl2RenderingContext,
+    locations: &[u32],
+  ) {
+    for l in locations {
+      context.vertex_attrib_divisor(*l, 0);
+      context.disable_vertex_attrib_array(*l);
+    }
+}
+
+/// Issue the instanced draw call for `mode` primitives: `drawElementsInstanced` if indices are provided, `drawArraysInstanced` of `n_vertices` otherwise
+pub fn draw_instanced(
+    context: &web_sys::WebGl2RenderingContext,
+    counters: &statistics::Counters,
+    mode: u32,
+    n_vertices: usize,
+    indices: Option<&Indices>,
+    n_instances: usize,
+  ) -> Result<(), Error> {
+    match indices {
+      Some(indices) => {
+        bind_indices(context, counters, indices)?;
+        context.draw_elements_instanced_with_i32(mode, indices.len().try_into()?, indices.gl_type(), 0, n_instances.try_into()?);
+        counters.record_draw(mode, indices.len(), n_instances);
+      },
+      None => {
+        context.draw_arrays_instanced(mode, 0, n_vertices.try_into()?, n_instances.try_into()?);
+        counters.record_draw(mode, n_vertices, n_instances);
+      },
+    }
+    Ok(())
+}
+
+/// Flatten instance model matrices (column-major)
+pub fn matrices_to_vecf32(matrices: &[nalgebra::Matrix4<f32>]) -> Vec<f32> {
+    matrices.iter().flat_map(|m| m.as_slice().to_vec()).collect()
+}
+
+/// Flatten the normal matrices - inverse transpose of the upper 3x3 - of instance model matrices (column-major)
+pub fn normal_matrices_to_vecf32(matrices: &[nalgebra::Matrix4<f32>]) -> Vec<f32> {
+    matrices.iter()
+    .flat_map(|m| {
+      let m = m.fixed_view::<3, 3>(0, 0).into_owned();
+      m.try_inverse().unwrap_or(m).transpose().as_slice().to_vec()
+    })
+    .collect()
+}
+
+/// Flatten instance colours
+pub fn colors_to_vecf32(colors: &[Color]) -> Vec<f32> {
+    colors.iter().flat_map(|c| c.as_array()).collect()
+}
@@ -0,0 +1,192 @@
+use super::*;
+
+/// Colour gradient that scalar values are mapped through
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub enum Colormap {
+  /// Perceptually uniform, dark blue to yellow
+  #[default]
+  Viridis = 0,
+  /// Perceptually uniform, dark blue to yellow through magenta
+  Plasma = 1,
+  /// Diverging, blue to red through light grey
+  Coolwarm = 2,
+  /// Dark blue to dark red through cyan and yellow
+  Jet = 3,
+  /// Black to white
+  Greyscale = 4,
+}
+
+impl Colormap {
+  /// Number of colours of the lookup texture
+  pub const LUT_SIZE: usize = 256;
+
+  /// Colours `(t, [r, g, b])` linearly interpolated between
+  fn stops(&self) -> &'static [(f32, [f32; 3])] {
+    match self {
+      Colormap::Viridis => &[
+        (0.0,   [0.267, 0.005, 0.329]), (0.125, [0.279, 0.175, 0.483]), (0.25, [0.230, 0.322, 0.546]),
+        (0.375, [0.173, 0.449, 0.558]), (0.5,   [0.128, 0.567, 0.551]), (0.625, [0.158, 0.684, 0.502]),
+        (0.75,  [0.369, 0.789, 0.383]), (0.875, [0.678, 0.864, 0.190]), (1.0,  [0.993, 0.906, 0.144]),
+      ],
+      Colormap::Plasma => &[
+        (0.0,   [0.050, 0.030, 0.528]), (0.125, [0.274, 0.012, 0.623]), (0.25, [0.448, 0.002, 0.660]),
+        (0.375, [0.611, 0.090, 0.620]), (0.5,   [0.798, 0.280, 0.470]), (0.625, [0.902, 0.425, 0.357]),
+        (0.75,  [0.973, 0.586, 0.252]), (0.875, [0.994, 0.753, 0.146]), (1.0,  [0.940, 0.975, 0.131]),
+      ],
+      Colormap::Coolwarm => &[
+        (0.0, [0.230, 0.299, 0.754]), (0.25, [0.552, 0.690, 0.996]), (0.5, [0.865, 0.865, 0.865]),
+        (0.75, [0.956, 0.604, 0.486]), (1.0, [0.706, 0.016, 0.150]),
+      ],
+      Colormap::Jet => &[
+        (0.0, [0.0, 0.0, 0.5]), (0.125, [0.0, 0.0, 1.0]), (0.375, [0.0, 1.0, 1.0]),
+        (0.625, [1.0, 1.0, 0.0]), (0.875, [1.0, 0.0, 0.0]), (1.0, [0.5, 0.0, 0.0]),
+      ],
+      Colormap::Greyscale => &[(0.0, [0.0, 0.0, 0.0]), (1.0, [1.0, 1.0, 1.0])],
+    }
+  }
+
+  /// Colour at `t` in `[0, 1]`
+  pub fn color(&self, t: f32) -> Color {
+    let stops = self.stops();
+    let t = t.clamp(0.0, 1.0);
+    let i = stops.iter().position(|(s, _)| *s >= t).unwrap_or(stops.len() - 1).max(1);
+    let ((t0, c0), (t1, c1)) = (stops[i - 1], stops[i]);
+    let f = if t1 > t0 { (t - t0) / (t1 - t0) } else { 0.0 };
+    Color::rgb(c0[0] + f*(c1[0] - c0[0]), c0[1] + f*(c1[1] - c0[1]), c0[2] + f*(c1[2] - c0[2]))
+  }
+
+  /// RGBA bytes of the lookup texture, of `LUT_SIZE` colours
+  pub fn lut(&self) -> Vec<u8> {
+    (0..Self::LUT_SIZE)
+      .flat_map(|i| {
+        let c = self.color(i as f32 / (Self::LUT_SIZE - 1) as f32);
+        [c.r(), c.g(), c.b(), 1.0].map(|v| (v * 255.0).round() as u8)
+      })
+      .collect()
+  }
+}
+
+/// Mapping of scalar values to colours: colormap, range of values mapped onto it and optional banding
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ScalarStyle {
+  colormap: Colormap,
+  range:    Option<(f32, f32)>,
+  bands:    u32,
+}
+
+impl ScalarStyle {
+  /// Maximum number of bands
+  pub const MAX_BANDS: u32 = 64;
+
+  /// Colormap
+  pub fn colormap(&self) -> Colormap { self.colormap }
+
+  /// Range `(min, max)` mapped onto the colormap. `None` for the range of the values drawn
+  pub fn range(&self) -> Option<(f32, f32)> { self.range }
+
+  /// Number of bands of uniform colour. 0 for a continuous gradient
+  pub fn bands(&self) -> u32 { self.bands }
+
+  /// Range mapped onto the colormap for `values`: the range set, or the range of the finite values
+  pub fn resolved_range(&self, values: &[f32]) -> (f32, f32) {
+    if let Some(range) = self.range { return range; }
+    let (min, max) = values.iter().filter(|v| v.is_finite())
+      .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), v| (min.min(*v), max.max(*v)));
+    if min > max { (0.0, 1.0) } else { (min, max) }
+  }
+
+  /// Range mapped onto the full colormap for the values of `range`. Constant values are mapped onto the middle
+  /// of the colormap
+  pub fn colormap_range((min, max): (f32, f32)) -> (f32, f32) {
+    if max > min { (min, max) } else { (min - 1.0, min + 1.0) }
+  }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+impl ScalarStyle {
+  /// Create the default style: viridis colormap over the range of the values, continuous
+  pub fn new() -> ScalarStyle { ScalarStyle::default() }
+
+  /// Set the colormap
+  pub fn with_colormap(mut self, colormap: Colormap) -> ScalarStyle { self.colormap = colormap; self }
+
+  /// Set the range of values mapped onto the colormap. Values outside are clamped
+  pub fn with_range(mut self, min: f32, max: f32) -> Result<ScalarStyle, JsError> {
+    if min.is_nan() || max.is_nan() || min >= max { return Err("Scalar range minimum must be less than maximum".into()); }
+    self.range = Some((min, max)); Ok(self)
+  }
+
+  /// Map the range of the values drawn onto the colormap
+  pub fn with_auto_range(mut self) -> ScalarStyle { self.range = None; self }
+
+  /// Set the number of bands of uniform colour
+  pub fn with_bands(mut self, bands: u32) -> Result<ScalarStyle, JsError> {
+    if bands == 0 || bands > Self::MAX_BANDS { return Err(format!("Number of bands must be between 1 and {}", Self::MAX_BANDS).into()); }
+    self.bands = bands; Ok(self)
+  }
+
+  /// Draw a continuous gradient
+  pub fn without_bands(mut self) -> ScalarStyle { self.bands = 0; self }
+
+  /// Retrieve the colour of the colormap at `t` in `[0, 1]`
+  pub fn color_at(&self, t: f32) -> Color { self.colormap.color(t) }
+
+  /// Expose clone functionality to JavaScript
+  pub fn clone(&self) -> ScalarStyle { Clone::clone(self) }
+
+  /// Convert scalar style to json
+  pub fn to_json(&self) -> Result<String, JsError> {
+    Ok(serde_json::to_string(&self).map_err(|e| format!("{e}"))?)
+  }
+
+  /// Create scalar style from json
+  pub fn from_json(json: &str) -> Result<ScalarStyle, JsError> {
+    Ok(serde_json::from_str(json).map_err(|e| format!("{e}"))?)
+  }
+}
+
+#[cfg(all(test, feature = "wasm"))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn colormap_range_widens_constant_values() {
+    assert_eq!(ScalarStyle::colormap_range((1.0, 3.0)), (1.0, 3.0));
+    assert_eq!(ScalarStyle::colormap_range((2.0, 2.0)), (1.0, 3.0));
+  }
+
+  #[test]
+  fn resolved_range_ignores_non_finite_values() {
+    let style = ScalarStyle::new();
+    assert_eq!(style.resolved_range(&[2.0, f32::NAN, -1.0, f32::INFINITY]), (-1.0, 2.0));
+    assert_eq!(style.resolved_range(&[]), (0.0, 1.0));
+    assert_eq!(style.resolved_range(&[f32::NAN]), (0.0, 1.0));
+  }
+
+  #[test]
+  fn resolved_range_is_the_range_set() {
+    let style = ScalarStyle::new().with_range(-5.0, 5.0).unwrap();
+    assert_eq!(style.resolved_range(&[0.0, 1.0]), (-5.0, 5.0));
+  }
+
+  #[test]
+  fn colormap_color_is_clamped_to_the_end_stops() {
+    let colormap = Colormap::Greyscale;
+    assert_eq!(colormap.color(-1.0), Color::rgb(0.0, 0.0, 0.0));
+    assert_eq!(colormap.color(0.5), Color::rgb(0.5, 0.5, 0.5));
+    assert_eq!(colormap.color(2.0), Color::rgb(1.0, 1.0, 1.0));
+  }
+
+  #[test]
+  fn lut_has_one_rgba_colour_per_entry() {
+    let lut = Colormap::Greyscale.lut();
+    assert_eq!(lut.len(), 4 * Colormap::LUT_SIZE);
+    assert_eq!(&lut[..4], &[0, 0, 0, 255]);
+    assert_eq!(&lut[lut.len() - 4..], &[255, 255, 255, 255]);
+  }
+}
@@ -0,0 +1,284 @@
+use super::*;
+
+/// Chunks of GLSL ES 3.00 shared by the programs, included in a shader source by name with `#include <name>`:
+/// - `clipping_vertex`: declares `v_clipPosition`, to be assigned the world position in vertex shaders of clipped programs,
+/// - `clipping`: the `clip()` function of fragment shaders of clipped programs, to be called first in `main`, testing
+///   up to `MAX_CLIP_PLANES` - `Clipping::MAX_EQUATIONS` - planes,
+/// - `transforms`: the `worldPosition` and `worldNormal` functions of vertex shaders, applying the per-instance
+///   `a_instanceMatrix` and `a_instanceNormalMatrix` when `INSTANCED` is defined,
+/// - `shadows`: the `shadowVisibility` function of fragment shaders receiving shadows, from `vShadowCoord`,
+/// - `lighting`: the `shade` function of fragment shaders, Blinn-Phong shading by the lights of the `Lighting`,
+/// - `colormap`: the `colormap` function of fragment shaders, looking up the colormap texture of `LUT_SIZE` texels,
+/// - `picking`: the `idColor` function, encoding a picking id as a colour,
+/// - `fxaa`: the `fxaa` function of fragment shaders, anti-aliasing `u_texture` of texel size `u_texel`.
+///
+/// A chunk is included once per shader, further includes being ignored
+const CHUNKS: [(&str, &str); 8] = [
+  ("clipping_vertex", CLIPPING_VERTEX),
+  ("clipping", CLIPPING),
+  ("transforms", TRANSFORMS),
+  ("shadows", SHADOWS),
+  ("lighting", LIGHTING),
+  ("colormap", COLORMAP),
+  ("picking", PICKING),
+  ("fxaa", FXAA),
+];
+
+/// Source of the shader compiled: version directive, `defines` - `#define name value` - and `source` with its
+/// chunks included
+pub fn preprocess(source: &str, defines: &[(&str, String)]) -> Result<String, Error> {
+  let mut output = String::from("#version 300 es\n");
+  for (name, value) in defines.iter() {
+    output.push_str(&format!("#define {} {}\n", name, value));
+  }
+  resolve(source, &mut Vec::new(), &mut output)?;
+  Ok(output)
+}
+
+/// Append `source` to `output`, replacing the include directives by the chunks not `included` yet
+fn resolve(source: &str, included: &mut Vec<&'static str>, output: &mut String) -> Result<(), Error> {
+  for line in source.lines() {
+    match line.trim().strip_prefix("#include") {
+      Some(name) => {
+        let name = name.trim().trim_start_matches('<').trim_end_matches('>');
+        let (name, chunk) = CHUNKS.iter().find(|(n, _)| *n == name).ok_or(format!("Unknown shader chunk <{}>", name))?;
+        if !included.contains(name) {
+          included.push(name);
+          if *name == "clipping" { output.push_str(&format!("#define MAX_CLIP_PLANES {}\n", renderer::Clipping::MAX_EQUATIONS)); }
+          resolve(chunk, included, output)?;
+        }
+      },
+      None => {
+        output.push_str(line);
+        output.push('\n');
+      },
+    }
+  }
+  Ok(())
+}
+
+const CLIPPING_VERTEX: &str = r#"
+     out highp vec3 v_clipPosition;
+"#;
+
+const CLIPPING: &str = r#"
+     in highp vec3 v_clipPosition;
+
+     uniform highp vec4 u_clipPlanes[MAX_CLIP_PLANES];
+     uniform int u_nClipPlanes;
+
+     // Discard the fragments on the negative side of any clipping plane
+     void clip()
+     {
+       for (int i = 0; i < MAX_CLIP_PLANES; i++) {
+         if (i >= u_nClipPlanes) { break; }
+         if (dot(u_clipPlanes[i].xyz, v_clipPosition) + u_clipPlanes[i].w < 0.0) { discard; }
+       }
+     }
+"#;
+
+const TRANSFORMS: &str = r#"
+     #ifdef INSTANCED
+     in mat4 a_instanceMatrix;
+     in mat3 a_instanceNormalMatrix;
+     #endif
+
+     // Model position in world coordinates
+     vec4 worldPosition(vec4 position)
+     {
+     #ifdef INSTANCED
+       return a_instanceMatrix*position;
+     #else
+       return position;
+     #endif
+     }
+
+     // Model normal in world coordinates
+     vec3 worldNormal(vec3 normal)
+     {
+     #ifdef INSTANCED
+       return a_instanceNormalMatrix*normal;
+     #else
+       return normal;
+     #endif
+     }
+"#;
+
+const SHADOWS: &str = r#"
+     #ifndef MAX_PCF_RADIUS
+     #define MAX_PCF_RADIUS 3
+     #endif
+
+     in highp vec4 vShadowCoord;
+
+     uniform highp sampler2D u_shadowMap;
+     uniform float u_shadowTexel;
+     uniform float u_shadowBias;
+     uniform float u_pcfRadius;
+
+     // Fraction of the percentage-closer filtering kernel lit by the shadow casting light,
+     // for a surface whose normal makes an angle of cosine `cosTheta` with the light
+     float shadowVisibility(float cosTheta)
+     {
+       highp vec3 coord = vShadowCoord.xyz/vShadowCoord.w*0.5 + 0.5;
+       if (coord.x < 0.0 || coord.x > 1.0 || coord.y < 0.0 || coord.y > 1.0 || coord.z > 1.0) { return 1.0; }
+       highp float bias = u_shadowBias*(1.0 + 2.0*(1.0 - clamp(cosTheta, 0.0, 1.0)));
+       float lit = 0.0;
+       float n = 0.0;
+       for (int i = -MAX_PCF_RADIUS; i <= MAX_PCF_RADIUS; i++) {
+         for (int j = -MAX_PCF_RADIUS; j <= MAX_PCF_RADIUS; j++) {
+           if (abs(float(i)) > u_pcfRadius || abs(float(j)) > u_pcfRadius) { continue; }
+           highp float depth = texture(u_shadowMap, coord.xy + vec2(float(i), float(j))*u_shadowTexel).r;
+           lit += coord.z - bias > depth ? 0.0 : 1.0;
+           n += 1.0;
+         }
+       }
+       return lit/n;
+     }
+"#;
+
+const LIGHTING: &str = r#"
+     #include <shadows>
+
+     #define MAX_DIRECTIONAL_LIGHTS 4
+     #define MAX_POINT_LIGHTS 4
+
+     uniform float u_shininess;
+     uniform float u_specular;
+
+     uniform vec3 u_ambient;
+     uniform int u_nDirectional;
+     uniform vec3 u_directionalDirection[MAX_DIRECTIONAL_LIGHTS];
+     uniform vec3 u_directionalColor[MAX_DIRECTIONAL_LIGHTS];
+     uniform int u_nPoint;
+     uniform vec3 u_pointPosition[MAX_POINT_LIGHTS];
+     uniform vec3 u_pointColor[MAX_POINT_LIGHTS];
+     uniform float u_pointRange[MAX_POINT_LIGHTS];
+
+     // Index of the directional light casting shadows, -1 if none
+     uniform int u_shadowLight;
+
+     // Blinn-Phong contribution of a light of colour `c` from direction `l`, viewed from direction `v`
+     vec3 blinnPhong(vec3 n, vec3 l, vec3 v, vec3 c, vec3 albedo)
+     {
+       float diffuse = max(dot(n, l), 0.0);
+       float specular = 0.0;
+       if (diffuse > 0.0) {
+         specular = pow(max(dot(n, normalize(l + v)), 0.0), u_shininess);
+       }
+       return c*(diffuse*albedo + u_specular*specular);
+     }
+
+     // Colour of a surface of colour `albedo` with the unit `normal` at `position`, in view coordinates
+     vec3 shade(vec3 albedo, vec3 normal, vec3 position)
+     {
+       vec3 view = normalize(-position);
+       vec3 rgb = u_ambient*albedo;
+       for (int i = 0; i < MAX_DIRECTIONAL_LIGHTS; i++) {
+         if (i >= u_nDirectional) { break; }
+         vec3 l = normalize(u_directionalDirection[i]);
+         float visibility = i == u_shadowLight ? shadowVisibility(dot(normal, l)) : 1.0;
+         rgb += visibility*blinnPhong(normal, l, view, u_directionalColor[i], albedo);
+       }
+       for (int i = 0; i < MAX_POINT_LIGHTS; i++) {
+         if (i >= u_nPoint) { break; }
+         vec3 d = u_pointPosition[i] - position;
+         float dist = length(d);
+         float attenuation = 1.0;
+         if (u_pointRange[i] > 0.0) {
+           attenuation = clamp(1.0 - dist/u_pointRange[i], 0.0, 1.0);
+           attenuation *= attenuation;
+         }
+         rgb += attenuation*blinnPhong(normal, d/max(dist, 1e-6), view, u_pointColor[i], albedo);
+       }
+       return rgb;
+     }
+"#;
+
+const COLORMAP: &str = r#"
+     uniform sampler2D u_colormap;
+     // Number of bands, 0 for a continuous gradient
+     uniform float u_bands;
+
+     // Colour of the value `t` in [0, 1] through the colormap
+     vec3 colormap(float t)
+     {
+       if (u_bands > 0.0) { t = (min(floor(t*u_bands), u_bands - 1.0) + 0.5)/u_bands; }
+       return texture(u_colormap, vec2((t*(LUT_SIZE - 1.0) + 0.5)/LUT_SIZE, 0.5)).rgb;
+     }
+"#;
+
+const PICKING: &str = r#"
+     // Colour encoding the picking `id` in base 255
+     highp vec4 idColor(highp uint id)
+     {
+       highp vec4 digits;
+       for (int i = 0; i < 4; i++) {
+         digits[i] = float(id % 255u);
+         id /= 255u;
+       }
+       return digits/255.0;
+     }
+"#;
+
+const FXAA: &str = r#"
+     #define FXAA_REDUCE_MIN (1.0/128.0)
+     #define FXAA_REDUCE_MUL (1.0/8.0)
+     #define FXAA_SPAN_MAX 8.0
+
+     // Fast approximate anti-aliasing (Lottes, 2009), blending along the local luminance edge direction
+     vec4 fxaa(vec2 uv)
+     {
+       vec3 luma = vec3(0.299, 0.587, 0.114);
+       float lumaNW = dot(texture(u_texture, uv + vec2(-1.0, -1.0)*u_texel).rgb, luma);
+       float lumaNE = dot(texture(u_texture, uv + vec2( 1.0, -1.0)*u_texel).rgb, luma);
+       float lumaSW = dot(texture(u_texture, uv + vec2(-1.0,  1.0)*u_texel).rgb, luma);
+       float lumaSE = dot(texture(u_texture, uv + vec2( 1.0,  1.0)*u_texel).rgb, luma);
+       float lumaM  = dot(texture(u_texture, uv).rgb, luma);
+       float lumaMin = min(lumaM, min(min(lumaNW, lumaNE), min(lumaSW, lumaSE)));
+       float lumaMax = max(lumaM, max(max(lumaNW, lumaNE), max(lumaSW, lumaSE)));
+
+       vec2 dir = vec2(-((lumaNW + lumaNE) - (lumaSW + lumaSE)), (lumaNW + lumaSW) - (lumaNE + lumaSE));
+       float dirReduce = max((lumaNW + lumaNE + lumaSW + lumaSE)*0.25*FXAA_REDUCE_MUL, FXAA_REDUCE_MIN);
+       float rcpDirMin = 1.0/(min(abs(dir.x), abs(dir.y)) + dirReduce);
+       dir = clamp(dir*rcpDirMin, vec2(-FXAA_SPAN_MAX), vec2(FXAA_SPAN_MAX))*u_texel;
+
+       vec4 rgbaA = 0.5*(texture(u_texture, uv + dir*(1.0/3.0 - 0.5)) + texture(u_texture, uv + dir*(2.0/3.0 - 0.5)));
+       vec4 rgbaB = 0.5*rgbaA + 0.25*(texture(u_texture, uv - 0.5*dir) + texture(u_texture, uv + 0.5*dir));
+       float lumaB = dot(rgbaB.rgb, luma);
+       return (lumaB < lumaMin || lumaB > lumaMax) ? rgbaA : rgbaB;
+     }
+"#;
+
+#[cfg(all(test, feature = "wasm"))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn version_precedes_the_defines() {
+    let source = preprocess("void main() {}", &[("INSTANCED", "1".to_string())]).unwrap();
+    assert_eq!(source, "#version 300 es\n#define INSTANCED 1\nvoid main() {}\n");
+  }
+
+  #[test]
+  fn chunks_are_included_once() {
+    let source = preprocess("#include <picking>\n  #include <picking>\n", &[]).unwrap();
+    assert_eq!(source.matches("vec4 idColor(").count(), 1);
+    assert!(!source.contains("#include"));
+  }
+
+  #[test]
+  fn clipping_defines_max_clip_planes() {
+    let source = preprocess("#include <clipping>", &[]).unwrap();
+    let define = format!("#define MAX_CLIP_PLANES {}\n", renderer::Clipping::MAX_EQUATIONS);
+    assert_eq!(source.matches(&define).count(), 1);
+    assert!(source.find(&define) < source.find("u_clipPlanes[MAX_CLIP_PLANES]"));
+    assert!(!preprocess("#include <picking>", &[]).unwrap().contains("MAX_CLIP_PLANES"));
+  }
+
+  #[test]
+  fn unknown_chunk_is_rejected() {
+    let error = preprocess("#include <unknown>", &[]).unwrap_err();
+    assert_eq!(error.inner().to_string(), "Unknown shader chunk <unknown>");
+  }
+}
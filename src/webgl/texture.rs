@@ -0,0 +1,179 @@
+use super::*;
+
+pub mod texturebuilder;
+
+/// Filter applied when a texture is magnified or minified
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub enum TextureFilter {
+  /// Closest texel
+  Nearest = 0,
+  /// Weighted average of the 4 closest texels
+  #[default]
+  Linear = 1,
+}
+
+/// Texture lookup outside of the `[0, 1]` range of texture coordinates
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub enum TextureWrap {
+  /// Texture repeated
+  #[default]
+  Repeat = 0,
+  /// Edge texels extended
+  ClampToEdge = 1,
+  /// Texture repeated, mirrored every other time
+  MirroredRepeat = 2,
+}
+
+impl TextureFilter {
+  /// WebGL filter, using mipmaps if `mipmap`
+  pub fn gl_filter(&self, mipmap: bool) -> u32 {
+    match (self, mipmap) {
+      (TextureFilter::Nearest, false) => web_sys::WebGl2RenderingContext::NEAREST,
+      (TextureFilter::Linear, false) => web_sys::WebGl2RenderingContext::LINEAR,
+      (TextureFilter::Nearest, true) => web_sys::WebGl2RenderingContext::NEAREST_MIPMAP_NEAREST,
+      (TextureFilter::Linear, true) => web_sys::WebGl2RenderingContext::LINEAR_MIPMAP_LINEAR,
+    }
+  }
+}
+
+impl TextureWrap {
+  /// WebGL wrap mode
+  pub fn gl_wrap(&self) -> u32 {
+    match self {
+      TextureWrap::Repeat => web_sys::WebGl2RenderingContext::REPEAT,
+      TextureWrap::ClampToEdge => web_sys::WebGl2RenderingContext::CLAMP_TO_EDGE,
+      TextureWrap::MirroredRepeat => web_sys::WebGl2RenderingContext::MIRRORED_REPEAT,
+    }
+  }
+}
+
+/// Pixels of a texture
+pub enum TextureSource {
+  /// RGBA bytes, row by row from the top row
+  Rgba { width: u32, height: u32, data: Vec<u8> },
+  /// HTML image, uploaded once loaded
+  Image(web_sys::HtmlImageElement),
+}
+
+/// Content of a `Texture`, shared by its copies
+pub struct TextureData {
+  uid:         String,
+  source:      TextureSource,
+  filter:      TextureFilter,
+  wrap:        (TextureWrap, TextureWrap),
+  mipmap:      bool,
+  transparent: bool,
+}
+
+/// Image mapped onto triangles by their texture coordinates. Copies share the pixels and the GPU texture,
+/// which is released by the `Renderer` once no copy remains
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[derive(Clone)]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(into = "TextureJson", try_from = "TextureJson")]
+pub struct Texture {
+  data: std::rc::Rc<TextureData>,
+}
+
+impl Texture {
+  /// Identifier shared by the copies of the texture
+  pub fn uid(&self) -> &str { &self.data.uid }
+
+  /// Pixels
+  pub fn source(&self) -> &TextureSource { &self.data.source }
+
+  /// Magnification and minification filter
+  pub fn filter(&self) -> TextureFilter { self.data.filter }
+
+  /// Wrap modes along the `u` and `v` texture coordinates
+  pub fn wrap(&self) -> (TextureWrap, TextureWrap) { self.data.wrap }
+
+  /// Whether mipmaps are generated for minification
+  pub fn mipmap(&self) -> bool { self.data.mipmap }
+
+  /// Whether the texture has transparent pixels
+  pub fn is_transparent(&self) -> bool { self.data.transparent }
+
+  /// Weak reference to the shared content, no longer valid once all copies are dropped
+  pub fn downgrade(&self) -> std::rc::Weak<TextureData> { std::rc::Rc::downgrade(&self.data) }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+impl Texture {
+  /// Retrieve the texture width in pixels. 0 while the image is loading
+  pub fn width(&self) -> u32 {
+    match &self.data.source {
+      TextureSource::Rgba { width, .. } => *width,
+      TextureSource::Image(image) => image.natural_width(),
+    }
+  }
+
+  /// Retrieve the texture height in pixels. 0 while the image is loading
+  pub fn height(&self) -> u32 {
+    match &self.data.source {
+      TextureSource::Rgba { height, .. } => *height,
+      TextureSource::Image(image) => image.natural_height(),
+    }
+  }
+
+  /// Number of copies sharing the texture
+  pub fn ref_count(&self) -> usize { std::rc::Rc::strong_count(&self.data) }
+
+  /// Expose clone functionality to JavaScript. The copy shares the texture
+  pub fn clone(&self) -> Texture { Clone::clone(self) }
+}
+
+/// Serialized form of a `Texture`. Images are serialized by their source URL
+#[derive(serde::Serialize, serde::Deserialize)]
+struct TextureJson {
+  #[serde(default)]
+  width:       u32,
+  #[serde(default)]
+  height:      u32,
+  #[serde(default)]
+  data:        Vec<u8>,
+  #[serde(default)]
+  src:         Option<String>,
+  #[serde(default)]
+  filter:      TextureFilter,
+  #[serde(default)]
+  wrap:        (TextureWrap, TextureWrap),
+  #[serde(default)]
+  mipmap:      Option<bool>,
+  #[serde(default)]
+  transparent: bool,
+}
+
+impl From<Texture> for TextureJson {
+  fn from(texture: Texture) -> TextureJson {
+    let (width, height, data, src) = match texture.source() {
+      TextureSource::Rgba { width, height, data } => (*width, *height, data.clone(), None),
+      TextureSource::Image(image) => (0, 0, Vec::new(), Some(image.src())),
+    };
+    TextureJson { width, height, data, src, filter: texture.filter(), wrap: texture.wrap(), mipmap: Some(texture.mipmap()), transparent: texture.is_transparent() }
+  }
+}
+
+impl TryFrom<TextureJson> for Texture {
+  type Error = String;
+  fn try_from(json: TextureJson) -> Result<Texture, String> {
+    let builder = match json.src {
+      Some(src) => {
+        let image = web_sys::HtmlImageElement::new().map_err(|e| format!("{e:?}"))?;
+        image.set_cross_origin(Some("anonymous"));
+        image.set_src(&src);
+        texturebuilder::TextureBuilder::new().image(image).transparent(json.transparent)
+      },
+      None => texturebuilder::TextureBuilder::new().rgba(json.width, json.height, json.data),
+    };
+    let builder = builder.filter(json.filter).wrap(json.wrap.0, json.wrap.1);
+    match json.mipmap {
+      Some(mipmap) => builder.mipmap(mipmap),
+      None => builder,
+    }.build_texture()
+  }
+}
@@ -0,0 +1,72 @@
+use super::*;
+
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[derive(Default)]
+/// Builder pattern for `Texture` struct. The pixels are required, other parameters take their default value
+pub struct TextureBuilder {
+  source: Option<TextureSource>,
+  filter: Option<TextureFilter>,
+  wrap: Option<(TextureWrap, TextureWrap)>,
+  mipmap: Option<bool>,
+  transparent: Option<bool>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+impl TextureBuilder {
+  /// Create an empty `TextureBuilder`
+  pub fn new() -> TextureBuilder { TextureBuilder::default() }
+
+  /// Specify the pixels as RGBA bytes, row by row from the top row
+  pub fn rgba(mut self, width: u32, height: u32, data: Vec<u8>) -> TextureBuilder {
+    self.source = Some(TextureSource::Rgba { width, height, data }); self
+  }
+
+  /// Specify the pixels as an HTML image. The texture is uploaded once the image is loaded
+  pub fn image(mut self, image: web_sys::HtmlImageElement) -> TextureBuilder {
+    self.source = Some(TextureSource::Image(image)); self
+  }
+
+  /// Specify the magnification and minification filter. Linear by default
+  pub fn filter(mut self, filter: TextureFilter) -> TextureBuilder { self.filter = Some(filter); self }
+
+  /// Specify the wrap modes along the `u` and `v` texture coordinates. Repeat by default
+  pub fn wrap(mut self, wrap_u: TextureWrap, wrap_v: TextureWrap) -> TextureBuilder { self.wrap = Some((wrap_u, wrap_v)); self }
+
+  /// Specify whether mipmaps are generated for minification. Enabled by default
+  pub fn mipmap(mut self, mipmap: bool) -> TextureBuilder { self.mipmap = Some(mipmap); self }
+
+  /// Specify whether an image has transparent pixels, so that it is drawn with the transparent elements.
+  /// Found from the pixels for RGBA bytes
+  pub fn transparent(mut self, transparent: bool) -> TextureBuilder { self.transparent = Some(transparent); self }
+
+  /// Build a `Texture` object
+  pub fn build(self) -> Result<Texture, JsError> {
+    Ok(self.build_texture()?)
+  }
+}
+
+impl TextureBuilder {
+  /// Build a `Texture` object, with the error as a string
+  pub fn build_texture(self) -> Result<Texture, String> {
+    let source = self.source.ok_or("Texture pixels are not specified")?;
+    let transparent = match &source {
+      TextureSource::Rgba { width, height, data } => {
+        if *width == 0 || *height == 0 { return Err("Texture size must not be null".into()); }
+        let expected = 4 * *width as usize * *height as usize;
+        if data.len() != expected { return Err(format!("Texture of {width}x{height} pixels requires {expected} bytes")); }
+        data.chunks_exact(4).any(|p| p[3] < 255)
+      },
+      TextureSource::Image(_) => self.transparent.unwrap_or(false),
+    };
+    Ok(Texture {
+      data: std::rc::Rc::new(TextureData {
+        uid: nanoid::nanoid!(6),
+        source,
+        filter: self.filter.unwrap_or_default(),
+        wrap: self.wrap.unwrap_or_default(),
+        mipmap: self.mipmap.unwrap_or(true),
+        transparent,
+      })
+    })
+  }
+}
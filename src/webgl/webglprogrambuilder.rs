@@ -6,6 +6,28 @@ pub struct WebGlProgramBuilder<'a> {
   context: Option<&'a web_sys::WebGl2RenderingContext>,
   vertex_shader_source: Option<&'a str>,
   fragment_shader_source: Option<&'a str>,
+  defines: Vec<(&'a str, String)>,
+  cache: Option<(&'a ProgramCache, &'a str)>,
+}
+
+/// Programs compiled by `WebGlProgramBuilder`, keyed by the name of the program and the defines of the variant.
+/// Clones share the programs
+#[cfg(feature = "wasm")]
+#[derive(Clone, Default)]
+pub struct ProgramCache {
+  programs: std::rc::Rc<std::cell::RefCell<std::collections::HashMap<String, web_sys::WebGlProgram>>>,
+}
+
+#[cfg(feature = "wasm")]
+impl ProgramCache {
+  /// Number of programs compiled
+  pub fn len(&self) -> usize { self.programs.borrow().len() }
+
+  /// Whether no program is compiled
+  pub fn is_empty(&self) -> bool { self.programs.borrow().is_empty() }
+
+  /// Forget the programs, compiled again on next use
+  pub fn clear(&self) { self.programs.borrow_mut().clear(); }
 }
 
 #[cfg(feature = "wasm")]
@@ -16,6 +38,8 @@ impl<'a> WebGlProgramBuilder<'a> {
       context: None,
       vertex_shader_source: None,
       fragment_shader_source: None,
+      defines: Vec::new(),
+      cache: None,
     }
   }
 
@@ -28,20 +52,38 @@ impl<'a> WebGlProgramBuilder<'a> {
   /// Specify the fragment shader source
   pub fn fragment_shader_source(mut self, fragment_shader_source: &'a str) -> WebGlProgramBuilder<'a> { self.fragment_shader_source = Some(fragment_shader_source); self }
 
-  /// Build the program
+  /// Define the feature flag `name` in both shaders
+  pub fn define(mut self, name: &'a str) -> WebGlProgramBuilder<'a> { self.defines.push((name, String::new())); self }
+
+  /// Define `name` as `value` in both shaders
+  pub fn define_value(mut self, name: &'a str, value: &str) -> WebGlProgramBuilder<'a> { self.defines.push((name, value.to_string())); self }
+
+  /// Retrieve the program from `cache`, where it is stored under `name` and its defines once compiled
+  pub fn cache(mut self, cache: &'a ProgramCache, name: &'a str) -> WebGlProgramBuilder<'a> { self.cache = Some((cache, name)); self }
+
+  /// Build the program. The shaders are GLSL ES 3.00 sources, without version directive, whose `#include <name>`
+  /// directives are replaced by the chunks of the shader library
   pub fn build(self) -> Result<web_sys::WebGlProgram, Error> {
     let context = self.context.ok_or("WebGl rendering context not specified")?;
+    let key = self.cache.map(|(_, name)| self.defines.iter().fold(name.to_string(), |key, (n, v)| format!("{key}|{n}={v}")));
+    if let (Some((cache, _)), Some(key)) = (self.cache, key.as_ref()) {
+      if let Some(program) = cache.programs.borrow().get(key) { return Ok(program.clone()); }
+    }
+
     let vertex_shader = self.compile_shader(context, web_sys::WebGl2RenderingContext::VERTEX_SHADER,
-                              self.vertex_shader_source.ok_or("Vertex shader source not specified")?)?;
+                              &shaderlibrary::preprocess(self.vertex_shader_source.ok_or("Vertex shader source not specified")?, &self.defines)?)?;
 
     let fragment_shader = self.compile_shader(context, web_sys::WebGl2RenderingContext::FRAGMENT_SHADER,
-                              self.fragment_shader_source.ok_or("Fragment shader source not specified")?)?;
+                              &shaderlibrary::preprocess(self.fragment_shader_source.ok_or("Fragment shader source not specified")?, &self.defines)?)?;
 
     let program = context.create_program().ok_or("Unable to create program")?;
     context.attach_shader(&program, &vertex_shader);
     context.attach_shader(&program, &fragment_shader);
     context.link_program(&program);
     if context.get_program_parameter(&program, web_sys::WebGl2RenderingContext::LINK_STATUS).as_bool().unwrap_or(false) {
+      if let (Some((cache, _)), Some(key)) = (self.cache, key) {
+        cache.programs.borrow_mut().insert(key, program.clone());
+      }
       Ok(program)
     } else {
       Err(context.get_program_info_log(&program).unwrap_or(String::from("Error in linking program")).into())